@@ -1,7 +1,13 @@
-use crate::parser::expression::{Expression, Visitor as ExpressionVisitor};
-use crate::parser::statement::{Statement, Visitor as StatementVisitor};
+use crate::parser::annotation::TypeAnnotation;
+use crate::parser::expression::{Expression, Literal, PropertyKey, Visitor as ExpressionVisitor};
+use crate::parser::pattern::{MatchPattern, Pattern};
+use crate::parser::statement::{
+    Access, Binding, ForKind, MethodKind, ModuleSpecifier, Parameter, Statement,
+    Visitor as StatementVisitor,
+};
 use crate::parser::token::Token;
 
+#[allow(clippy::large_enum_variant, clippy::vec_box)]
 enum OneOf {
     Expression(Expression),
     Statement(Statement),
@@ -12,25 +18,25 @@ enum OneOf {
 
 impl From<Expression> for OneOf {
     fn from(expression: Expression) -> Self {
-        return OneOf::Expression(expression);
+        OneOf::Expression(expression)
     }
 }
 
 impl From<Statement> for OneOf {
     fn from(statement: Statement) -> Self {
-        return OneOf::Statement(statement);
+        OneOf::Statement(statement)
     }
 }
 
 impl From<Token> for OneOf {
     fn from(token: Token) -> Self {
-        return OneOf::Token(token);
+        OneOf::Token(token)
     }
 }
 
 impl From<Vec<Box<OneOf>>> for OneOf {
     fn from(vec: Vec<Box<OneOf>>) -> Self {
-        return OneOf::Vec(vec);
+        OneOf::Vec(vec)
     }
 }
 
@@ -42,13 +48,13 @@ impl From<Vec<Expression>> for OneOf {
             boxed_vec.push(Box::new(OneOf::Expression(expr)));
         }
 
-        return OneOf::Vec(boxed_vec);
+        OneOf::Vec(boxed_vec)
     }
 }
 
 impl From<String> for OneOf {
     fn from(string: String) -> Self {
-        return OneOf::String(string);
+        OneOf::String(string)
     }
 }
 
@@ -56,25 +62,25 @@ pub struct AstPrinter;
 
 impl AstPrinter {
     pub fn print_expression(&mut self, expression: &Expression) -> String {
-        return expression.accept(self);
+        expression.accept(self)
     }
 
     pub fn print_statement(&mut self, statement: &Statement) -> String {
-        return statement.accept(self);
+        statement.accept(self)
     }
 
     fn parenthesize(&mut self, name: &str, exprs: &[Expression]) -> String {
         let mut builder = String::new();
-        builder.push_str("(");
+        builder.push('(');
         builder.push_str(name);
 
         for expr in exprs {
-            builder.push_str(" ");
+            builder.push(' ');
             builder.push_str(&expr.accept(self));
         }
 
-        builder.push_str(")");
-        return builder;
+        builder.push(')');
+        builder
     }
 
     fn parenthesize2(&mut self, name: &str, parts: &[Box<OneOf>]) -> String {
@@ -83,13 +89,186 @@ impl AstPrinter {
 
         self.transform(&mut builder, parts);
 
-        builder.push_str(")");
-        return builder;
+        builder.push(')');
+        builder
+    }
+
+    fn parameters(&mut self, params: &[Parameter]) -> String {
+        params
+            .iter()
+            .map(|param| {
+                let mut builder = String::new();
+                if param.rest {
+                    builder.push_str("...");
+                }
+                let target = self.pattern(&param.target);
+                builder.push_str(&annotated(target, &param.annotation));
+                if let Some(default) = &param.default {
+                    builder.push('=');
+                    builder.push_str(&default.accept(self));
+                }
+                builder
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// The `: R` after a parameter list, if there is a return type.
+    fn return_type(&self, return_type: &Option<TypeAnnotation>) -> String {
+        match return_type {
+            Some(annotation) => format!(": {}", annotation),
+            None => String::new(),
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) -> String {
+        let mut parts = Vec::new();
+        let (open, close, rest) = match pattern {
+            Pattern::Identifier { name } => return name.lexeme.clone(),
+            Pattern::Member { expression } => return self.print_expression(expression),
+            Pattern::Array {
+                bracket: _,
+                elements,
+                rest,
+            } => {
+                for element in elements {
+                    parts.push(match element {
+                        Some(element) => {
+                            self.pattern_part(String::new(), &element.target, &element.default)
+                        }
+                        None => String::new(),
+                    });
+                }
+                ("[", "]", rest)
+            }
+            Pattern::Object {
+                brace: _,
+                properties,
+                rest,
+            } => {
+                for property in properties {
+                    let key = match &property.key {
+                        PropertyKey::Named { name, token: _ } => format!("{}: ", name),
+                        PropertyKey::Computed(key) => {
+                            format!("[{}]: ", self.print_expression(key))
+                        }
+                    };
+                    parts.push(self.pattern_part(key, &property.target, &property.default));
+                }
+                ("{", "}", rest)
+            }
+        };
+        if let Some(rest) = rest {
+            parts.push(format!("...{}", self.pattern(rest)));
+        }
+        format!("{}{}{}", open, parts.join(", "), close)
+    }
+
+    fn match_pattern(&mut self, pattern: &MatchPattern) -> String {
+        match pattern {
+            MatchPattern::Wildcard { token: _ } => String::from("_"),
+            MatchPattern::Literal { token: _, value } => match value {
+                Some(Literal::String(value)) => format!("{:?}", value),
+                Some(value) => value.to_string(),
+                None => String::from("nil"),
+            },
+            MatchPattern::Binding { name } => name.lexeme.clone(),
+            MatchPattern::Array {
+                bracket: _,
+                elements,
+                rest,
+            } => {
+                let mut parts: Vec<String> = elements
+                    .iter()
+                    .map(|element| self.match_pattern(element))
+                    .collect();
+                if let Some(rest) = rest {
+                    parts.push(format!("...{}", self.match_pattern(rest)));
+                }
+                format!("[{}]", parts.join(", "))
+            }
+            MatchPattern::Object {
+                brace: _,
+                properties,
+            } => {
+                let parts: Vec<String> = properties
+                    .iter()
+                    .map(|property| {
+                        format!(
+                            "{}: {}",
+                            property.name,
+                            self.match_pattern(&property.pattern)
+                        )
+                    })
+                    .collect();
+                format!("{{{}}}", parts.join(", "))
+            }
+            MatchPattern::Variant {
+                enumeration,
+                name,
+                fields,
+            } => {
+                let mut builder = format!("{}.{}", self.print_expression(enumeration), name.lexeme);
+                if let Some(fields) = fields {
+                    let fields: Vec<String> = fields
+                        .iter()
+                        .map(|field| self.match_pattern(field))
+                        .collect();
+                    builder.push_str(&format!("({})", fields.join(", ")));
+                }
+                builder
+            }
+            MatchPattern::Or { patterns } => {
+                let patterns: Vec<String> = patterns
+                    .iter()
+                    .map(|pattern| self.match_pattern(pattern))
+                    .collect();
+                patterns.join(" | ")
+            }
+        }
+    }
+
+    fn pattern_part(
+        &mut self,
+        prefix: String,
+        target: &Pattern,
+        default: &Option<Expression>,
+    ) -> String {
+        let mut builder = prefix;
+        builder.push_str(&self.pattern(target));
+        if let Some(default) = default {
+            builder.push_str(&format!(" = {}", self.print_expression(default)));
+        }
+        builder
+    }
+
+    fn specifiers(&self, specifiers: &[ModuleSpecifier]) -> String {
+        let specifiers = specifiers
+            .iter()
+            .map(
+                |specifier| match specifier.name.lexeme == specifier.alias.lexeme {
+                    true => specifier.name.lexeme.clone(),
+                    false => format!("{} as {}", specifier.name.lexeme, specifier.alias.lexeme),
+                },
+            )
+            .collect::<Vec<String>>();
+        format!("{{{}}}", specifiers.join(", "))
+    }
+
+    fn modifiers(&self, access: Access, is_static: bool) -> &'static str {
+        match (access, is_static) {
+            (Access::Public, false) => "",
+            (Access::Public, true) => "static ",
+            (Access::Private, false) => "private ",
+            (Access::Private, true) => "private static ",
+            (Access::Protected, false) => "protected ",
+            (Access::Protected, true) => "protected static ",
+        }
     }
 
     fn transform(&mut self, builder: &mut String, parts: &[Box<OneOf>]) {
         for part in parts {
-            builder.push_str(" ");
+            builder.push(' ');
 
             match part.as_ref() {
                 OneOf::Expression(expression) => {
@@ -113,6 +292,27 @@ impl AstPrinter {
 }
 
 impl ExpressionVisitor<String> for AstPrinter {
+    fn visit_array_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Array {
+                bracket: _,
+                elements,
+            } => {
+                let elements = elements
+                    .iter()
+                    .map(|element| match element {
+                        Some(element) => self.print_expression(element),
+                        None => String::from("<hole>"),
+                    })
+                    .collect::<Vec<String>>();
+                format!("(array {})", elements.join(" "))
+            }
+            _ => {
+                panic!("Expected Array expression");
+            }
+        }
+    }
+
     fn visit_assign_expression(&mut self, expr: &Expression) -> String {
         match expr {
             Expression::Assign { name, value } => self.parenthesize2(
@@ -128,6 +328,39 @@ impl ExpressionVisitor<String> for AstPrinter {
         }
     }
 
+    fn visit_assign_pattern_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::AssignPattern {
+                target,
+                equals: _,
+                value,
+            } => {
+                let target = self.pattern(target);
+                self.parenthesize2(
+                    "assign",
+                    &[
+                        Box::new(Into::<OneOf>::into(target)),
+                        Box::new(Into::<OneOf>::into(value.as_ref().to_owned())),
+                    ],
+                )
+            }
+            _ => {
+                panic!("Expected AssignPattern expression");
+            }
+        }
+    }
+
+    fn visit_await_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Await { keyword: _, value } => {
+                self.parenthesize("await", &[value.as_ref().to_owned()])
+            }
+            _ => {
+                panic!("Expected Await expression");
+            }
+        }
+    }
+
     fn visit_binary_expression(&mut self, expr: &Expression) -> String {
         match expr {
             Expression::Binary {
@@ -150,8 +383,9 @@ impl ExpressionVisitor<String> for AstPrinter {
                 callee,
                 paren: _,
                 arguments,
+                optional,
             } => self.parenthesize2(
-                "call",
+                if *optional { "call?." } else { "call" },
                 &[
                     Box::new(Into::<OneOf>::into(callee.as_ref().to_owned())),
                     Box::new(Into::<OneOf>::into(arguments.to_owned())),
@@ -163,10 +397,57 @@ impl ExpressionVisitor<String> for AstPrinter {
         }
     }
 
+    fn visit_function_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Function {
+                name,
+                params,
+                return_type,
+                body,
+                arrow,
+                is_generator,
+                is_async,
+            } => {
+                let mut builder = String::from("(");
+                if *is_async {
+                    builder.push_str("async ");
+                }
+                if *arrow {
+                    builder.push_str("=> (");
+                } else {
+                    builder.push_str(if *is_generator { "fun* " } else { "fun " });
+                    if let Some(name) = name {
+                        builder.push_str(&name.lexeme);
+                    }
+                    builder.push('(');
+                }
+
+                builder.push_str(&self.parameters(params));
+                builder.push(')');
+                builder.push_str(&self.return_type(return_type));
+                builder.push(' ');
+
+                for statement in body {
+                    builder.push_str(&statement.accept(self));
+                }
+
+                builder.push(')');
+                builder
+            }
+            _ => {
+                panic!("Expected Function expression");
+            }
+        }
+    }
+
     fn visit_get_expression(&mut self, expr: &Expression) -> String {
         match expr {
-            Expression::Get { object, name } => self.parenthesize2(
-                "get",
+            Expression::Get {
+                object,
+                name,
+                optional,
+            } => self.parenthesize2(
+                if *optional { "get?." } else { "get" },
                 &[
                     Box::new(Into::<OneOf>::into(object.as_ref().to_owned())),
                     Box::new(Into::<OneOf>::into(name.lexeme.clone())),
@@ -189,6 +470,23 @@ impl ExpressionVisitor<String> for AstPrinter {
         }
     }
 
+    fn visit_index_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Index {
+                object,
+                bracket: _,
+                index,
+                optional,
+            } => self.parenthesize(
+                if *optional { "index?." } else { "index" },
+                &[object.as_ref().to_owned(), index.as_ref().to_owned()],
+            ),
+            _ => {
+                panic!("Expected Index expression");
+            }
+        }
+    }
+
     fn visit_literal_expression(&mut self, expr: &Expression) -> String {
         match expr {
             Expression::Literal { value } => match value {
@@ -217,6 +515,110 @@ impl ExpressionVisitor<String> for AstPrinter {
         }
     }
 
+    fn visit_match_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Match {
+                keyword: _,
+                subject,
+                arms,
+            } => {
+                let mut builder = format!("(match {}", self.print_expression(subject));
+                for arm in arms {
+                    builder.push_str(&format!(" (arm {}", self.match_pattern(&arm.pattern)));
+                    if let Some(guard) = &arm.guard {
+                        builder.push_str(&format!(" if {}", self.print_expression(guard)));
+                    }
+                    builder.push_str(&format!(" {})", self.print_expression(&arm.body)));
+                }
+                builder.push(')');
+                builder
+            }
+            _ => {
+                panic!("Expected Match expression");
+            }
+        }
+    }
+
+    fn visit_new_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::New {
+                keyword: _,
+                callee,
+                arguments,
+            } => self.parenthesize2(
+                "new",
+                &[
+                    Box::new(Into::<OneOf>::into(callee.as_ref().to_owned())),
+                    Box::new(Into::<OneOf>::into(arguments.to_owned())),
+                ],
+            ),
+            _ => {
+                panic!("Expected New expression");
+            }
+        }
+    }
+
+    fn visit_object_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Object {
+                brace: _,
+                properties,
+            } => {
+                let mut builder = String::new();
+                builder.push_str("(object");
+
+                for property in properties {
+                    let key = match &property.key {
+                        PropertyKey::Named { name, token: _ } => name.clone(),
+                        PropertyKey::Computed(key) => format!("[{}]", self.print_expression(key)),
+                    };
+                    builder.push_str(&format!(
+                        " ({} {})",
+                        key,
+                        self.print_expression(&property.value)
+                    ));
+                }
+
+                builder.push(')');
+                builder
+            }
+            _ => {
+                panic!("Expected Object expression");
+            }
+        }
+    }
+
+    fn visit_optional_chain_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::OptionalChain { expression } => {
+                self.parenthesize("chain", &[expression.as_ref().to_owned()])
+            }
+            _ => {
+                panic!("Expected OptionalChain expression");
+            }
+        }
+    }
+
+    fn visit_record_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Record { hash: _, object } => {
+                self.parenthesize("record", &[object.as_ref().to_owned()])
+            }
+            _ => {
+                panic!("Expected Record expression");
+            }
+        }
+    }
+
+    fn visit_regex_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Regex { token, regex: _ } => token.lexeme.clone(),
+            _ => {
+                panic!("Expected Regex expression");
+            }
+        }
+    }
+
     fn visit_set_expression(&mut self, expr: &Expression) -> String {
         match expr {
             Expression::Set {
@@ -237,6 +639,71 @@ impl ExpressionVisitor<String> for AstPrinter {
         }
     }
 
+    fn visit_set_index_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::SetIndex {
+                object,
+                bracket: _,
+                index,
+                value,
+            } => self.parenthesize(
+                "index=",
+                &[
+                    object.as_ref().to_owned(),
+                    index.as_ref().to_owned(),
+                    value.as_ref().to_owned(),
+                ],
+            ),
+            _ => {
+                panic!("Expected SetIndex expression");
+            }
+        }
+    }
+
+    fn visit_spread_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Spread {
+                ellipsis: _,
+                expression,
+            } => self.parenthesize("...", &[expression.as_ref().to_owned()]),
+            _ => {
+                panic!("Expected Spread expression");
+            }
+        }
+    }
+
+    fn visit_super_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Super { keyword: _, method } => match method {
+                Some(method) => format!("(super {})", method.lexeme),
+                None => String::from("super"),
+            },
+            _ => {
+                panic!("Expected Super expression");
+            }
+        }
+    }
+
+    fn visit_this_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::This { keyword: _ } => String::from("this"),
+            _ => {
+                panic!("Expected This expression");
+            }
+        }
+    }
+
+    fn visit_tuple_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Tuple { hash: _, array } => {
+                self.parenthesize("tuple", &[array.as_ref().to_owned()])
+            }
+            _ => {
+                panic!("Expected Tuple expression");
+            }
+        }
+    }
+
     fn visit_unary_expression(&mut self, expr: &Expression) -> String {
         match expr {
             Expression::Unary { operator, right } => {
@@ -256,6 +723,25 @@ impl ExpressionVisitor<String> for AstPrinter {
             }
         }
     }
+
+    fn visit_yield_expression(&mut self, expr: &Expression) -> String {
+        match expr {
+            Expression::Yield {
+                keyword: _,
+                value,
+                delegate,
+            } => {
+                let name = if *delegate { "yield*" } else { "yield" };
+                match value {
+                    Some(value) => self.parenthesize(name, &[value.as_ref().to_owned()]),
+                    None => format!("({})", name),
+                }
+            }
+            _ => {
+                panic!("Expected Yield expression");
+            }
+        }
+    }
 }
 
 impl StatementVisitor<String> for AstPrinter {
@@ -269,8 +755,8 @@ impl StatementVisitor<String> for AstPrinter {
                     builder.push_str(&self.print_statement(statement));
                 }
 
-                builder.push_str(")");
-                return builder;
+                builder.push(')');
+                builder
             }
             _ => {
                 panic!("Expected block statement");
@@ -278,10 +764,196 @@ impl StatementVisitor<String> for AstPrinter {
         }
     }
 
+    fn visit_break_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Break { keyword: _, label } => match label {
+                Some(label) => format!("(break {})", label.lexeme),
+                None => String::from("(break)"),
+            },
+            _ => {
+                panic!("Expected break statement");
+            }
+        }
+    }
+
+    fn visit_class_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Class {
+                name,
+                superclass,
+                methods,
+                fields,
+            } => {
+                let mut builder = String::new();
+                builder.push_str(format!("(class {}", name.lexeme).as_str());
+                if let Some(superclass) = superclass {
+                    builder.push_str(&format!(" < {}", self.print_expression(superclass)));
+                }
+
+                for field in fields {
+                    builder.push_str(" (field ");
+                    builder.push_str(self.modifiers(field.access, field.is_static));
+                    builder.push_str(&annotated(field.name.lexeme.clone(), &field.annotation));
+                    if let Some(initializer) = &field.initializer {
+                        builder.push_str(&format!(" = {}", self.print_expression(initializer)));
+                    }
+                    builder.push(')');
+                }
+
+                for method in methods {
+                    builder.push_str(" (");
+                    builder.push_str(self.modifiers(method.access, method.is_static));
+                    match method.kind {
+                        MethodKind::Getter => builder.push_str("get "),
+                        MethodKind::Setter => builder.push_str("set "),
+                        MethodKind::Method => {}
+                    }
+                    if method.is_async {
+                        builder.push_str("async ");
+                    }
+                    if method.is_generator {
+                        builder.push('*');
+                    }
+                    match &method.computed {
+                        Some(computed) => {
+                            let computed = self.print_expression(computed);
+                            builder.push_str(&format!("[{}](", computed));
+                        }
+                        None => builder.push_str(&format!("{}(", method.name.lexeme)),
+                    }
+                    builder.push_str(&self.parameters(&method.params));
+                    builder.push(')');
+                    builder.push_str(&self.return_type(&method.return_type));
+                    builder.push(' ');
+
+                    for body in &method.body {
+                        builder.push_str(&body.accept(self));
+                    }
+                    builder.push(')');
+                }
+
+                builder.push(')');
+                builder
+            }
+            _ => {
+                panic!("Expected class statement");
+            }
+        }
+    }
+
+    fn visit_continue_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Continue { keyword: _, label } => match label {
+                Some(label) => format!("(continue {})", label.lexeme),
+                None => String::from("(continue)"),
+            },
+            _ => {
+                panic!("Expected continue statement");
+            }
+        }
+    }
+
+    fn visit_do_while_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::DoWhile {
+                body,
+                condition,
+                label,
+            } => {
+                let name = match label {
+                    Some(label) => format!("{}: do", label.lexeme),
+                    None => String::from("do"),
+                };
+                self.parenthesize2(
+                    &name,
+                    &[
+                        Box::new(Into::<OneOf>::into(body.as_ref().to_owned())),
+                        Box::new(Into::<OneOf>::into(condition.to_owned())),
+                    ],
+                )
+            }
+            _ => {
+                panic!("Expected do-while statement");
+            }
+        }
+    }
+
+    fn visit_enum_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Enum { name, variants } => {
+                let mut builder = format!("(enum {}", name.lexeme);
+                for variant in variants {
+                    match &variant.fields {
+                        Some(fields) => {
+                            builder.push_str(&format!(" ({}", variant.name.lexeme));
+                            for field in fields {
+                                builder.push_str(&format!(" {}", field.lexeme));
+                            }
+                            builder.push(')');
+                        }
+                        None => builder.push_str(&format!(" {}", variant.name.lexeme)),
+                    }
+                }
+                builder.push(')');
+                builder
+            }
+            _ => {
+                panic!("Expected enum statement");
+            }
+        }
+    }
+
+    fn visit_export_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Export {
+                keyword: _,
+                declaration,
+                default,
+            } => {
+                let name = match default {
+                    true => "export default",
+                    false => "export",
+                };
+                self.parenthesize2(
+                    name,
+                    &[Box::new(Into::<OneOf>::into(
+                        declaration.as_ref().to_owned(),
+                    ))],
+                )
+            }
+            _ => {
+                panic!("Expected export statement");
+            }
+        }
+    }
+
+    fn visit_export_default_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::ExportDefault { keyword: _, value } => {
+                self.parenthesize("export default", std::slice::from_ref(value))
+            }
+            _ => {
+                panic!("Expected export default statement");
+            }
+        }
+    }
+
+    fn visit_export_names_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::ExportNames {
+                keyword: _,
+                specifiers,
+            } => format!("(export {})", self.specifiers(specifiers)),
+            _ => {
+                panic!("Expected export statement");
+            }
+        }
+    }
+
     fn visit_expression_statement(&mut self, stmt: &Statement) -> String {
         match stmt {
             Statement::Expression { expression } => {
-                return self.parenthesize(";", &[expression.clone()]);
+                self.parenthesize(";", std::slice::from_ref(expression))
             }
             _ => {
                 panic!("Expected expression statement");
@@ -289,27 +961,72 @@ impl StatementVisitor<String> for AstPrinter {
         }
     }
 
-    fn visit_function_statement(&mut self, stmt: &Statement) -> String {
+    fn visit_for_each_statement(&mut self, stmt: &Statement) -> String {
         match stmt {
-            Statement::Function { name, params, body } => {
-                let mut builder = String::new();
-                builder.push_str(format!("(fun {}(", name.lexeme).as_str());
+            Statement::ForEach {
+                keyword: _,
+                kind,
+                binding,
+                target,
+                iterable,
+                body,
+                label,
+            } => {
+                let mut name = match label {
+                    Some(label) => format!("{}: for", label.lexeme),
+                    None => String::from("for"),
+                };
+                name.push_str(match binding {
+                    Binding::Let => " let ",
+                    Binding::Const => " const ",
+                    Binding::Assign => " ",
+                });
+                name.push_str(&self.pattern(target));
+                name.push_str(match kind {
+                    ForKind::In => " in",
+                    ForKind::Of => " of",
+                });
+                self.parenthesize2(
+                    &name,
+                    &[
+                        Box::new(Into::<OneOf>::into(iterable.to_owned())),
+                        Box::new(Into::<OneOf>::into(body.as_ref().to_owned())),
+                    ],
+                )
+            }
+            _ => {
+                panic!("Expected for-each statement");
+            }
+        }
+    }
 
-                for param in params {
-                    if param != &params[0] {
-                        builder.push_str(" ");
-                    }
-                    builder.push_str(param.lexeme.as_str());
+    fn visit_function_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Function {
+                name,
+                params,
+                return_type,
+                body,
+                is_generator,
+                is_async,
+            } => {
+                let mut builder = String::from("(");
+                if *is_async {
+                    builder.push_str("async ");
                 }
-
-                builder.push_str(") ");
+                let keyword = if *is_generator { "fun*" } else { "fun" };
+                builder.push_str(format!("{} {}(", keyword, name.lexeme).as_str());
+                builder.push_str(&self.parameters(params));
+                builder.push(')');
+                builder.push_str(&self.return_type(return_type));
+                builder.push(' ');
 
                 for body in body {
                     builder.push_str(&body.accept(self));
                 }
 
-                builder.push_str(")");
-                return builder;
+                builder.push(')');
+                builder
             }
             _ => {
                 panic!("Expected function statement");
@@ -325,7 +1042,7 @@ impl StatementVisitor<String> for AstPrinter {
                 else_branch,
             } => match else_branch {
                 Some(else_branch) => {
-                    return self.parenthesize2(
+                    self.parenthesize2(
                         "if",
                         &[
                             // it's so bad XD
@@ -333,17 +1050,15 @@ impl StatementVisitor<String> for AstPrinter {
                             Box::new(Into::<OneOf>::into(then_branch.as_ref().to_owned())),
                             Box::new(Into::<OneOf>::into(else_branch.as_ref().to_owned())),
                         ],
-                    );
-                }
-                None => {
-                    return self.parenthesize2(
-                        "if",
-                        &[
-                            Box::new(Into::<OneOf>::into(condition.to_owned())),
-                            Box::new(Into::<OneOf>::into(then_branch.as_ref().to_owned())),
-                        ],
-                    );
+                    )
                 }
+                None => self.parenthesize2(
+                    "if",
+                    &[
+                        Box::new(Into::<OneOf>::into(condition.to_owned())),
+                        Box::new(Into::<OneOf>::into(then_branch.as_ref().to_owned())),
+                    ],
+                ),
             },
             _ => {
                 panic!("Expected if statement");
@@ -351,10 +1066,78 @@ impl StatementVisitor<String> for AstPrinter {
         }
     }
 
+    fn visit_import_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Import {
+                keyword: _,
+                source,
+                path: _,
+                default,
+                namespace,
+                specifiers,
+            } => {
+                let mut builder = String::from("(import");
+                if let Some(default) = default {
+                    builder.push_str(&format!(" {}", default.lexeme));
+                }
+                if let Some(namespace) = namespace {
+                    builder.push_str(&format!(" * as {}", namespace.lexeme));
+                }
+                if !specifiers.is_empty() {
+                    builder.push_str(&format!(" {}", self.specifiers(specifiers)));
+                }
+                builder.push_str(&format!(" from {})", source.lexeme));
+                builder
+            }
+            _ => {
+                panic!("Expected import statement");
+            }
+        }
+    }
+
+    fn visit_interface_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Interface {
+                name,
+                extends,
+                members,
+            } => {
+                let mut builder = format!("(interface {}", name.lexeme);
+                for extended in extends {
+                    builder.push_str(&format!(" < {}", extended.lexeme));
+                }
+                for member in members {
+                    let optional = if member.optional { "?" } else { "" };
+                    builder.push_str(&format!(
+                        " ({}{}: {})",
+                        member.name.lexeme, optional, member.annotation
+                    ));
+                }
+                builder.push(')');
+                builder
+            }
+            _ => {
+                panic!("Expected interface statement");
+            }
+        }
+    }
+
+    fn visit_labeled_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Labeled { label, body } => self.parenthesize2(
+                &format!("{}:", label.lexeme),
+                &[Box::new(Into::<OneOf>::into(body.as_ref().to_owned()))],
+            ),
+            _ => {
+                panic!("Expected labeled statement");
+            }
+        }
+    }
+
     fn visit_print_statement(&mut self, stmt: &Statement) -> String {
         match stmt {
             Statement::Print { expression } => {
-                return self.parenthesize("print", &[expression.clone()]);
+                self.parenthesize("print", std::slice::from_ref(expression))
             }
             _ => {
                 panic!("Expected print statement");
@@ -368,7 +1151,7 @@ impl StatementVisitor<String> for AstPrinter {
                 if value.is_some() {
                     return self.parenthesize("return", &[value.clone().unwrap()]);
                 }
-                return self.parenthesize("return", &[]);
+                self.parenthesize("return", &[])
             }
             _ => {
                 panic!("Expected return statement");
@@ -378,12 +1161,17 @@ impl StatementVisitor<String> for AstPrinter {
 
     fn visit_let_statement(&mut self, stmt: &Statement) -> String {
         match stmt {
-            Statement::Let { name, initializer } => {
+            Statement::Let {
+                target,
+                annotation,
+                initializer,
+            } => {
+                let target = annotated(self.pattern(target), annotation);
                 if initializer.is_some() {
                     return self.parenthesize2(
                         "let",
                         &[
-                            Box::new(Into::<OneOf>::into(name.to_owned())),
+                            Box::new(Into::<OneOf>::into(target)),
                             Box::new(Into::<OneOf>::into(String::from("="))),
                             Box::new(Into::<OneOf>::into(
                                 initializer.as_ref().unwrap().to_owned(),
@@ -391,7 +1179,7 @@ impl StatementVisitor<String> for AstPrinter {
                         ],
                     );
                 }
-                return format!("(let {})", name.lexeme);
+                format!("(let {})", target)
             }
             _ => {
                 panic!("Expected let statement");
@@ -401,12 +1189,17 @@ impl StatementVisitor<String> for AstPrinter {
 
     fn visit_const_statement(&mut self, stmt: &Statement) -> String {
         match stmt {
-            Statement::Const { name, initializer } => {
+            Statement::Const {
+                target,
+                annotation,
+                initializer,
+            } => {
+                let target = annotated(self.pattern(target), annotation);
                 if initializer.is_some() {
                     return self.parenthesize2(
                         "const",
                         &[
-                            Box::new(Into::<OneOf>::into(name.to_owned())),
+                            Box::new(Into::<OneOf>::into(target)),
                             Box::new(Into::<OneOf>::into(String::from("="))),
                             Box::new(Into::<OneOf>::into(
                                 initializer.as_ref().unwrap().to_owned(),
@@ -414,7 +1207,7 @@ impl StatementVisitor<String> for AstPrinter {
                         ],
                     );
                 }
-                return format!("(const {})", name.lexeme);
+                format!("(const {})", target)
             }
             _ => {
                 panic!("Expected const statement");
@@ -422,16 +1215,123 @@ impl StatementVisitor<String> for AstPrinter {
         }
     }
 
+    fn visit_switch_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Switch {
+                discriminant,
+                cases,
+            } => {
+                let mut parts = vec![Box::new(Into::<OneOf>::into(discriminant.to_owned()))];
+
+                for case in cases {
+                    let mut builder = String::new();
+                    match &case.test {
+                        Some(test) => {
+                            builder.push_str("(case ");
+                            builder.push_str(&test.accept(self));
+                        }
+                        None => builder.push_str("(default"),
+                    }
+                    for statement in &case.body {
+                        builder.push(' ');
+                        builder.push_str(&statement.accept(self));
+                    }
+                    builder.push(')');
+                    parts.push(Box::new(Into::<OneOf>::into(builder)));
+                }
+
+                self.parenthesize2("switch", &parts)
+            }
+            _ => {
+                panic!("Expected switch statement");
+            }
+        }
+    }
+
+    fn visit_throw_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Throw { keyword: _, value } => {
+                self.parenthesize("throw", std::slice::from_ref(value))
+            }
+            _ => {
+                panic!("Expected throw statement");
+            }
+        }
+    }
+
+    fn visit_try_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::Try {
+                try_block,
+                catch_param,
+                catch_block,
+                finally_block,
+            } => {
+                let mut parts = vec![Box::new(Into::<OneOf>::into(Statement::Block {
+                    statements: try_block.to_owned(),
+                }))];
+
+                if let Some(catch_block) = catch_block {
+                    let mut builder = String::from("(catch");
+                    if let Some(param) = catch_param {
+                        builder.push(' ');
+                        builder.push_str(&param.lexeme);
+                    }
+                    for statement in catch_block {
+                        builder.push(' ');
+                        builder.push_str(&statement.accept(self));
+                    }
+                    builder.push(')');
+                    parts.push(Box::new(Into::<OneOf>::into(builder)));
+                }
+
+                if let Some(finally_block) = finally_block {
+                    parts.push(Box::new(Into::<OneOf>::into(self.parenthesize2(
+                        "finally",
+                        &[Box::new(Into::<OneOf>::into(Statement::Block {
+                            statements: finally_block.to_owned(),
+                        }))],
+                    ))));
+                }
+
+                self.parenthesize2("try", &parts)
+            }
+            _ => {
+                panic!("Expected try statement");
+            }
+        }
+    }
+
+    fn visit_type_alias_statement(&mut self, stmt: &Statement) -> String {
+        match stmt {
+            Statement::TypeAlias { name, value } => format!("(type {} = {})", name.lexeme, value),
+            _ => {
+                panic!("Expected type alias statement");
+            }
+        }
+    }
+
     fn visit_while_statement(&mut self, stmt: &Statement) -> String {
         match stmt {
-            Statement::While { condition, body } => {
-                return self.parenthesize2(
-                    "while",
-                    &[
-                        Box::new(Into::<OneOf>::into(condition.to_owned())),
-                        Box::new(Into::<OneOf>::into(body.as_ref().to_owned())),
-                    ],
-                );
+            Statement::While {
+                condition,
+                body,
+                increment,
+                label,
+            } => {
+                let mut parts = vec![
+                    Box::new(Into::<OneOf>::into(condition.to_owned())),
+                    Box::new(Into::<OneOf>::into(body.as_ref().to_owned())),
+                ];
+                if let Some(increment) = increment {
+                    parts.push(Box::new(Into::<OneOf>::into(increment.to_owned())));
+                }
+
+                let name = match label {
+                    Some(label) => format!("{}: while", label.lexeme),
+                    None => String::from("while"),
+                };
+                self.parenthesize2(&name, &parts)
             }
             _ => {
                 panic!("Expected while statement");
@@ -439,3 +1339,11 @@ impl StatementVisitor<String> for AstPrinter {
         }
     }
 }
+
+/// Appends the `: T` annotation, if any, to a declared name or pattern.
+fn annotated(target: String, annotation: &Option<TypeAnnotation>) -> String {
+    match annotation {
+        Some(annotation) => format!("{}: {}", target, annotation),
+        None => target,
+    }
+}
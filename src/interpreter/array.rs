@@ -0,0 +1,344 @@
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+
+use crate::RuntimeError;
+
+use super::{
+    callable::{Callable, NativeMethodFn},
+    iterator,
+    object::{Object, ObjectKind},
+    symbol::ITERATOR,
+    value::Value,
+    Interpreter, Unwind,
+};
+
+pub const METHODS: &[(&str, usize, Option<usize>, NativeMethodFn)] = &[
+    ("filter", 1, Some(1), filter),
+    ("forEach", 1, Some(1), for_each),
+    ("indexOf", 1, Some(2), index_of),
+    ("join", 0, Some(1), join),
+    ("map", 1, Some(1), map),
+    ("pop", 0, Some(0), pop),
+    ("push", 0, None, push),
+    ("reduce", 1, Some(2), reduce),
+    ("slice", 0, Some(2), slice),
+    ("sort", 0, Some(1), sort),
+    (ITERATOR, 0, Some(0), iterator::iterator),
+];
+
+/// Parses a property key that names an element, such as `"2"` but not
+/// `"02"` or `"-1"`.
+pub fn index(key: &str) -> Option<usize> {
+    key.parse::<usize>()
+        .ok()
+        .filter(|index| index.to_string() == key)
+}
+
+fn with_elements<T>(receiver: &Value, f: impl FnOnce(&mut Vec<Value>) -> T) -> T {
+    match receiver {
+        Value::Object(object) => match &mut object.borrow_mut().kind {
+            ObjectKind::Array(elements) => f(elements),
+            _ => panic!("Expected array receiver"),
+        },
+        _ => panic!("Expected array receiver"),
+    }
+}
+
+fn elements(receiver: &Value) -> Vec<Value> {
+    with_elements(receiver, |elements| elements.clone())
+}
+
+fn callback(interpreter: &Interpreter, value: &Value) -> Result<Rc<dyn Callable>, RuntimeError> {
+    match value {
+        Value::Callable(callback) => Ok(callback.clone()),
+        _ => Err(RuntimeError::new(
+            interpreter.call_site(),
+            &format!("{} is not a function.", value),
+        )),
+    }
+}
+
+fn call_back(
+    interpreter: &mut Interpreter,
+    callback: &Rc<dyn Callable>,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let paren = interpreter.call_site();
+    interpreter.call_back(&paren, callback.clone(), arguments)
+}
+
+/// Resolves a `slice` style position, where negative numbers count from the
+/// end, into an index clamped to `0..=len`.
+fn position(
+    interpreter: &Interpreter,
+    value: Option<&Value>,
+    len: usize,
+    default: usize,
+) -> Result<usize, RuntimeError> {
+    match value {
+        None | Some(Value::Nil) => Ok(default),
+        Some(Value::Number(position)) => {
+            let position = position.trunc();
+            Ok(if position < 0.0 {
+                (len as f64 + position).max(0.0) as usize
+            } else {
+                position.min(len as f64) as usize
+            })
+        }
+        Some(value) => Err(RuntimeError::new(
+            interpreter.call_site(),
+            &format!("Expected a number but got {}.", value.type_name()),
+        )),
+    }
+}
+
+fn filter(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let predicate = callback(interpreter, &arguments[0])?;
+    let mut kept = Vec::new();
+    for (index, element) in elements(receiver).into_iter().enumerate() {
+        let arguments = vec![element.clone(), Value::from(index as f64), receiver.clone()];
+        let result = call_back(interpreter, &predicate, arguments)?;
+        if interpreter.is_truthy(&result) {
+            kept.push(element);
+        }
+    }
+    Ok(Value::array(kept))
+}
+
+fn for_each(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let action = callback(interpreter, &arguments[0])?;
+    for (index, element) in elements(receiver).into_iter().enumerate() {
+        let arguments = vec![element, Value::from(index as f64), receiver.clone()];
+        call_back(interpreter, &action, arguments)?;
+    }
+    Ok(Value::Nil)
+}
+
+fn index_of(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let elements = elements(receiver);
+    let start = position(interpreter, arguments.get(1), elements.len(), 0)?;
+    let found = elements[start..]
+        .iter()
+        .position(|element| interpreter.is_equal(element, &arguments[0]));
+    Ok(Value::from(match found {
+        Some(found) => (start + found) as f64,
+        None => -1.0,
+    }))
+}
+
+fn join(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let separator = match arguments.first() {
+        None | Some(Value::Nil) => String::from(","),
+        Some(separator) => separator.to_string(),
+    };
+    Ok(Value::from(join_elements(
+        receiver,
+        &separator,
+        &mut Vec::new(),
+    )))
+}
+
+/// Joins the elements of `array` with `separator`. Nested arrays are joined
+/// with commas in turn, and `nil` and an array that contains itself become
+/// empty. `seen` holds the arrays being joined.
+fn join_elements(array: &Value, separator: &str, seen: &mut Vec<*const RefCell<Object>>) -> String {
+    let Value::Object(object) = array else {
+        return array.to_string();
+    };
+    seen.push(Rc::as_ptr(object));
+    let parts = elements(array)
+        .iter()
+        .map(|element| match element {
+            Value::Nil => String::new(),
+            Value::Object(nested) if seen.contains(&Rc::as_ptr(nested)) => String::new(),
+            Value::Object(nested) if matches!(nested.borrow().kind, ObjectKind::Array(_)) => {
+                join_elements(element, ",", seen)
+            }
+            element => element.to_string(),
+        })
+        .collect::<Vec<String>>();
+    seen.pop();
+    parts.join(separator)
+}
+
+fn map(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let transform = callback(interpreter, &arguments[0])?;
+    let mut mapped = Vec::new();
+    for (index, element) in elements(receiver).into_iter().enumerate() {
+        let arguments = vec![element, Value::from(index as f64), receiver.clone()];
+        mapped.push(call_back(interpreter, &transform, arguments)?);
+    }
+    Ok(Value::array(mapped))
+}
+
+fn pop(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    _arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    Ok(with_elements(receiver, |elements| {
+        elements.pop().unwrap_or(Value::Nil)
+    }))
+}
+
+fn push(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    Ok(with_elements(receiver, |elements| {
+        elements.extend(arguments);
+        Value::from(elements.len() as f64)
+    }))
+}
+
+fn reduce(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let reducer = callback(interpreter, &arguments[0])?;
+    let mut elements = elements(receiver).into_iter().enumerate();
+
+    let mut accumulator = match arguments.get(1) {
+        Some(initial) => initial.clone(),
+        None => match elements.next() {
+            Some((_, first)) => first,
+            None => {
+                return Err(RuntimeError::new(
+                    interpreter.call_site(),
+                    "Reduce of empty array with no initial value.",
+                )
+                .into())
+            }
+        },
+    };
+
+    for (index, element) in elements {
+        let arguments = vec![
+            accumulator,
+            element,
+            Value::from(index as f64),
+            receiver.clone(),
+        ];
+        accumulator = call_back(interpreter, &reducer, arguments)?;
+    }
+    Ok(accumulator)
+}
+
+fn slice(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let elements = elements(receiver);
+    let start = position(interpreter, arguments.first(), elements.len(), 0)?;
+    let end = position(
+        interpreter,
+        arguments.get(1),
+        elements.len(),
+        elements.len(),
+    )?;
+    Ok(Value::array(match start < end {
+        true => elements[start..end].to_vec(),
+        false => Vec::new(),
+    }))
+}
+
+fn sort(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let compare = match arguments.first() {
+        None | Some(Value::Nil) => None,
+        Some(compare) => Some(callback(interpreter, compare)?),
+    };
+
+    let sorted = merge_sort(interpreter, elements(receiver), &compare)?;
+    with_elements(receiver, |elements| *elements = sorted);
+    Ok(receiver.clone())
+}
+
+/// A stable sort that, unlike `slice::sort_by`, lets the comparator fail.
+fn merge_sort(
+    interpreter: &mut Interpreter,
+    mut values: Vec<Value>,
+    compare: &Option<Rc<dyn Callable>>,
+) -> Result<Vec<Value>, Unwind> {
+    if values.len() <= 1 {
+        return Ok(values);
+    }
+
+    let right = values.split_off(values.len() / 2);
+    let left = merge_sort(interpreter, values, compare)?;
+    let right = merge_sort(interpreter, right, compare)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        if order(interpreter, compare, &right[j], &left[i])? == Ordering::Less {
+            merged.push(right[j].clone());
+            j += 1;
+        } else {
+            merged.push(left[i].clone());
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&left[i..]);
+    merged.extend_from_slice(&right[j..]);
+    Ok(merged)
+}
+
+/// Without a comparator elements sort by their string form, with nil last.
+fn order(
+    interpreter: &mut Interpreter,
+    compare: &Option<Rc<dyn Callable>>,
+    left: &Value,
+    right: &Value,
+) -> Result<Ordering, Unwind> {
+    match (left, right) {
+        (Value::Nil, Value::Nil) => return Ok(Ordering::Equal),
+        (Value::Nil, _) => return Ok(Ordering::Greater),
+        (_, Value::Nil) => return Ok(Ordering::Less),
+        _ => {}
+    }
+
+    let compare = match compare {
+        Some(compare) => compare,
+        None => return Ok(left.to_string().cmp(&right.to_string())),
+    };
+
+    match call_back(interpreter, compare, vec![left.clone(), right.clone()])? {
+        Value::Number(result) if result < 0.0 => Ok(Ordering::Less),
+        Value::Number(result) if result > 0.0 => Ok(Ordering::Greater),
+        Value::Number(_) => Ok(Ordering::Equal),
+        result => Err(RuntimeError::new(
+            interpreter.call_site(),
+            &format!(
+                "Comparator must return a number but got {}.",
+                result.type_name()
+            ),
+        )
+        .into()),
+    }
+}
@@ -0,0 +1,345 @@
+use std::{cmp::Ordering, fmt};
+
+/// An integer of any size, such as `123n`. It is kept as a sign and a
+/// magnitude in base 2^32, least significant digit first.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    /// Never ends in a zero digit, so zero has no digits at all.
+    digits: Vec<u32>,
+}
+
+/// Why an operation on big integers has no result.
+pub enum Failure {
+    DivisionByZero,
+    NegativeExponent,
+    TooLarge,
+}
+
+impl Failure {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Failure::DivisionByZero => "Division by zero.",
+            Failure::NegativeExponent => "Exponent must be non-negative.",
+            Failure::TooLarge => "Maximum BigInt size exceeded.",
+        }
+    }
+}
+
+impl BigInt {
+    fn new(negative: bool, digits: Vec<u32>) -> BigInt {
+        let digits = trim(digits);
+        BigInt {
+            negative: negative && !digits.is_empty(),
+            digits,
+        }
+    }
+
+    pub fn from_u64(value: u64) -> BigInt {
+        BigInt::new(false, vec![value as u32, (value >> 32) as u32])
+    }
+
+    /// Parses a decimal integer with an optional sign, as `BigInt("12")`
+    /// does. Surrounding whitespace is ignored.
+    pub fn parse(text: &str) -> Option<BigInt> {
+        let text = text.trim();
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|digit| digit.is_ascii_digit()) {
+            return None;
+        }
+
+        // Nine decimal digits at a time always fit in one digit.
+        let mut magnitude = Vec::new();
+        for chunk in digits.as_bytes().chunks(9) {
+            let chunk = std::str::from_utf8(chunk).ok()?;
+            let value: u32 = chunk.parse().ok()?;
+            magnitude = mul_small(&magnitude, 10u32.pow(chunk.len() as u32));
+            magnitude = add_magnitudes(&magnitude, &[value]);
+        }
+        Some(BigInt::new(negative, magnitude))
+    }
+
+    /// Converts a number with no fractional part.
+    pub fn from_f64(value: f64) -> Option<BigInt> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return None;
+        }
+        let bits = value.abs().to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        if exponent == 0 {
+            return Some(BigInt::new(false, Vec::new()));
+        }
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let shift = exponent - 1075;
+        let magnitude = match shift >= 0 {
+            true => shift_left(&BigInt::from_u64(mantissa).digits, shift as usize),
+            false => BigInt::from_u64(mantissa >> -shift).digits,
+        };
+        Some(BigInt::new(value < 0.0, magnitude))
+    }
+
+    /// The nearest number, which loses precision past 2^53.
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0.0, |total, digit| total * 4294967296.0 + *digit as f64);
+        match self.negative {
+            true => -magnitude,
+            false => magnitude,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn negate(&self) -> BigInt {
+        BigInt::new(!self.negative, self.digits.clone())
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitudes(&self.digits, &other.digits));
+        }
+        match compare_magnitudes(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::new(
+                other.negative,
+                subtract_magnitudes(&other.digits, &self.digits),
+            ),
+            _ => BigInt::new(
+                self.negative,
+                subtract_magnitudes(&self.digits, &other.digits),
+            ),
+        }
+    }
+
+    pub fn subtract(&self, other: &BigInt) -> BigInt {
+        self.add(&other.negate())
+    }
+
+    pub fn multiply(&self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            multiply_magnitudes(&self.digits, &other.digits),
+        )
+    }
+
+    /// Division that rounds towards zero, as `/` does for big integers.
+    pub fn divide(&self, other: &BigInt) -> Result<BigInt, Failure> {
+        let (quotient, _) = self.divide_with_remainder(other)?;
+        Ok(quotient)
+    }
+
+    /// The remainder of [`BigInt::divide`], which has the sign of `self`.
+    pub fn remainder(&self, other: &BigInt) -> Result<BigInt, Failure> {
+        let (_, remainder) = self.divide_with_remainder(other)?;
+        Ok(remainder)
+    }
+
+    fn divide_with_remainder(&self, other: &BigInt) -> Result<(BigInt, BigInt), Failure> {
+        if other.is_zero() {
+            return Err(Failure::DivisionByZero);
+        }
+        let (quotient, remainder) = divide_magnitudes(&self.digits, &other.digits);
+        Ok((
+            BigInt::new(self.negative != other.negative, quotient),
+            BigInt::new(self.negative, remainder),
+        ))
+    }
+
+    pub fn pow(&self, exponent: &BigInt) -> Result<BigInt, Failure> {
+        if exponent.negative {
+            return Err(Failure::NegativeExponent);
+        }
+        // Zero, one and minus one stay small whatever the exponent.
+        if self.digits.len() <= 1 && self.digits.first().copied().unwrap_or(0) <= 1 {
+            let odd = exponent.digits.first().is_some_and(|digit| digit % 2 == 1);
+            return Ok(match exponent.is_zero() {
+                true => BigInt::from_u64(1),
+                false => BigInt::new(self.negative && odd, self.digits.clone()),
+            });
+        }
+        if exponent.digits.len() > 1 {
+            return Err(Failure::TooLarge);
+        }
+
+        let mut exponent = exponent.digits.first().copied().unwrap_or(0);
+        let mut base = self.clone();
+        let mut result = BigInt::from_u64(1);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.multiply(&base);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.multiply(&base);
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.digits, &other.digits),
+            (true, true) => compare_magnitudes(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // Peel off nine decimal digits at a time, least significant first.
+        let mut chunks = Vec::new();
+        let mut magnitude = self.digits.clone();
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = divide_small(&magnitude, 1_000_000_000);
+            chunks.push(remainder);
+            magnitude = quotient;
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+fn trim(mut digits: Vec<u32>) -> Vec<u32> {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    digits
+}
+
+fn compare_magnitudes(left: &[u32], right: &[u32]) -> Ordering {
+    left.len()
+        .cmp(&right.len())
+        .then_with(|| left.iter().rev().cmp(right.iter().rev()))
+}
+
+fn add_magnitudes(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(left.len().max(right.len()) + 1);
+    let mut carry = 0u64;
+    for index in 0..left.len().max(right.len()) {
+        let total = carry
+            + left.get(index).copied().unwrap_or(0) as u64
+            + right.get(index).copied().unwrap_or(0) as u64;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    trim(sum)
+}
+
+/// `left - right`, where `left` is the larger.
+fn subtract_magnitudes(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(left.len());
+    let mut borrow = 0i64;
+    for (index, digit) in left.iter().enumerate() {
+        let mut total = *digit as i64 - borrow - right.get(index).copied().unwrap_or(0) as i64;
+        borrow = 0;
+        if total < 0 {
+            total += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    trim(difference)
+}
+
+fn multiply_magnitudes(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; left.len() + right.len()];
+    for (i, a) in left.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, b) in right.iter().enumerate() {
+            let total = product[i + j] as u64 + *a as u64 * *b as u64 + carry;
+            product[i + j] = total as u32;
+            carry = total >> 32;
+        }
+        product[i + right.len()] = carry as u32;
+    }
+    trim(product)
+}
+
+fn mul_small(magnitude: &[u32], factor: u32) -> Vec<u32> {
+    multiply_magnitudes(magnitude, &[factor])
+}
+
+fn divide_small(magnitude: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; magnitude.len()];
+    let mut remainder = 0u64;
+    for (index, digit) in magnitude.iter().enumerate().rev() {
+        let current = (remainder << 32) | *digit as u64;
+        quotient[index] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    (trim(quotient), remainder as u32)
+}
+
+/// Long division, one bit at a time. `divisor` must not be zero.
+fn divide_magnitudes(dividend: &[u32], divisor: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if divisor.len() == 1 {
+        let (quotient, remainder) = divide_small(dividend, divisor[0]);
+        return (quotient, trim(vec![remainder]));
+    }
+
+    let mut quotient = vec![0u32; dividend.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for bit in (0..dividend.len() * 32).rev() {
+        remainder = shift_left(&remainder, 1);
+        if dividend[bit / 32] >> (bit % 32) & 1 == 1 {
+            match remainder.first_mut() {
+                Some(digit) => *digit |= 1,
+                None => remainder.push(1),
+            }
+        }
+        if compare_magnitudes(&remainder, divisor) != Ordering::Less {
+            remainder = subtract_magnitudes(&remainder, divisor);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (trim(quotient), remainder)
+}
+
+fn shift_left(magnitude: &[u32], bits: usize) -> Vec<u32> {
+    let mut shifted = vec![0u32; bits / 32];
+    let offset = bits % 32;
+    let mut carry = 0u32;
+    for digit in magnitude {
+        match offset {
+            0 => shifted.push(*digit),
+            _ => {
+                shifted.push((digit << offset) | carry);
+                carry = digit >> (32 - offset);
+            }
+        }
+    }
+    shifted.push(carry);
+    trim(shifted)
+}
@@ -0,0 +1,268 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::parser::{
+    statement::{Parameter, Statement},
+    token::Token,
+};
+
+use super::{environment::Environment, generator, promise, value::Value, Interpreter, Unwind};
+
+pub trait Callable {
+    fn min_arity(&self) -> usize;
+    /// `None` when any number of extra arguments is accepted.
+    fn max_arity(&self) -> Option<usize>;
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind>;
+    fn name(&self) -> String;
+    /// A copy whose `this` is `receiver`, for functions that take `this`
+    /// from the object they are called on rather than where they are
+    /// written.
+    fn with_receiver(&self, _receiver: &Value) -> Option<Rc<dyn Callable>> {
+        None
+    }
+}
+
+pub struct Function {
+    name: Option<Token>,
+    params: Rc<[Parameter]>,
+    body: Rc<[Statement]>,
+    closure: Rc<RefCell<Environment>>,
+    /// Generator functions return a generator instead of running their body.
+    is_generator: bool,
+    /// Async functions run their body until the first `await` and return a
+    /// promise of its result.
+    is_async: bool,
+    /// Methods of object literals have no `this` until they are called on
+    /// an object.
+    is_method: bool,
+}
+
+impl Function {
+    pub fn new(
+        name: Option<Token>,
+        params: Vec<Parameter>,
+        body: Vec<Statement>,
+        closure: Rc<RefCell<Environment>>,
+        is_generator: bool,
+        is_async: bool,
+    ) -> Function {
+        Function {
+            name,
+            params: params.into(),
+            body: body.into(),
+            closure,
+            is_generator,
+            is_async,
+            is_method: false,
+        }
+    }
+
+    /// Makes this a method of an object literal.
+    pub fn into_method(self) -> Function {
+        Function {
+            is_method: true,
+            ..self
+        }
+    }
+
+    /// Returns a copy of this method whose `this` refers to `this`.
+    pub fn bind(&self, this: Value) -> Function {
+        let mut environment = Environment::with_enclosing(self.closure.clone());
+        environment.define("this", this);
+        Function {
+            name: self.name.clone(),
+            params: self.params.clone(),
+            body: self.body.clone(),
+            closure: Rc::new(RefCell::new(environment)),
+            is_generator: self.is_generator,
+            is_async: self.is_async,
+            is_method: false,
+        }
+    }
+}
+
+impl Callable for Function {
+    fn min_arity(&self) -> usize {
+        self.params
+            .iter()
+            .take_while(|param| param.default.is_none() && !param.rest)
+            .count()
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        match self.params.last() {
+            Some(param) if param.rest => None,
+            _ => Some(self.params.len()),
+        }
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+        let environment = Rc::new(RefCell::new(Environment::with_enclosing(
+            self.closure.clone(),
+        )));
+        if self.is_method {
+            environment.borrow_mut().define("this", Value::Nil);
+        }
+
+        let mut arguments = arguments.into_iter();
+        for param in self.params.iter() {
+            if param.rest {
+                let rest = Value::array(arguments.by_ref().collect());
+                interpreter.bind_pattern_in(&param.target, rest, environment.clone())?;
+                break;
+            }
+
+            // Defaults see the parameters before them, so they are evaluated
+            // inside the new environment.
+            let value = match (arguments.next(), &param.default) {
+                (Some(Value::Nil) | None, Some(default)) => {
+                    interpreter.evaluate_in(default, environment.clone())?
+                }
+                (argument, _) => argument.unwrap_or(Value::Nil),
+            };
+            interpreter.bind_pattern_in(&param.target, value, environment.clone())?;
+        }
+
+        if self.is_generator || self.is_async {
+            let body = generator::create(interpreter, self.name(), self.body.clone(), environment)?;
+            return match self.is_async {
+                true => promise::run_async(interpreter, body),
+                false => Ok(body),
+            };
+        }
+
+        match interpreter.execute_block(&self.body, environment) {
+            Ok(()) => Ok(Value::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(unwind) => Err(unwind),
+        }
+    }
+
+    fn name(&self) -> String {
+        match &self.name {
+            Some(name) => name.lexeme.clone(),
+            None => String::from("anonymous"),
+        }
+    }
+
+    fn with_receiver(&self, receiver: &Value) -> Option<Rc<dyn Callable>> {
+        match self.is_method {
+            true => Some(Rc::new(self.bind(receiver.clone()))),
+            false => None,
+        }
+    }
+}
+
+pub type NativeFn = fn(&mut Interpreter, Vec<Value>) -> Result<Value, Unwind>;
+
+pub struct NativeFunction {
+    name: &'static str,
+    min_arity: usize,
+    max_arity: Option<usize>,
+    function: NativeFn,
+}
+
+impl NativeFunction {
+    pub fn new(name: &'static str, arity: usize, function: NativeFn) -> NativeFunction {
+        NativeFunction::with_arity(name, arity, Some(arity), function)
+    }
+
+    pub fn with_arity(
+        name: &'static str,
+        min_arity: usize,
+        max_arity: Option<usize>,
+        function: NativeFn,
+    ) -> NativeFunction {
+        NativeFunction {
+            name,
+            min_arity,
+            max_arity,
+            function,
+        }
+    }
+}
+
+impl Callable for NativeFunction {
+    fn min_arity(&self) -> usize {
+        self.min_arity
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        self.max_arity
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+        (self.function)(interpreter, arguments)
+    }
+
+    fn name(&self) -> String {
+        format!("native {}", self.name)
+    }
+}
+
+pub type NativeMethodFn = fn(&mut Interpreter, &Value, Vec<Value>) -> Result<Value, Unwind>;
+
+/// A native function bound to the value it was read from, like `list.push`.
+pub struct NativeMethod {
+    name: &'static str,
+    min_arity: usize,
+    max_arity: Option<usize>,
+    receiver: Value,
+    function: NativeMethodFn,
+}
+
+impl NativeMethod {
+    pub fn new(
+        name: &'static str,
+        min_arity: usize,
+        max_arity: Option<usize>,
+        receiver: Value,
+        function: NativeMethodFn,
+    ) -> NativeMethod {
+        NativeMethod {
+            name,
+            min_arity,
+            max_arity,
+            receiver,
+            function,
+        }
+    }
+}
+
+/// Looks up a built-in method in `methods`, a table of names, arities and
+/// functions, and binds it to `receiver`.
+pub fn method(
+    methods: &[(&'static str, usize, Option<usize>, NativeMethodFn)],
+    receiver: &Value,
+    name: &str,
+) -> Option<Value> {
+    methods
+        .iter()
+        .find(|(method, _, _, _)| *method == name)
+        .map(|(name, min_arity, max_arity, function)| {
+            Value::Callable(Rc::new(NativeMethod::new(
+                name,
+                *min_arity,
+                *max_arity,
+                receiver.clone(),
+                *function,
+            )))
+        })
+}
+
+impl Callable for NativeMethod {
+    fn min_arity(&self) -> usize {
+        self.min_arity
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        self.max_arity
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+        (self.function)(interpreter, &self.receiver, arguments)
+    }
+
+    fn name(&self) -> String {
+        format!("native {}", self.name)
+    }
+}
@@ -0,0 +1,219 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::parser::{
+    expression::Expression,
+    statement::{Access, MethodKind},
+    token::TokenType,
+};
+
+use super::{
+    callable::Function,
+    environment::Environment,
+    object::{Object, ObjectKind},
+    value::Value,
+};
+
+#[derive(Clone)]
+pub enum Member {
+    Method(Rc<Function>),
+    /// A getter and setter pair sharing a name. Either half may be missing.
+    Accessor {
+        getter: Option<Rc<Function>>,
+        setter: Option<Rc<Function>>,
+    },
+}
+
+/// Where a field is stored. Private fields are keyed by the name their
+/// class was given when it was declared, not by their source name.
+pub enum FieldKey {
+    Public(String),
+    Private(String),
+}
+
+pub struct FieldInit {
+    pub key: FieldKey,
+    pub initializer: Option<Expression>,
+}
+
+pub struct Class {
+    pub name: String,
+    superclass: Option<Rc<RefCell<Object>>>,
+    members: HashMap<String, Member>,
+    statics: HashMap<String, Member>,
+    private_members: HashMap<String, Member>,
+    private_statics: HashMap<String, Member>,
+    /// The modifiers of the named members and fields, which are checked
+    /// whenever they are read or written.
+    access: HashMap<String, Access>,
+    static_access: HashMap<String, Access>,
+    /// Instance fields, initialized in order on every new instance.
+    pub fields: Vec<FieldInit>,
+    /// The scope that methods and field initializers close over.
+    pub environment: Rc<RefCell<Environment>>,
+}
+
+impl Class {
+    pub fn new(
+        name: String,
+        superclass: Option<Rc<RefCell<Object>>>,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Class {
+        Class {
+            name,
+            superclass,
+            members: HashMap::new(),
+            statics: HashMap::new(),
+            private_members: HashMap::new(),
+            private_statics: HashMap::new(),
+            access: HashMap::new(),
+            static_access: HashMap::new(),
+            fields: Vec::new(),
+            environment,
+        }
+    }
+
+    pub fn define(&mut self, name: &str, kind: MethodKind, is_static: bool, function: Function) {
+        let members = match is_static {
+            true => &mut self.statics,
+            false => &mut self.members,
+        };
+        insert_member(members, name, kind, function);
+    }
+
+    pub fn define_private(
+        &mut self,
+        key: &str,
+        kind: MethodKind,
+        is_static: bool,
+        function: Function,
+    ) {
+        let members = match is_static {
+            true => &mut self.private_statics,
+            false => &mut self.private_members,
+        };
+        insert_member(members, key, kind, function);
+    }
+
+    pub fn declare_access(&mut self, name: &str, is_static: bool, access: Access) {
+        let modifiers = match is_static {
+            true => &mut self.static_access,
+            false => &mut self.access,
+        };
+        modifiers.insert(name.to_string(), access);
+    }
+
+    /// The modifier this class itself declares `name` with, if it declares
+    /// it at all.
+    pub fn access(&self, name: &str, is_static: bool) -> Option<Access> {
+        let modifiers = match is_static {
+            true => &self.static_access,
+            false => &self.access,
+        };
+        modifiers.get(name).copied()
+    }
+
+    /// Private methods and accessors. These are copied onto every instance,
+    /// or onto the class itself for static ones, rather than inherited.
+    pub fn private_members(&self, is_static: bool) -> &HashMap<String, Member> {
+        match is_static {
+            true => &self.private_statics,
+            false => &self.private_members,
+        }
+    }
+
+    /// Looks a member up on this class and then along its superclasses.
+    pub fn find(&self, name: &str, is_static: bool) -> Option<Member> {
+        let members = match is_static {
+            true => &self.statics,
+            false => &self.members,
+        };
+        match members.get(name) {
+            Some(member) => Some(member.clone()),
+            None => self.superclass()?.find(name, is_static),
+        }
+    }
+
+    pub fn superclass(&self) -> Option<Rc<Class>> {
+        match &self.superclass.as_ref()?.borrow().kind {
+            ObjectKind::Class(class) => Some(class.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// The class a value refers to, when it is a class rather than an instance.
+pub fn class_of(value: &Value) -> Option<Rc<Class>> {
+    match value {
+        Value::Object(object) => match &object.borrow().kind {
+            ObjectKind::Class(class) => Some(class.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The class of an instance.
+pub fn instance_class(value: &Value) -> Option<Rc<Class>> {
+    match value {
+        Value::Object(object) => match &object.borrow().kind {
+            ObjectKind::Instance(class) => Some(class.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The name of the method that overloads a binary operator, such as
+/// `operator+` for `operator +(other) {}`.
+pub fn operator_method(operator: TokenType) -> Option<&'static str> {
+    Some(match operator {
+        TokenType::Plus => "operator+",
+        TokenType::Minus => "operator-",
+        TokenType::Star => "operator*",
+        TokenType::Slash => "operator/",
+        TokenType::Percent => "operator%",
+        TokenType::StarStar => "operator**",
+        TokenType::EqualEqual => "operator==",
+        TokenType::BangEqual => "operator!=",
+        TokenType::Less => "operator<",
+        TokenType::LessEqual => "operator<=",
+        TokenType::Greater => "operator>",
+        TokenType::GreaterEqual => "operator>=",
+        _ => return None,
+    })
+}
+
+/// Adds a method, or one half of an accessor pair, to `members`.
+fn insert_member(
+    members: &mut HashMap<String, Member>,
+    name: &str,
+    kind: MethodKind,
+    function: Function,
+) {
+    let function = Rc::new(function);
+    if kind == MethodKind::Method {
+        members.insert(name.to_string(), Member::Method(function));
+        return;
+    }
+
+    let member = members
+        .entry(name.to_string())
+        .and_modify(|member| {
+            if let Member::Method(_) = member {
+                *member = Member::Accessor {
+                    getter: None,
+                    setter: None,
+                };
+            }
+        })
+        .or_insert(Member::Accessor {
+            getter: None,
+            setter: None,
+        });
+    if let Member::Accessor { getter, setter } = member {
+        match kind {
+            MethodKind::Getter => *getter = Some(function),
+            _ => *setter = Some(function),
+        }
+    }
+}
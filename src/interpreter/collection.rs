@@ -0,0 +1,542 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+use crate::RuntimeError;
+
+use super::{
+    callable::{Callable, NativeFn, NativeFunction, NativeMethodFn},
+    iterator::{self, Iteration},
+    object::{Object, ObjectKind},
+    symbol::ITERATOR,
+    value::{HashKey, Value},
+    Interpreter, Unwind,
+};
+
+pub const MAP_METHODS: &[(&str, usize, Option<usize>, NativeMethodFn)] = &[
+    ("clear", 0, Some(0), clear),
+    ("delete", 1, Some(1), delete),
+    ("entries", 0, Some(0), entries),
+    ("forEach", 1, Some(1), for_each),
+    ("get", 1, Some(1), get),
+    ("has", 1, Some(1), has),
+    ("keys", 0, Some(0), keys),
+    ("set", 2, Some(2), set),
+    ("values", 0, Some(0), values),
+    (ITERATOR, 0, Some(0), iterator::iterator),
+];
+
+pub const SET_METHODS: &[(&str, usize, Option<usize>, NativeMethodFn)] = &[
+    ("add", 1, Some(1), add),
+    ("clear", 0, Some(0), clear),
+    ("delete", 1, Some(1), delete),
+    ("entries", 0, Some(0), entries),
+    ("forEach", 1, Some(1), for_each),
+    ("has", 1, Some(1), has),
+    ("keys", 0, Some(0), keys),
+    ("values", 0, Some(0), values),
+    (ITERATOR, 0, Some(0), iterator::iterator),
+];
+
+pub const WEAK_MAP_METHODS: &[(&str, usize, Option<usize>, NativeMethodFn)] = &[
+    ("delete", 1, Some(1), weak_delete),
+    ("get", 1, Some(1), weak_get),
+    ("has", 1, Some(1), weak_has),
+    ("set", 2, Some(2), weak_set),
+];
+
+pub const WEAK_SET_METHODS: &[(&str, usize, Option<usize>, NativeMethodFn)] = &[
+    ("add", 1, Some(1), weak_add),
+    ("delete", 1, Some(1), weak_delete),
+    ("has", 1, Some(1), weak_has),
+];
+
+/// The entries of a `Map` or `Set`, in the order they were first added.
+/// A `Set` stores each value as both key and value. Deleting leaves a hole
+/// so that the other entries keep their places, and the holes are swept
+/// away once they outnumber the entries.
+#[derive(Default)]
+pub struct Entries {
+    entries: Vec<Option<(Value, Value)>>,
+    indices: HashMap<HashKey, usize>,
+    /// Where the iterations in progress are, which sweeping moves along
+    /// with the entries.
+    cursors: Vec<Weak<Cell<usize>>>,
+}
+
+impl Entries {
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn get(&self, key: &Value) -> Option<Value> {
+        let index = self.indices.get(&HashKey(key.clone()))?;
+        self.entries[*index]
+            .as_ref()
+            .map(|(_, value)| value.clone())
+    }
+
+    pub fn has(&self, key: &Value) -> bool {
+        self.indices.contains_key(&HashKey(key.clone()))
+    }
+
+    /// Sets the value for `key`, which keeps its place if it is already
+    /// there. `-0` is stored as `0`, as they are the same key.
+    pub fn insert(&mut self, key: Value, value: Value) {
+        let key = match key {
+            // Adding 0 turns -0 into 0 and leaves every other number alone.
+            Value::Number(number) => Value::Number(number + 0.0),
+            key => key,
+        };
+        match self.indices.get(&HashKey(key.clone())) {
+            Some(index) => self.entries[*index] = Some((key, value)),
+            None => {
+                self.indices
+                    .insert(HashKey(key.clone()), self.entries.len());
+                self.entries.push(Some((key, value)));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &Value) -> bool {
+        let Some(index) = self.indices.remove(&HashKey(key.clone())) else {
+            return false;
+        };
+        self.entries[index] = None;
+        if self.entries.len() - self.indices.len() > self.indices.len() {
+            for cursor in self.cursors.iter().filter_map(Weak::upgrade) {
+                let passed = &self.entries[..cursor.get().min(self.entries.len())];
+                cursor.set(passed.iter().flatten().count());
+            }
+            self.entries.retain(Option::is_some);
+            for (index, (key, _)) in self.entries.iter().flatten().enumerate() {
+                self.indices.insert(HashKey(key.clone()), index);
+            }
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.indices.clear();
+        for cursor in self.cursors.iter().filter_map(Weak::upgrade) {
+            cursor.set(0);
+        }
+    }
+
+    /// Starts an iteration that visits the entries as they are when it
+    /// reaches them, so entries added meanwhile are visited and deleted
+    /// ones are not.
+    pub fn cursor(&mut self) -> Rc<Cell<usize>> {
+        let cursor = Rc::new(Cell::new(0));
+        self.cursors.retain(|cursor| cursor.strong_count() > 0);
+        self.cursors.push(Rc::downgrade(&cursor));
+        cursor
+    }
+
+    /// The entry at or after `cursor`, which is moved past it, skipping
+    /// the holes that deleted entries left.
+    pub fn next(&self, cursor: &Cell<usize>) -> Option<(Value, Value)> {
+        while let Some(entry) = self.entries.get(cursor.get()) {
+            cursor.set(cursor.get() + 1);
+            if let Some(entry) = entry {
+                return Some(entry.clone());
+            }
+        }
+        None
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Value, Value)> {
+        self.entries.iter().flatten()
+    }
+}
+
+/// A key held by a `WeakMap` or `WeakSet` without keeping it alive.
+enum WeakKey {
+    Object(Weak<RefCell<Object>>),
+    Callable(Weak<dyn Callable>),
+}
+
+impl WeakKey {
+    fn is_alive(&self) -> bool {
+        match self {
+            WeakKey::Object(object) => object.strong_count() > 0,
+            WeakKey::Callable(callable) => callable.strong_count() > 0,
+        }
+    }
+}
+
+/// The entries of a `WeakMap` or `WeakSet`, keyed by the address of their
+/// key. An entry whose key has been dropped is never found again, even if
+/// a new object takes the same address, and such entries are swept out as
+/// the collection grows. A value that refers to its own key keeps it alive.
+#[derive(Default)]
+pub struct WeakEntries {
+    entries: HashMap<usize, (WeakKey, Value)>,
+    /// How many entries there may be before the dead ones are swept.
+    sweep_at: usize,
+}
+
+impl WeakEntries {
+    /// The address and weak reference for `key`, or `None` if it is not an
+    /// object. Records and tuples are compared by value, so they cannot be
+    /// weak keys either.
+    fn key(key: &Value) -> Option<(usize, WeakKey)> {
+        match key {
+            Value::Object(object) => match object.borrow().kind {
+                ObjectKind::Record | ObjectKind::Tuple(_) => None,
+                _ => Some((
+                    Rc::as_ptr(object) as usize,
+                    WeakKey::Object(Rc::downgrade(object)),
+                )),
+            },
+            Value::Callable(callable) => Some((
+                Rc::as_ptr(callable) as *const () as usize,
+                WeakKey::Callable(Rc::downgrade(callable)),
+            )),
+            _ => None,
+        }
+    }
+
+    fn get(&self, address: usize) -> Option<Value> {
+        match self.entries.get(&address) {
+            Some((key, value)) if key.is_alive() => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn insert(&mut self, address: usize, key: WeakKey, value: Value) {
+        if self.entries.len() >= self.sweep_at {
+            self.entries.retain(|_, (key, _)| key.is_alive());
+            self.sweep_at = (self.entries.len() * 2).max(16);
+        }
+        self.entries.insert(address, (key, value));
+    }
+
+    fn remove(&mut self, address: usize) -> bool {
+        let found = self.get(address).is_some();
+        self.entries.remove(&address);
+        found
+    }
+}
+
+/// Creates the globals `Map`, `Set`, `WeakMap` and `WeakSet`, which `new`
+/// calls.
+pub fn constructors() -> Vec<(&'static str, Value)> {
+    [
+        ("Map", construct_map as NativeFn),
+        ("Set", construct_set),
+        ("WeakMap", construct_weak_map),
+        ("WeakSet", construct_weak_set),
+    ]
+    .into_iter()
+    .map(|(name, construct)| {
+        let constructor = NativeFunction::with_arity(name, 0, Some(1), construct);
+        let object = Object::new(ObjectKind::Constructor(Rc::new(constructor)));
+        (name, Value::Object(Rc::new(RefCell::new(object))))
+    })
+    .collect()
+}
+
+/// Makes a collection of `kind` and adds the values `iterable` produces to
+/// it with its `add` or `set` method.
+fn construct(
+    interpreter: &mut Interpreter,
+    kind: ObjectKind,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let is_map = matches!(kind, ObjectKind::Map(_) | ObjectKind::WeakMap(_));
+    let collection = Value::Object(Rc::new(RefCell::new(Object::new(kind))));
+    let iterable = match arguments.into_iter().next() {
+        None | Some(Value::Nil) => return Ok(collection),
+        Some(iterable) => iterable,
+    };
+
+    let token = interpreter.call_site();
+    let mut iteration = Iteration::new(interpreter, &token, &iterable)?;
+    let name = if is_map { "set" } else { "add" };
+    let Value::Callable(add) = interpreter.get_property(&token, &collection, name)? else {
+        unreachable!("Collections have an '{}' method", name);
+    };
+    while let Some(value) = iteration.next(interpreter, &token)? {
+        let arguments = match is_map {
+            true if matches!(value, Value::Object(_)) => vec![
+                interpreter.get_property(&token, &value, "0")?,
+                interpreter.get_property(&token, &value, "1")?,
+            ],
+            true => {
+                return Err(RuntimeError::new(
+                    token,
+                    &format!("Iterator value {:?} is not an entry object.", value),
+                )
+                .into())
+            }
+            false => vec![value],
+        };
+        interpreter.call(&token, add.clone(), arguments)?;
+    }
+    Ok(collection)
+}
+
+fn construct_map(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    construct(interpreter, ObjectKind::Map(Entries::default()), arguments)
+}
+
+fn construct_set(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    construct(interpreter, ObjectKind::Set(Entries::default()), arguments)
+}
+
+fn construct_weak_map(
+    interpreter: &mut Interpreter,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    construct(
+        interpreter,
+        ObjectKind::WeakMap(WeakEntries::default()),
+        arguments,
+    )
+}
+
+fn construct_weak_set(
+    interpreter: &mut Interpreter,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    construct(
+        interpreter,
+        ObjectKind::WeakSet(WeakEntries::default()),
+        arguments,
+    )
+}
+
+/// Runs `f` on the entries of the `Map` or `Set` `receiver`. They are taken
+/// out of it meanwhile, so that `receiver` can be one of its own keys.
+fn with_entries<T>(receiver: &Value, f: impl FnOnce(&mut Entries) -> T) -> T {
+    let Value::Object(object) = receiver else {
+        panic!("Expected collection receiver");
+    };
+    let mut entries = match &mut object.borrow_mut().kind {
+        ObjectKind::Map(entries) | ObjectKind::Set(entries) => std::mem::take(entries),
+        _ => panic!("Expected collection receiver"),
+    };
+    let result = f(&mut entries);
+    match &mut object.borrow_mut().kind {
+        ObjectKind::Map(taken) | ObjectKind::Set(taken) => *taken = entries,
+        _ => unreachable!(),
+    }
+    result
+}
+
+/// Runs `f` on the entries of the `WeakMap` or `WeakSet` `receiver`. Keys
+/// must be looked up before, as `receiver` is borrowed meanwhile.
+fn with_weak_entries<T>(receiver: &Value, f: impl FnOnce(&mut WeakEntries) -> T) -> T {
+    match receiver {
+        Value::Object(object) => match &mut object.borrow_mut().kind {
+            ObjectKind::WeakMap(entries) | ObjectKind::WeakSet(entries) => f(entries),
+            _ => panic!("Expected weak collection receiver"),
+        },
+        _ => panic!("Expected weak collection receiver"),
+    }
+}
+
+/// The entries as they are now, for the methods that return them as an
+/// array.
+fn snapshot(receiver: &Value) -> Vec<(Value, Value)> {
+    with_entries(receiver, |entries| entries.iter().cloned().collect())
+}
+
+fn add(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let value = arguments.into_iter().next().unwrap();
+    with_entries(receiver, |entries| entries.insert(value.clone(), value));
+    Ok(receiver.clone())
+}
+
+fn clear(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    _arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    with_entries(receiver, Entries::clear);
+    Ok(Value::Nil)
+}
+
+fn delete(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let removed = with_entries(receiver, |entries| entries.remove(&arguments[0]));
+    Ok(Value::Bool(removed))
+}
+
+/// `entries()`, `keys()` and `values()` return arrays rather than
+/// iterators, which `for...of` and spreading treat the same way.
+fn entries(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    _arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    Ok(Value::array(
+        snapshot(receiver)
+            .into_iter()
+            .map(|(key, value)| Value::array(vec![key, value]))
+            .collect(),
+    ))
+}
+
+fn for_each(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let action = match &arguments[0] {
+        Value::Callable(action) => action.clone(),
+        value => {
+            return Err(RuntimeError::new(
+                interpreter.call_site(),
+                &format!("{} is not a function.", value),
+            )
+            .into())
+        }
+    };
+    let cursor = with_entries(receiver, Entries::cursor);
+    while let Some((key, value)) = with_entries(receiver, |entries| entries.next(&cursor)) {
+        let paren = interpreter.call_site();
+        interpreter.call_back(&paren, action.clone(), vec![value, key, receiver.clone()])?;
+    }
+    Ok(Value::Nil)
+}
+
+fn get(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let value = with_entries(receiver, |entries| entries.get(&arguments[0]));
+    Ok(value.unwrap_or(Value::Nil))
+}
+
+fn has(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let found = with_entries(receiver, |entries| entries.has(&arguments[0]));
+    Ok(Value::Bool(found))
+}
+
+fn keys(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    _arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    Ok(Value::array(
+        snapshot(receiver).into_iter().map(|(key, _)| key).collect(),
+    ))
+}
+
+fn set(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let mut arguments = arguments.into_iter();
+    let (key, value) = (arguments.next().unwrap(), arguments.next().unwrap());
+    with_entries(receiver, |entries| entries.insert(key, value));
+    Ok(receiver.clone())
+}
+
+fn values(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    _arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    Ok(Value::array(
+        snapshot(receiver)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect(),
+    ))
+}
+
+fn weak_add(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let value = arguments.into_iter().next().unwrap();
+    let Some((address, key)) = WeakEntries::key(&value) else {
+        return Err(RuntimeError::new(
+            interpreter.call_site(),
+            &format!("Invalid value used in weak set: {:?}.", value),
+        )
+        .into());
+    };
+    with_weak_entries(receiver, |entries| {
+        entries.insert(address, key, Value::Bool(true))
+    });
+    Ok(receiver.clone())
+}
+
+fn weak_delete(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let removed = match WeakEntries::key(&arguments[0]) {
+        Some((address, _)) => with_weak_entries(receiver, |entries| entries.remove(address)),
+        None => false,
+    };
+    Ok(Value::Bool(removed))
+}
+
+fn weak_get(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let value = match WeakEntries::key(&arguments[0]) {
+        Some((address, _)) => with_weak_entries(receiver, |entries| entries.get(address)),
+        None => None,
+    };
+    Ok(value.unwrap_or(Value::Nil))
+}
+
+fn weak_has(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let found = match WeakEntries::key(&arguments[0]) {
+        Some((address, _)) => with_weak_entries(receiver, |entries| entries.get(address)),
+        None => None,
+    };
+    Ok(Value::Bool(found.is_some()))
+}
+
+fn weak_set(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let mut arguments = arguments.into_iter();
+    let (key, value) = (arguments.next().unwrap(), arguments.next().unwrap());
+    let Some((address, key)) = WeakEntries::key(&key) else {
+        return Err(RuntimeError::new(
+            interpreter.call_site(),
+            &format!("Invalid value used as weak map key: {:?}.", key),
+        )
+        .into());
+    };
+    with_weak_entries(receiver, |entries| entries.insert(address, key, value));
+    Ok(receiver.clone())
+}
@@ -0,0 +1,320 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::{
+    callable::{NativeFn, NativeFunction},
+    object::{Object, ObjectKind},
+    symbol,
+    value::Value,
+    Interpreter, Unwind,
+};
+
+const METHODS: &[(&str, usize, Option<usize>, NativeFn)] = &[
+    ("error", 0, None, error),
+    ("log", 0, None, log),
+    ("table", 0, Some(1), table),
+    ("warn", 0, None, warn),
+];
+
+/// How deeply nested objects are shown before they are abbreviated to
+/// `[Object]` or `[Array]`.
+const MAX_DEPTH: usize = 2;
+
+/// Creates the global `console`.
+pub fn console() -> Value {
+    let mut object = Object::new(ObjectKind::Ordinary);
+    for (name, min_arity, max_arity, function) in METHODS {
+        object.set(
+            name,
+            Value::Callable(Rc::new(NativeFunction::with_arity(
+                name, *min_arity, *max_arity, *function,
+            ))),
+        );
+    }
+    Value::Object(Rc::new(RefCell::new(object)))
+}
+
+/// The global `print`, which scripts written when `print` was a statement
+/// can call instead. It is `console.log` under another name.
+pub fn print(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    log(interpreter, arguments)
+}
+
+fn log(_interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    println!("{}", format(&arguments));
+    Ok(Value::Nil)
+}
+
+fn error(_interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    eprintln!("{}", format(&arguments));
+    Ok(Value::Nil)
+}
+
+fn warn(_interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    eprintln!("{}", format(&arguments));
+    Ok(Value::Nil)
+}
+
+/// `console.table(data)`, which falls back to logging `data` when it has
+/// no rows to show.
+fn table(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    match arguments.first().and_then(render_table) {
+        Some(table) => {
+            println!("{}", table);
+            Ok(Value::Nil)
+        }
+        None => log(interpreter, arguments),
+    }
+}
+
+/// Joins `arguments` with spaces as `console.log` prints them. When the
+/// first is a string, its `%s`, `%d`, `%i`, `%f`, `%o`, `%O` and `%c`
+/// directives are replaced by the arguments that follow it in turn, and
+/// `%%` by a single `%`.
+pub fn format(arguments: &[Value]) -> String {
+    let mut arguments = arguments.iter();
+    let mut pieces = Vec::new();
+    if let Some(Value::String(template)) = arguments.clone().next() {
+        arguments.next();
+        let mut result = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            let directive = match (c, chars.peek()) {
+                ('%', Some(directive)) => *directive,
+                _ => {
+                    result.push(c);
+                    continue;
+                }
+            };
+            if directive == '%' {
+                chars.next();
+                result.push('%');
+                continue;
+            }
+            if !matches!(directive, 's' | 'd' | 'i' | 'f' | 'o' | 'O' | 'c') {
+                result.push(c);
+                continue;
+            }
+            let Some(argument) = arguments.next() else {
+                result.push(c);
+                continue;
+            };
+            chars.next();
+            result.push_str(&substitute(directive, argument));
+        }
+        pieces.push(result);
+    }
+    pieces.extend(arguments.map(inspect));
+    pieces.join(" ")
+}
+
+fn substitute(directive: char, argument: &Value) -> String {
+    match (directive, argument) {
+        ('s', Value::Object(_)) | ('o' | 'O', _) => inspect(argument),
+        ('s', _) => argument.to_string(),
+        ('d' | 'i', Value::Number(number)) => Value::Number(number.trunc()).to_string(),
+        ('d' | 'i', Value::BigInt(number)) => format!("{}n", number),
+        ('f', Value::Number(number)) => Value::Number(*number).to_string(),
+        ('d' | 'i' | 'f', _) => String::from("NaN"),
+        // `%c` styles the output in a browser, which a terminal cannot.
+        _ => String::new(),
+    }
+}
+
+/// Shows `value` as `console.log` does. Strings are printed as they are,
+/// but inside objects they are quoted.
+pub fn inspect(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.to_string(),
+        value => inspect_nested(value, 0, &mut Vec::new()),
+    }
+}
+
+/// Shows a value found `depth` objects down. `seen` holds the objects that
+/// contain it, so that a cycle is shown as `[Circular]` rather than
+/// followed forever.
+fn inspect_nested(value: &Value, depth: usize, seen: &mut Vec<*const RefCell<Object>>) -> String {
+    let Value::Object(object) = value else {
+        return format!("{:?}", value);
+    };
+    if seen.contains(&Rc::as_ptr(object)) {
+        return String::from("[Circular]");
+    }
+
+    let object_ref = object.borrow();
+    let (open, close, abbreviation) = match &object_ref.kind {
+        ObjectKind::Array(_) => ("[", "]", String::from("[Array]")),
+        ObjectKind::Tuple(_) => ("#[", "]", String::from("[Tuple]")),
+        ObjectKind::Ordinary => ("{ ", " }", String::from("[Object]")),
+        ObjectKind::Record => ("#{ ", " }", String::from("[Record]")),
+        ObjectKind::Instance(class) => ("{ ", " }", format!("[{}]", class.name)),
+        ObjectKind::Variant(variant) if variant.fields.is_some() => {
+            ("(", ")", format!("[{}]", variant.qualified_name()))
+        }
+        ObjectKind::Map(_) => ("{ ", " }", String::from("[Map]")),
+        ObjectKind::Set(_) => ("{ ", " }", String::from("[Set]")),
+        _ => return object_ref.to_string(),
+    };
+    if depth > MAX_DEPTH {
+        return abbreviation;
+    }
+
+    seen.push(Rc::as_ptr(object));
+    let members: Vec<String> = match &object_ref.kind {
+        ObjectKind::Array(elements) | ObjectKind::Tuple(elements) => elements
+            .iter()
+            .map(|element| inspect_nested(element, depth + 1, seen))
+            .collect(),
+        ObjectKind::Map(entries) => entries
+            .iter()
+            .map(|(key, value)| {
+                let key = inspect_nested(key, depth + 1, seen);
+                format!("{} => {}", key, inspect_nested(value, depth + 1, seen))
+            })
+            .collect(),
+        ObjectKind::Set(entries) => entries
+            .iter()
+            .map(|(value, _)| inspect_nested(value, depth + 1, seen))
+            .collect(),
+        ObjectKind::Variant(_) => object_ref
+            .keys()
+            .iter()
+            .map(|key| {
+                let field = object_ref.get(key).unwrap_or(Value::Nil);
+                inspect_nested(&field, depth + 1, seen)
+            })
+            .collect(),
+        _ => object_ref
+            .keys()
+            .iter()
+            .map(|key| {
+                let property = object_ref.get(key).unwrap_or(Value::Nil);
+                let key = symbol::display_key(key);
+                format!("{}: {}", key, inspect_nested(&property, depth + 1, seen))
+            })
+            .collect(),
+    };
+    seen.pop();
+
+    let prefix = match &object_ref.kind {
+        ObjectKind::Instance(class) => format!("{} ", class.name),
+        ObjectKind::Variant(variant) => variant.qualified_name(),
+        ObjectKind::Map(entries) => format!("Map({}) ", entries.len()),
+        ObjectKind::Set(entries) => format!("Set({}) ", entries.len()),
+        _ => String::new(),
+    };
+    match (members.is_empty(), &object_ref.kind) {
+        (true, ObjectKind::Ordinary | ObjectKind::Instance(_))
+        | (true, ObjectKind::Map(_) | ObjectKind::Set(_)) => format!("{}{{}}", prefix),
+        (true, ObjectKind::Record) => String::from("#{}"),
+        _ => format!("{}{}{}{}", prefix, open, members.join(", "), close),
+    }
+}
+
+/// The rows of a table: the elements of an array, or the properties of any
+/// other object with properties, each named by its index or key.
+fn rows(value: &Value) -> Option<Vec<(String, Value)>> {
+    let Value::Object(object) = value else {
+        return None;
+    };
+    let object = object.borrow();
+    match &object.kind {
+        ObjectKind::Array(elements) | ObjectKind::Tuple(elements) => Some(
+            elements
+                .iter()
+                .enumerate()
+                .map(|(index, element)| (index.to_string(), element.clone()))
+                .collect(),
+        ),
+        ObjectKind::Ordinary | ObjectKind::Record | ObjectKind::Instance(_) => Some(
+            object
+                .keys()
+                .iter()
+                .map(|key| {
+                    (
+                        symbol::display_key(key),
+                        object.get(key).unwrap_or(Value::Nil),
+                    )
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Draws `data` as a table with a row for each of its members. Members that
+/// are objects have a column for each of their own members, and any others
+/// are shown in a `Values` column.
+pub fn render_table(data: &Value) -> Option<String> {
+    let rows = rows(data)?;
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut has_values = false;
+    let mut cells: Vec<Vec<(String, Value)>> = Vec::new();
+    for (_, row) in &rows {
+        match self::rows(row) {
+            Some(members) => {
+                for (key, _) in &members {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+                cells.push(members);
+            }
+            None => {
+                has_values = true;
+                cells.push(Vec::new());
+            }
+        }
+    }
+
+    let mut header = vec![String::from("(index)")];
+    header.extend(columns.iter().cloned());
+    if has_values {
+        header.push(String::from("Values"));
+    }
+
+    // Cells are narrow, so objects in them only show their own members.
+    let cell = |value: &Value| inspect_nested(value, MAX_DEPTH, &mut Vec::new());
+    let mut lines: Vec<Vec<String>> = Vec::new();
+    for ((index, row), members) in rows.iter().zip(&cells) {
+        let mut line = vec![index.clone()];
+        for column in &columns {
+            let member = members.iter().find(|(key, _)| key == column);
+            line.push(member.map_or(String::new(), |(_, value)| cell(value)));
+        }
+        if has_values {
+            line.push(match self::rows(row) {
+                Some(_) => String::new(),
+                None => cell(row),
+            });
+        }
+        lines.push(line);
+    }
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            std::iter::once(&header)
+                .chain(&lines)
+                .map(|line| line[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let border = |left: &str, middle: &str, right: &str| {
+        let segments: Vec<String> = widths.iter().map(|width| "─".repeat(width + 2)).collect();
+        format!("{}{}{}", left, segments.join(middle), right)
+    };
+    let row = |line: &Vec<String>| {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!(" {}{} ", cell, " ".repeat(width - cell.chars().count())))
+            .collect();
+        format!("│{}│", cells.join("│"))
+    };
+
+    let mut table = vec![border("┌", "┬", "┐"), row(&header), border("├", "┼", "┤")];
+    table.extend(lines.iter().map(row));
+    table.push(border("└", "┴", "┘"));
+    Some(table.join("\n"))
+}
@@ -0,0 +1,104 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::parser::statement;
+
+use super::{
+    callable::Callable,
+    object::{Object, ObjectKind},
+    value::Value,
+    Interpreter, Unwind,
+};
+
+pub struct Enum {
+    pub name: String,
+    pub variants: Vec<Rc<Variant>>,
+}
+
+impl Enum {
+    pub fn variant(&self, name: &str) -> Option<Rc<Variant>> {
+        self.variants
+            .iter()
+            .find(|variant| variant.name == name)
+            .cloned()
+    }
+}
+
+/// What every value made from one variant of an enum shares.
+pub struct Variant {
+    /// The name of the enum the variant belongs to.
+    pub enumeration: String,
+    pub name: String,
+    /// `None` for a variant like `Color.Red`, which is a single value rather
+    /// than a function that makes them.
+    pub fields: Option<Vec<String>>,
+}
+
+impl Variant {
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.enumeration, self.name)
+    }
+}
+
+/// Creates the object an `enum` declaration binds its name to. Variants
+/// without fields are stored on it as values and the rest as constructors.
+pub fn create(name: &str, variants: &[statement::Variant]) -> Value {
+    let variants: Vec<Rc<Variant>> = variants
+        .iter()
+        .map(|variant| {
+            Rc::new(Variant {
+                enumeration: name.to_string(),
+                name: variant.name.lexeme.clone(),
+                fields: variant
+                    .fields
+                    .as_ref()
+                    .map(|fields| fields.iter().map(|field| field.lexeme.clone()).collect()),
+            })
+        })
+        .collect();
+
+    let mut enumeration = Object::new(ObjectKind::Enum(Rc::new(Enum {
+        name: name.to_string(),
+        variants: variants.clone(),
+    })));
+    for variant in variants {
+        let value = match variant.fields {
+            Some(_) => Value::Callable(Rc::new(Constructor {
+                variant: variant.clone(),
+            })),
+            None => instance(variant.clone(), Vec::new()),
+        };
+        enumeration.set(&variant.name, value);
+    }
+    Value::Object(Rc::new(RefCell::new(enumeration)))
+}
+
+fn instance(variant: Rc<Variant>, values: Vec<Value>) -> Value {
+    let mut object = Object::new(ObjectKind::Variant(variant.clone()));
+    for (field, value) in variant.fields.iter().flatten().zip(values) {
+        object.set(field, value);
+    }
+    Value::Object(Rc::new(RefCell::new(object)))
+}
+
+/// `Shape.Circle`, which makes a `Shape.Circle(r)` value when called.
+struct Constructor {
+    variant: Rc<Variant>,
+}
+
+impl Callable for Constructor {
+    fn min_arity(&self) -> usize {
+        self.variant.fields.as_ref().map_or(0, Vec::len)
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        Some(self.min_arity())
+    }
+
+    fn call(&self, _interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+        Ok(instance(self.variant.clone(), arguments))
+    }
+
+    fn name(&self) -> String {
+        self.variant.qualified_name()
+    }
+}
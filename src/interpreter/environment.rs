@@ -0,0 +1,127 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{parser::token::Token, ErrorKind, RuntimeError};
+
+use super::value::Value;
+
+struct Binding {
+    value: Value,
+    mutable: bool,
+    /// For an imported name, the scope of the module that exports it and
+    /// the name it has there, which reads give the current value of.
+    import: Option<(Rc<RefCell<Environment>>, Token)>,
+}
+
+pub struct Environment {
+    values: HashMap<String, Binding>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment {
+            values: HashMap::new(),
+            enclosing: None,
+        }
+    }
+
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Environment {
+        Environment {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(
+            name.to_string(),
+            Binding {
+                value,
+                mutable: true,
+                import: None,
+            },
+        );
+    }
+
+    pub fn define_const(&mut self, name: &str, value: Value) {
+        self.values.insert(
+            name.to_string(),
+            Binding {
+                value,
+                mutable: false,
+                import: None,
+            },
+        );
+    }
+
+    /// Binds `name` to the variable `local` of the module scope `module`.
+    /// It follows that variable as the module changes it, and cannot be
+    /// assigned to itself.
+    pub fn define_import(&mut self, name: &str, module: Rc<RefCell<Environment>>, local: Token) {
+        self.values.insert(
+            name.to_string(),
+            Binding {
+                value: Value::Nil,
+                mutable: false,
+                import: Some((module, local)),
+            },
+        );
+    }
+
+    /// Whether this is `scope` or an environment nested inside it.
+    pub fn is_within(&self, scope: &Rc<RefCell<Environment>>) -> bool {
+        if std::ptr::eq(self, scope.as_ptr()) {
+            return true;
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().is_within(scope),
+            None => false,
+        }
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Value, RuntimeError> {
+        if let Some(binding) = self.values.get(&name.lexeme) {
+            return match &binding.import {
+                Some((module, local)) => module.borrow().get(local),
+                None => Ok(binding.value.clone()),
+            };
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get(name),
+            None => Err(RuntimeError::with_kind(
+                name.clone(),
+                ErrorKind::ReferenceError,
+                &format!("Undefined variable '{}'.", name.lexeme),
+            )),
+        }
+    }
+
+    pub fn assign(&mut self, name: &Token, value: Value) -> Result<(), RuntimeError> {
+        if let Some(binding) = self.values.get_mut(&name.lexeme) {
+            if !binding.mutable {
+                return Err(RuntimeError::new(
+                    name.clone(),
+                    &format!("Assignment to constant variable '{}'.", name.lexeme),
+                ));
+            }
+            binding.value = value;
+            return Ok(());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(RuntimeError::with_kind(
+                name.clone(),
+                ErrorKind::ReferenceError,
+                &format!("Undefined variable '{}'.", name.lexeme),
+            )),
+        }
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    rc::Rc,
+};
+
+use crate::{parser::token::Token, ErrorKind, RuntimeError};
+
+use super::{
+    callable::Callable, object::Object, promise::Reaction, value::Value, Interpreter, Unwind,
+};
+
+/// Work that waits for the running code to finish before it starts.
+pub enum Job {
+    /// A callback passed to `queueMicrotask` or `setTimeout`, and the call
+    /// that scheduled it.
+    Callback {
+        function: Rc<dyn Callable>,
+        arguments: Vec<Value>,
+        token: Token,
+    },
+    /// Hands the outcome of a settled promise to a reaction waiting on it.
+    Reaction {
+        reaction: Reaction,
+        outcome: Result<Value, Value>,
+    },
+}
+
+/// The jobs that run once a script has finished. Time is simulated: timers
+/// fire in the order they fall due without actually waiting, so that every
+/// run of a script does the same thing.
+pub struct EventLoop {
+    microtasks: VecDeque<Job>,
+    /// Keyed by due time and then id, which is the order they fire in.
+    timers: BTreeMap<(u64, usize), Job>,
+    /// Milliseconds since the script started, as far as timers can tell.
+    now: u64,
+    timer_ids: usize,
+    /// Rejected promises that nothing has reacted to yet, with what they
+    /// were rejected with and where.
+    unhandled: Vec<(Rc<RefCell<Object>>, Value, Token)>,
+}
+
+impl EventLoop {
+    pub fn new() -> EventLoop {
+        EventLoop {
+            microtasks: VecDeque::new(),
+            timers: BTreeMap::new(),
+            now: 0,
+            timer_ids: 0,
+            unhandled: Vec::new(),
+        }
+    }
+
+    pub fn queue_microtask(&mut self, job: Job) {
+        self.microtasks.push_back(job);
+    }
+
+    /// Schedules `job` to run `delay` milliseconds from now and returns the
+    /// timer's id.
+    pub fn set_timeout(&mut self, job: Job, delay: u64) -> usize {
+        self.timer_ids += 1;
+        self.timers.insert((self.now + delay, self.timer_ids), job);
+        self.timer_ids
+    }
+
+    pub fn clear_timeout(&mut self, id: usize) {
+        self.timers.retain(|(_, timer), _| *timer != id);
+    }
+
+    pub fn reject_unhandled(&mut self, promise: Rc<RefCell<Object>>, value: Value, token: Token) {
+        self.unhandled.push((promise, value, token));
+    }
+
+    /// Notes that a reaction has been added to a rejected promise.
+    pub fn handle(&mut self, promise: &Rc<RefCell<Object>>) {
+        self.unhandled
+            .retain(|(unhandled, _, _)| !Rc::ptr_eq(unhandled, promise));
+    }
+
+    /// Takes the job to run next: microtasks come first, then the timer that
+    /// is due soonest. A rejection that is still unhandled once the
+    /// microtasks have run is an error.
+    pub fn next_job(&mut self) -> Result<Option<Job>, RuntimeError> {
+        if let Some(job) = self.microtasks.pop_front() {
+            return Ok(Some(job));
+        }
+
+        if let Some((_, value, token)) = self.unhandled.drain(..).next() {
+            return Err(RuntimeError::with_kind(
+                token,
+                ErrorKind::Error,
+                &format!("Uncaught (in promise) {}", value),
+            ));
+        }
+
+        Ok(self.timers.pop_first().map(|((due, _), job)| {
+            self.now = due;
+            job
+        }))
+    }
+
+    /// Drops every pending job, as when the script has failed.
+    pub fn clear(&mut self) {
+        self.microtasks.clear();
+        self.timers.clear();
+        self.unhandled.clear();
+    }
+}
+
+impl Default for EventLoop {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn callback(interpreter: &Interpreter, value: &Value) -> Result<Rc<dyn Callable>, RuntimeError> {
+    match value {
+        Value::Callable(callback) => Ok(callback.clone()),
+        _ => Err(RuntimeError::new(
+            interpreter.call_site(),
+            &format!("{} is not a function.", value),
+        )),
+    }
+}
+
+pub fn queue_microtask(
+    interpreter: &mut Interpreter,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let job = Job::Callback {
+        function: callback(interpreter, &arguments[0])?,
+        arguments: Vec::new(),
+        token: interpreter.call_site(),
+    };
+    interpreter.event_loop.borrow_mut().queue_microtask(job);
+    Ok(Value::Nil)
+}
+
+/// `setTimeout(callback, delay, ...arguments)`. A missing or negative delay
+/// means the callback runs as soon as the running code and the microtasks
+/// it queued are done.
+pub fn set_timeout(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    let mut arguments = arguments.into_iter();
+    let function = callback(interpreter, &arguments.next().unwrap_or(Value::Nil))?;
+    let delay = match arguments.next() {
+        Some(Value::Number(delay)) if delay > 0.0 => delay as u64,
+        _ => 0,
+    };
+    let job = Job::Callback {
+        function,
+        arguments: arguments.collect(),
+        token: interpreter.call_site(),
+    };
+    let id = interpreter.event_loop.borrow_mut().set_timeout(job, delay);
+    Ok(Value::from(id as f64))
+}
+
+pub fn clear_timeout(
+    interpreter: &mut Interpreter,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    if let Some(Value::Number(id)) = arguments.first() {
+        interpreter
+            .event_loop
+            .borrow_mut()
+            .clear_timeout(*id as usize);
+    }
+    Ok(Value::Nil)
+}
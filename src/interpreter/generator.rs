@@ -0,0 +1,288 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use corosensei::{stack::DefaultStack, Coroutine, CoroutineResult};
+
+use crate::{
+    parser::{statement::Statement, token::Token},
+    ErrorKind, RuntimeError,
+};
+
+use super::{
+    callable::NativeMethodFn,
+    environment::Environment,
+    iterator::{iterator_result, Iteration, Step},
+    object::{Object, ObjectKind},
+    symbol::ITERATOR,
+    value::Value,
+    Frame, Interpreter, Unwind,
+};
+
+/// Generator bodies run on native stacks of their own, which are smaller
+/// than the main one and so allow fewer nested calls.
+const STACK_SIZE: usize = crate::STACK_SIZE / 8;
+const MAX_CALL_DEPTH: usize = super::MAX_CALL_DEPTH / 4;
+
+/// How many generators and async calls may be unfinished at once. Each one
+/// holds its own native stack, which takes two of the 65,530 memory mappings
+/// that Linux allows a process by default (`vm.max_map_count`): the stack
+/// and the guard page below it. Going past this is a `RangeError` rather
+/// than a failure to map a stack at some count that depends on the system.
+pub const MAX_UNFINISHED: usize = 25_000;
+
+thread_local! {
+    static UNFINISHED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// One of the `MAX_UNFINISHED` stacks, which is given back when the body
+/// that runs on it is finished or dropped.
+struct StackSlot;
+
+impl StackSlot {
+    fn claim() -> Option<StackSlot> {
+        UNFINISHED.with(|unfinished| {
+            let count = unfinished.get();
+            (count < MAX_UNFINISHED).then(|| {
+                unfinished.set(count + 1);
+                StackSlot
+            })
+        })
+    }
+}
+
+impl Drop for StackSlot {
+    fn drop(&mut self) {
+        UNFINISHED.with(|unfinished| unfinished.set(unfinished.get() - 1));
+    }
+}
+
+pub const METHODS: &[(&str, usize, Option<usize>, NativeMethodFn)] = &[
+    ("next", 0, Some(1), next),
+    ("return", 0, Some(1), return_),
+    ("throw", 0, Some(1), throw),
+    (ITERATOR, 0, Some(0), iterator),
+];
+
+/// What a paused generator is resumed with, by `next`, `return` or `throw`.
+pub enum Resume {
+    Next(Value),
+    Return(Value),
+    Throw(Value),
+}
+
+pub type Yielder = corosensei::Yielder<Resume, Value>;
+
+type Body = Coroutine<Resume, Value, Result<Value, Unwind>>;
+
+pub enum GeneratorState {
+    /// Not started yet, or paused at a `yield`.
+    Suspended(Body),
+    Running,
+    Completed,
+}
+
+/// Creates the object that calling a generator function returns. The body
+/// does not start running until the first call to `next`, and runs in
+/// `environment`, where the arguments are already bound.
+pub fn create(
+    interpreter: &Interpreter,
+    name: String,
+    body: Rc<[Statement]>,
+    environment: Rc<RefCell<Environment>>,
+) -> Result<Value, RuntimeError> {
+    let paren = interpreter.call_site();
+    let slot = StackSlot::claim().ok_or_else(|| {
+        RuntimeError::with_kind(
+            paren.clone(),
+            ErrorKind::RangeError,
+            &format!(
+                "Too many unfinished generators and async calls (at most {}).",
+                MAX_UNFINISHED
+            ),
+        )
+    })?;
+    let stack = DefaultStack::new(STACK_SIZE).map_err(|_| {
+        RuntimeError::with_kind(
+            paren.clone(),
+            ErrorKind::RangeError,
+            "Cannot allocate a stack for the generator.",
+        )
+    })?;
+
+    let mut fork = interpreter.fork(Frame { name, paren }, MAX_CALL_DEPTH);
+    let coroutine = Coroutine::with_stack(stack, move |yielder: &Yielder, _: Resume| {
+        let _slot = slot;
+        fork.yielder = Some(yielder);
+        match fork.execute_block(&body, environment) {
+            Ok(()) => Ok(Value::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(error)) => Err(fork.throw_error(error)),
+            Err(unwind) => Err(unwind),
+        }
+    });
+
+    Ok(Value::Object(Rc::new(RefCell::new(Object::new(
+        ObjectKind::Generator(GeneratorState::Suspended(coroutine)),
+    )))))
+}
+
+/// Pauses the running generator, handing `value` to whoever resumed it,
+/// and produces what it is resumed with.
+fn pause(interpreter: &Interpreter, value: Value) -> Resume {
+    let yielder = interpreter
+        .yielder
+        .expect("the resolver only allows 'yield' inside generators");
+    // SAFETY: only the coroutine that owns this interpreter sets `yielder`,
+    // and it stays on that coroutine's stack until the body has finished.
+    unsafe { &*yielder }.suspend(value)
+}
+
+/// Pauses the running generator at `yield`, handing `value` to whoever
+/// resumed it, and produces the value it is resumed with.
+pub fn suspend(interpreter: &Interpreter, keyword: &Token, value: Value) -> Result<Value, Unwind> {
+    match pause(interpreter, value) {
+        Resume::Next(value) => Ok(value),
+        Resume::Return(value) => Err(Unwind::Return(value)),
+        Resume::Throw(value) => Err(Unwind::Throw {
+            value,
+            token: keyword.clone(),
+        }),
+    }
+}
+
+/// Runs `yield*`, yielding every value of `iterable` in turn and producing
+/// the value it finished with. What the generator is resumed with is passed
+/// on to the iterator's `next`, `throw` or `return` method.
+pub fn delegate(
+    interpreter: &mut Interpreter,
+    keyword: &Token,
+    iterable: &Value,
+) -> Result<Value, Unwind> {
+    let mut iteration = Iteration::new(interpreter, keyword, iterable)?;
+    let mut resume = Resume::Next(Value::Nil);
+    loop {
+        let step = match resume {
+            Resume::Next(value) => iteration.send(interpreter, keyword, value)?,
+            Resume::Throw(value) => {
+                match iteration.forward(interpreter, keyword, "throw", value)? {
+                    Some(step) => step,
+                    None => {
+                        iteration.close(interpreter, keyword)?;
+                        return Err(RuntimeError::new(
+                            keyword.clone(),
+                            "The iterator does not provide a 'throw' method.",
+                        )
+                        .into());
+                    }
+                }
+            }
+            Resume::Return(value) => {
+                match iteration.forward(interpreter, keyword, "return", value.clone())? {
+                    Some(Step::Value(value)) => Step::Value(value),
+                    Some(Step::Done(value)) => return Err(Unwind::Return(value)),
+                    None => return Err(Unwind::Return(value)),
+                }
+            }
+        };
+        match step {
+            Step::Value(value) => resume = pause(interpreter, value),
+            Step::Done(value) => return Ok(value),
+        }
+    }
+}
+
+fn with_state<T>(receiver: &Value, f: impl FnOnce(&mut GeneratorState) -> T) -> T {
+    match receiver {
+        Value::Object(object) => match &mut object.borrow_mut().kind {
+            ObjectKind::Generator(state) => f(state),
+            _ => panic!("Expected generator receiver"),
+        },
+        _ => panic!("Expected generator receiver"),
+    }
+}
+
+/// Runs the generator `receiver` up to its next `yield` or to its end.
+/// Errors about the generator itself are reported at `token`.
+pub fn resume(token: &Token, receiver: &Value, resume: Resume) -> Result<Step, Unwind> {
+    // The body is taken out while it runs, so that it can use the generator
+    // object without finding it borrowed.
+    let state = with_state(receiver, |state| {
+        std::mem::replace(state, GeneratorState::Running)
+    });
+    let mut body = match state {
+        GeneratorState::Suspended(body) if body.started() || matches!(resume, Resume::Next(_)) => {
+            body
+        }
+        GeneratorState::Running => {
+            return Err(RuntimeError::new(token.clone(), "Generator is already running.").into())
+        }
+        // Returning from or throwing into a generator that has not started
+        // finishes it without running any of its body.
+        _ => {
+            with_state(receiver, |state| *state = GeneratorState::Completed);
+            return match resume {
+                Resume::Next(_) => Ok(Step::Done(Value::Nil)),
+                Resume::Return(value) => Ok(Step::Done(value)),
+                Resume::Throw(value) => Err(Unwind::Throw {
+                    value,
+                    token: token.clone(),
+                }),
+            };
+        }
+    };
+
+    match body.resume(resume) {
+        CoroutineResult::Yield(value) => {
+            with_state(receiver, |state| *state = GeneratorState::Suspended(body));
+            Ok(Step::Value(value))
+        }
+        CoroutineResult::Return(result) => {
+            with_state(receiver, |state| *state = GeneratorState::Completed);
+            Ok(Step::Done(result?))
+        }
+    }
+}
+
+fn argument(arguments: Vec<Value>) -> Value {
+    arguments.into_iter().next().unwrap_or(Value::Nil)
+}
+
+fn next(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let token = interpreter.call_site();
+    let step = resume(&token, receiver, Resume::Next(argument(arguments)))?;
+    Ok(iterator_result(step))
+}
+
+fn return_(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let token = interpreter.call_site();
+    let step = resume(&token, receiver, Resume::Return(argument(arguments)))?;
+    Ok(iterator_result(step))
+}
+
+fn throw(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let token = interpreter.call_site();
+    let step = resume(&token, receiver, Resume::Throw(argument(arguments)))?;
+    Ok(iterator_result(step))
+}
+
+fn iterator(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    _arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    Ok(receiver.clone())
+}
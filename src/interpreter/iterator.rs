@@ -0,0 +1,278 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use crate::{parser::token::Token, RuntimeError};
+
+use super::{
+    callable::{Callable, NativeMethodFn},
+    object::{Object, ObjectKind},
+    symbol::ITERATOR,
+    value::Value,
+    Interpreter, Unwind,
+};
+
+/// The methods of the iterators that the built-in `[Symbol.iterator]()`
+/// methods return.
+pub const METHODS: &[(&str, usize, Option<usize>, NativeMethodFn)] =
+    &[("next", 0, Some(0), next), (ITERATOR, 0, Some(0), itself)];
+
+/// A position in the values being iterated over.
+pub enum Iteration {
+    /// Arrays are walked by index, so elements pushed during a loop are
+    /// visited too.
+    Array {
+        array: Rc<RefCell<Object>>,
+        index: usize,
+    },
+    /// Maps and sets are walked by position too, giving `[key, value]`
+    /// pairs and values, so entries added during a loop are visited and
+    /// deleted ones are not.
+    Entries {
+        collection: Rc<RefCell<Object>>,
+        cursor: Rc<Cell<usize>>,
+    },
+    Values(std::vec::IntoIter<Value>),
+    /// The iterator that an object's `Symbol.iterator` method returned,
+    /// and its `next` method.
+    Protocol {
+        iterator: Value,
+        next: Rc<dyn Callable>,
+    },
+}
+
+/// What one step of an iteration produced.
+pub enum Step {
+    Value(Value),
+    /// The iteration is over. Iterators may finish with a value, which is
+    /// what `yield*` evaluates to.
+    Done(Value),
+}
+
+impl Iteration {
+    /// Starts iterating over `value`, as `for...of` and spreading do.
+    pub fn new(
+        interpreter: &mut Interpreter,
+        token: &Token,
+        value: &Value,
+    ) -> Result<Iteration, Unwind> {
+        let object = match value {
+            Value::String(value) => {
+                let characters = value
+                    .chars()
+                    .map(|character| Value::from(character.to_string()))
+                    .collect::<Vec<Value>>();
+                return Ok(Iteration::Values(characters.into_iter()));
+            }
+            Value::Object(object) => object,
+            _ => {
+                return Err(RuntimeError::new(
+                    token.clone(),
+                    &format!("{} is not iterable.", value.type_name()),
+                )
+                .into())
+            }
+        };
+        match &object.borrow().kind {
+            ObjectKind::Array(_) => {
+                return Ok(Iteration::Array {
+                    array: object.clone(),
+                    index: 0,
+                })
+            }
+            // Tuples never change, so their elements can be taken up front.
+            ObjectKind::Tuple(elements) => {
+                return Ok(Iteration::Values(elements.clone().into_iter()))
+            }
+            _ => {}
+        }
+        if let ObjectKind::Map(entries) | ObjectKind::Set(entries) = &mut object.borrow_mut().kind {
+            return Ok(Iteration::Entries {
+                collection: object.clone(),
+                cursor: entries.cursor(),
+            });
+        }
+
+        let method = match interpreter.get_property(token, value, ITERATOR)? {
+            Value::Callable(method) => method,
+            _ => return Err(RuntimeError::new(token.clone(), "Object is not iterable.").into()),
+        };
+        let iterator = interpreter.call(token, method, Vec::new())?;
+        match interpreter.get_property(token, &iterator, "next")? {
+            Value::Callable(next) => Ok(Iteration::Protocol { iterator, next }),
+            _ => {
+                Err(RuntimeError::new(token.clone(), "Iterator's 'next' is not a function.").into())
+            }
+        }
+    }
+
+    /// Produces the next value, or `None` once the iteration is done.
+    pub fn next(
+        &mut self,
+        interpreter: &mut Interpreter,
+        token: &Token,
+    ) -> Result<Option<Value>, Unwind> {
+        Ok(match self.step(interpreter, token)? {
+            Step::Value(value) => Some(value),
+            Step::Done(_) => None,
+        })
+    }
+
+    pub fn step(&mut self, interpreter: &mut Interpreter, token: &Token) -> Result<Step, Unwind> {
+        match self {
+            Iteration::Array { array, index } => {
+                let value = match &array.borrow().kind {
+                    ObjectKind::Array(elements) => elements.get(*index).cloned(),
+                    _ => None,
+                };
+                *index += 1;
+                Ok(value.map_or(Step::Done(Value::Nil), Step::Value))
+            }
+            Iteration::Entries { collection, cursor } => {
+                let value = match &collection.borrow().kind {
+                    ObjectKind::Map(entries) => entries
+                        .next(cursor)
+                        .map(|(key, value)| Value::array(vec![key, value])),
+                    ObjectKind::Set(entries) => entries.next(cursor).map(|(value, _)| value),
+                    _ => None,
+                };
+                Ok(value.map_or(Step::Done(Value::Nil), Step::Value))
+            }
+            Iteration::Values(values) => {
+                Ok(values.next().map_or(Step::Done(Value::Nil), Step::Value))
+            }
+            Iteration::Protocol { iterator: _, next } => {
+                let result = interpreter.call(token, next.clone(), Vec::new())?;
+                result_step(interpreter, token, result)
+            }
+        }
+    }
+
+    /// Steps the iteration as `yield*` does when it is resumed with `value`,
+    /// which is passed on to the iterator's `next` method.
+    pub fn send(
+        &mut self,
+        interpreter: &mut Interpreter,
+        token: &Token,
+        value: Value,
+    ) -> Result<Step, Unwind> {
+        match self {
+            Iteration::Protocol { iterator: _, next } => {
+                let result = interpreter.call(token, next.clone(), vec![value])?;
+                result_step(interpreter, token, result)
+            }
+            _ => self.step(interpreter, token),
+        }
+    }
+
+    /// Calls the iterator's `throw` or `return` method with `value`, or
+    /// produces `None` when it has no such method.
+    pub fn forward(
+        &self,
+        interpreter: &mut Interpreter,
+        token: &Token,
+        name: &str,
+        value: Value,
+    ) -> Result<Option<Step>, Unwind> {
+        let Iteration::Protocol { iterator, next: _ } = self else {
+            return Ok(None);
+        };
+        match interpreter.get_property(token, iterator, name)? {
+            Value::Callable(method) => {
+                let result = interpreter.call(token, method, vec![value])?;
+                Ok(Some(result_step(interpreter, token, result)?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Tells an iterator that it will not be asked for more values, as a
+    /// loop that is left early does, by calling its `return` method.
+    pub fn close(&self, interpreter: &mut Interpreter, token: &Token) -> Result<(), Unwind> {
+        let Iteration::Protocol { iterator, next: _ } = self else {
+            return Ok(());
+        };
+        if let Value::Callable(method) = interpreter.get_property(token, iterator, "return")? {
+            interpreter.call(token, method, Vec::new())?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the `{ value, done }` object that an iterator method returned.
+fn result_step(
+    interpreter: &mut Interpreter,
+    token: &Token,
+    result: Value,
+) -> Result<Step, Unwind> {
+    if !matches!(result, Value::Object(_)) {
+        return Err(RuntimeError::new(
+            token.clone(),
+            &format!("Iterator result {} is not an object.", result),
+        )
+        .into());
+    }
+
+    let done = interpreter.get_property(token, &result, "done")?;
+    let value = interpreter.get_property(token, &result, "value")?;
+    Ok(match interpreter.is_truthy(&done) {
+        true => Step::Done(value),
+        false => Step::Value(value),
+    })
+}
+
+/// `{ value, done }`, the object that iterators' `next` methods return.
+pub fn iterator_result(step: Step) -> Value {
+    let (value, done) = match step {
+        Step::Value(value) => (value, false),
+        Step::Done(value) => (value, true),
+    };
+    let mut result = Object::new(ObjectKind::Ordinary);
+    result.set("value", value);
+    result.set("done", Value::from(done));
+    Value::Object(Rc::new(RefCell::new(result)))
+}
+
+/// `[Symbol.iterator]()` on arrays, strings, maps and sets, which returns
+/// an iterator over the values that `for...of` would visit.
+pub fn iterator(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    _arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let iteration = Iteration::new(interpreter, &interpreter.call_site(), receiver)?;
+    Ok(Value::Object(Rc::new(RefCell::new(Object::new(
+        ObjectKind::Iterator(Box::new(iteration)),
+    )))))
+}
+
+fn next(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    _arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let Value::Object(object) = receiver else {
+        panic!("Expected iterator receiver");
+    };
+    // The iteration is taken out while it steps, since stepping may run
+    // code that reads the iterator.
+    let finished = Box::new(Iteration::Values(Vec::new().into_iter()));
+    let mut iteration = match &mut object.borrow_mut().kind {
+        ObjectKind::Iterator(iteration) => std::mem::replace(iteration, finished),
+        _ => panic!("Expected iterator receiver"),
+    };
+    let step = iteration.step(interpreter, &interpreter.call_site());
+    if let ObjectKind::Iterator(slot) = &mut object.borrow_mut().kind {
+        *slot = iteration;
+    }
+    Ok(iterator_result(step?))
+}
+
+fn itself(
+    _interpreter: &mut Interpreter,
+    receiver: &Value,
+    _arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    Ok(receiver.clone())
+}
@@ -1,248 +1,4272 @@
-use std::any::Any;
-
-use anyhow::{bail, Ok, Result};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::{
+    module_path,
     parser::{
-        expression::{Expression, Visitor as ExpressionVisitor},
+        expression::{Expression, Literal, PropertyKey, Visitor as ExpressionVisitor},
+        pattern::{MatchPattern, Pattern},
+        statement::{Access, Binding, ForKind, Statement, SwitchCase, Visitor as StatementVisitor},
         token::{Token, TokenType},
     },
-    JSPlusPlus, RuntimeError,
+    ErrorKind, RuntimeError,
+};
+
+use self::{
+    bigint::BigInt,
+    callable::{Callable, Function, NativeFunction},
+    class::{class_of, instance_class, operator_method, Class, FieldInit, FieldKey, Member},
+    environment::Environment,
+    event_loop::{EventLoop, Job},
+    generator::Yielder,
+    iterator::Iteration,
+    object::{Namespace, Object, ObjectKind, PrivateSlot},
+    value::{format_number, Value},
 };
 
-pub struct Interpreter;
+mod array;
+pub mod bigint;
+pub mod callable;
+pub mod class;
+pub mod collection;
+mod console;
+pub mod enumeration;
+pub mod environment;
+pub mod event_loop;
+pub mod generator;
+pub mod iterator;
+pub mod object;
+pub mod promise;
+pub mod record;
+pub mod regex;
+pub mod regexp;
+mod string;
+pub mod symbol;
+pub mod value;
+
+/// Deep enough for ordinary recursion while staying clear of the native stack.
+const MAX_CALL_DEPTH: usize = 1024;
+
+/// Everything that can cut the evaluation of a statement short. Loops,
+/// labeled statements and function calls catch the variants they own and
+/// let the rest propagate.
+pub enum Unwind {
+    Error(RuntimeError),
+    Break(Option<String>),
+    Continue(Option<String>),
+    Return(Value),
+    Throw {
+        value: Value,
+        token: Token,
+    },
+    /// Leaves an optional chain at a `?.` that found nil.
+    ShortCircuit,
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        Unwind::Error(error)
+    }
+}
+
+struct Frame {
+    name: String,
+    /// The call expression that entered this frame.
+    paren: Token,
+}
+
+pub struct Interpreter {
+    pub globals: Rc<RefCell<Environment>>,
+    environment: Rc<RefCell<Environment>>,
+    frames: Vec<Frame>,
+    /// Counts the private names handed out, so that each class declaration
+    /// gets keys of its own.
+    private_names: Rc<Cell<usize>>,
+    /// The namespace objects of modules that have finished running.
+    modules: HashMap<PathBuf, Value>,
+    /// Where imports in the running script or module are resolved from.
+    directory: PathBuf,
+    /// The running module's exports, as exported names and the local
+    /// bindings they are read from.
+    exports: Vec<(String, Token)>,
+    max_call_depth: usize,
+    /// Set while running a generator's body, to suspend it at `yield`.
+    yielder: Option<*const Yielder>,
+    event_loop: Rc<RefCell<EventLoop>>,
+}
 
 impl Interpreter {
     pub fn new() -> Interpreter {
-        Interpreter
+        Interpreter::with_event_loop(Rc::new(RefCell::new(EventLoop::new())))
     }
 
-    pub fn interpret(&mut self, expression: &Expression) {
-        let value = self.evaluate(expression);
+    /// Creates an interpreter that queues promise reactions and timers on
+    /// `event_loop`, for its owner to run.
+    pub fn with_event_loop(event_loop: Rc<RefCell<EventLoop>>) -> Interpreter {
+        let globals = Rc::new(RefCell::new(Environment::new()));
 
-        match value {
-            std::result::Result::Ok(value) => println!("{}", self.stringify(value)),
-            Err(error) => JSPlusPlus::error(0, &error.to_string()),
+        globals.borrow_mut().define(
+            "clock",
+            Value::Callable(Rc::new(NativeFunction::new("clock", 0, |_, _| {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                Ok(Value::Number(now.as_secs_f64()))
+            }))),
+        );
+
+        globals
+            .borrow_mut()
+            .define_const("Symbol", symbol::symbol());
+
+        for (name, function) in [
+            ("Error", native_error as callable::NativeFn),
+            ("TypeError", native_type_error),
+            ("ReferenceError", native_reference_error),
+            ("RangeError", native_range_error),
+            ("SyntaxError", native_syntax_error),
+        ] {
+            globals.borrow_mut().define(
+                name,
+                Value::Callable(Rc::new(NativeFunction::with_arity(
+                    name,
+                    0,
+                    Some(1),
+                    function,
+                ))),
+            );
+        }
+
+        globals.borrow_mut().define("console", console::console());
+        globals.borrow_mut().define(
+            "print",
+            Value::Callable(Rc::new(NativeFunction::with_arity(
+                "print",
+                0,
+                None,
+                console::print,
+            ))),
+        );
+        globals.borrow_mut().define("RegExp", regexp::constructor());
+        for (name, constructor) in collection::constructors() {
+            globals.borrow_mut().define(name, constructor);
+        }
+        globals.borrow_mut().define(
+            "BigInt",
+            Value::Callable(Rc::new(NativeFunction::new("BigInt", 1, native_bigint))),
+        );
+        globals
+            .borrow_mut()
+            .define("Promise", promise::constructor());
+        for (name, min_arity, max_arity, function) in [
+            (
+                "queueMicrotask",
+                1,
+                Some(1),
+                event_loop::queue_microtask as callable::NativeFn,
+            ),
+            ("setTimeout", 1, None, event_loop::set_timeout),
+            ("clearTimeout", 0, Some(1), event_loop::clear_timeout),
+        ] {
+            globals.borrow_mut().define(
+                name,
+                Value::Callable(Rc::new(NativeFunction::with_arity(
+                    name, min_arity, max_arity, function,
+                ))),
+            );
+        }
+
+        Interpreter {
+            environment: globals.clone(),
+            globals,
+            frames: Vec::new(),
+            private_names: Rc::new(Cell::new(0)),
+            modules: HashMap::new(),
+            directory: std::env::current_dir().unwrap_or_default(),
+            exports: Vec::new(),
+            max_call_depth: MAX_CALL_DEPTH,
+            yielder: None,
+            event_loop,
+        }
+    }
+
+    /// An interpreter sharing this one's globals, for running code such as
+    /// a generator's body on a native stack of its own. Its call stack
+    /// starts out holding only `frame`.
+    fn fork(&self, frame: Frame, max_call_depth: usize) -> Interpreter {
+        Interpreter {
+            globals: self.globals.clone(),
+            environment: self.environment.clone(),
+            frames: vec![frame],
+            private_names: self.private_names.clone(),
+            modules: self.modules.clone(),
+            directory: self.directory.clone(),
+            exports: Vec::new(),
+            max_call_depth,
+            yielder: None,
+            event_loop: self.event_loop.clone(),
         }
     }
 
-    fn stringify(&self, value: Box<dyn Any>) -> String {
-        match value.downcast_ref::<f64>() {
-            Some(value) => {
-                let text = value.to_string();
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
 
-                if text.ends_with(".0") {
-                    text[..text.len() - 2].to_string()
-                } else {
-                    text
-                }
+    /// Sets the directory that imports are resolved from and returns the
+    /// previous one.
+    pub fn set_directory(&mut self, directory: PathBuf) -> PathBuf {
+        std::mem::replace(&mut self.directory, directory)
+    }
+
+    pub fn has_module(&self, path: &Path) -> bool {
+        self.modules.contains_key(path)
+    }
+
+    /// Runs a module in a scope of its own and caches its namespace object,
+    /// whose exports read the module's variables as they are now. Every
+    /// module it imports must already have been run.
+    pub fn interpret_module(
+        &mut self,
+        statements: &[Statement],
+        path: PathBuf,
+    ) -> Result<(), RuntimeError> {
+        let environment = Rc::new(RefCell::new(Environment::with_enclosing(
+            self.globals.clone(),
+        )));
+        let previous = std::mem::replace(&mut self.environment, environment.clone());
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let directory = self.set_directory(directory);
+        let exports = std::mem::take(&mut self.exports);
+
+        let result = self.interpret(statements);
+
+        self.environment = previous;
+        self.set_directory(directory);
+        let exports = std::mem::replace(&mut self.exports, exports);
+        result?;
+
+        for (_, local) in &exports {
+            environment.borrow().get(local).map_err(|_| {
+                RuntimeError::with_kind(
+                    local.clone(),
+                    ErrorKind::ReferenceError,
+                    &format!(
+                        "Uncaught ReferenceError: Export '{}' is not defined.",
+                        local.lexeme
+                    ),
+                )
+            })?;
+        }
+        let namespace = Namespace {
+            environment,
+            exports,
+        };
+        let namespace = Object::new(ObjectKind::Module(Rc::new(namespace)));
+        self.modules
+            .insert(path, Value::Object(Rc::new(RefCell::new(namespace))));
+        Ok(())
+    }
+
+    pub fn interpret(&mut self, statements: &[Statement]) -> Result<(), RuntimeError> {
+        for statement in statements {
+            if let Err(unwind) = self.execute(statement) {
+                return self.uncaught(unwind);
             }
-            None => match value.downcast_ref::<bool>() {
-                Some(value) => value.to_string(),
-                None => match value.downcast_ref::<String>() {
-                    Some(value) => value.to_string(),
-                    None => "nil".to_string(),
-                },
-            },
+        }
+        Ok(())
+    }
+
+    /// Runs a job from the event loop. Errors it does not catch end the
+    /// script, as they do in top-level code.
+    pub fn run_job(&mut self, job: Job) -> Result<(), RuntimeError> {
+        let result = match job {
+            Job::Callback {
+                function,
+                arguments,
+                token,
+            } => self.call_back(&token, function, arguments).map(|_| ()),
+            Job::Reaction { reaction, outcome } => promise::run_reaction(self, reaction, outcome),
+        };
+        result.or_else(|unwind| self.uncaught(unwind))
+    }
+
+    fn uncaught(&self, unwind: Unwind) -> Result<(), RuntimeError> {
+        match unwind {
+            Unwind::Error(error) => Err(RuntimeError::with_kind(
+                error.token,
+                error.kind,
+                &format!("Uncaught {}: {}", error.kind.name(), error.message),
+            )),
+            Unwind::Throw { value, token } => Err(RuntimeError::with_kind(
+                token,
+                ErrorKind::Error,
+                &format!("Uncaught {}", value),
+            )),
+            // The resolver rejects `break`, `continue` and `return` outside
+            // of their enclosing constructs.
+            _ => Ok(()),
+        }
+    }
+
+    pub fn execute_block(
+        &mut self,
+        statements: &[Statement],
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<(), Unwind> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+
+        let mut result = Ok(());
+        for statement in statements {
+            result = self.execute(statement);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.environment = previous;
+        result
+    }
+
+    pub fn evaluate_in(
+        &mut self,
+        expression: &Expression,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Value, Unwind> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+        let result = self.evaluate(expression);
+        self.environment = previous;
+        result
+    }
+
+    /// Binds the names in `pattern` as `let` does, inside `environment`.
+    pub fn bind_pattern_in(
+        &mut self,
+        pattern: &Pattern,
+        value: Value,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<(), Unwind> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+        let result = self.bind_pattern(pattern, value, Binding::Let);
+        self.environment = previous;
+        result
+    }
+
+    fn execute(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        statement.accept(self)
+    }
+
+    fn evaluate(&mut self, expression: &Expression) -> Result<Value, Unwind> {
+        expression.accept(self)
+    }
+
+    /// Renders the call stack below `depth` the way an error's `stack`
+    /// property shows it, starting at `location` in the innermost frame.
+    fn stack_trace(&self, depth: usize, location: String) -> Vec<String> {
+        let mut trace = Vec::new();
+        let mut location = location;
+        for frame in self.frames[..depth].iter().rev() {
+            trace.push(format!("at {} ({})", frame.name, location));
+            location = frame.paren.location();
+        }
+        trace.push(format!("at <script> ({})", location));
+        trace
+    }
+
+    fn error_object(&self, kind: ErrorKind, message: &str, stack: &[String]) -> Value {
+        let mut object = Object::new(ObjectKind::Error);
+        object.set("name", Value::from(kind.name()));
+        object.set("message", Value::from(message));
+
+        let mut text = if message.is_empty() {
+            kind.name().to_string()
+        } else {
+            format!("{}: {}", kind.name(), message)
+        };
+        for frame in stack {
+            text.push_str("\n    ");
+            text.push_str(frame);
+        }
+        object.set("stack", Value::from(text));
+
+        Value::Object(Rc::new(RefCell::new(object)))
+    }
+
+    /// Turns a runtime error into a thrown error object while the frames it
+    /// happened in are still on the stack.
+    fn throw_error(&self, error: RuntimeError) -> Unwind {
+        let stack = self.stack_trace(self.frames.len(), error.token.location());
+        Unwind::Throw {
+            value: self.error_object(error.kind, &error.message, &stack),
+            token: error.token,
         }
     }
 
-    fn evaluate(&mut self, expression: &Expression) -> Result<Box<dyn Any>> {
-        Ok(expression.accept(self))
+    /// Turns anything `catch` can intercept into the value bound to its
+    /// parameter. Other unwinds are handed back untouched.
+    fn exception_value(&self, unwind: Unwind) -> Result<Value, Unwind> {
+        match unwind {
+            Unwind::Error(error) => self.exception_value(self.throw_error(error)),
+            Unwind::Throw { value, token: _ } => Ok(value),
+            unwind => Err(unwind),
+        }
     }
 
-    fn is_truthy(&self, value: &Box<dyn Any>) -> bool {
-        match value.downcast_ref::<bool>() {
-            Some(value) => *value,
-            None => false,
+    fn is_truthy(&self, value: &Value) -> bool {
+        match value {
+            Value::Nil => false,
+            Value::Bool(value) => *value,
+            Value::Number(value) => *value != 0.0 && !value.is_nan(),
+            Value::BigInt(value) => !value.is_zero(),
+            Value::String(value) => !value.is_empty(),
+            Value::Callable(_) | Value::Object(_) => true,
         }
     }
 
-    fn is_equal(&self, left: &Box<dyn Any>, right: &Box<dyn Any>) -> bool {
-        match (left.downcast_ref::<bool>(), right.downcast_ref::<bool>()) {
-            (Some(left), Some(right)) => left == right,
-            _ => false,
+    fn is_equal(&self, left: &Value, right: &Value) -> bool {
+        left.equals(right)
+    }
+
+    /// Calls the method a class defines for a binary operator, as in
+    /// `operator +(other) {}`. The left operand's method is used if it has
+    /// one. Failing that, the right operand's is only if it declares a
+    /// second parameter, as in `operator *(other, reversed) {}`, which tells
+    /// the method whether `other` was on the left. A string on the left of
+    /// `+` is never handed to the right operand, since `+` joins them.
+    fn overloaded_operator(
+        &mut self,
+        operator: &Token,
+        left: &Value,
+        right: &Value,
+    ) -> Result<Option<Value>, Unwind> {
+        let Some(name) = operator_method(operator.token_type) else {
+            return Ok(None);
+        };
+        let joins = operator.token_type == TokenType::Plus && matches!(left, Value::String(_));
+        let candidates = [(left, right, false), (right, left, true)];
+        for (this, other, reversed) in candidates {
+            let method = match instance_class(this).and_then(|class| class.find(name, false)) {
+                Some(Member::Method(method)) => method,
+                _ => continue,
+            };
+            let flagged = method.max_arity().is_none_or(|max| max >= 2);
+            if reversed && (joins || !flagged) {
+                continue;
+            }
+            let mut arguments = vec![other.clone()];
+            if flagged {
+                arguments.push(Value::Bool(reversed));
+            }
+            let bound = Rc::new(method.bind(this.clone()));
+            return Ok(Some(self.call(operator, bound, arguments)?));
+        }
+
+        // `a != b` is `!(a == b)` when only `==` is defined.
+        if operator.token_type == TokenType::BangEqual {
+            let equal = Token {
+                token_type: TokenType::EqualEqual,
+                ..operator.clone()
+            };
+            if let Some(equal) = self.overloaded_operator(&equal, left, right)? {
+                return Ok(Some(Value::Bool(!self.is_truthy(&equal))));
+            }
         }
+        Ok(None)
     }
 
-    fn check_number_operand(&self, operator: &Token, operand: &Box<dyn Any>) -> Result<()> {
-        if operand.is::<f64>() {
+    /// Reports an instance used with an operator its class does not
+    /// overload. Equality still compares instances by identity, as `===`
+    /// and `!==` always do, and `+` still joins them to strings.
+    fn check_not_overloaded(
+        &self,
+        operator: &Token,
+        left: &Value,
+        right: &Value,
+    ) -> Result<(), RuntimeError> {
+        let (left_class, right_class) = (instance_class(left), instance_class(right));
+        if left_class.is_none() && right_class.is_none() {
+            return Ok(());
+        }
+        let allowed = match operator.token_type {
+            TokenType::EqualEqual
+            | TokenType::BangEqual
+            | TokenType::EqualEqualEqual
+            | TokenType::BangEqualEqual => true,
+            TokenType::Plus => {
+                matches!(left, Value::String(_)) || matches!(right, Value::String(_))
+            }
+            _ => operator_method(operator.token_type).is_none(),
+        };
+        if allowed {
             return Ok(());
         }
 
-        bail!(RuntimeError::new(
+        let describe = |value: &Value, class: Option<Rc<Class>>| match class {
+            Some(class) => class.name.clone(),
+            None => value.type_name().to_string(),
+        };
+        Err(RuntimeError::new(
             operator.clone(),
-            "Operand must be a number."
-        ));
+            &format!(
+                "Operator '{}' is not defined for {} and {}.",
+                operator.lexeme,
+                describe(left, left_class),
+                describe(right, right_class)
+            ),
+        ))
+    }
+
+    fn check_number_operand(&self, operator: &Token, operand: &Value) -> Result<f64, RuntimeError> {
+        match operand {
+            Value::Number(value) => Ok(*value),
+            _ => Err(RuntimeError::new(
+                operator.clone(),
+                "Operand must be a number.",
+            )),
+        }
     }
 
     fn check_number_operands(
         &self,
         operator: &Token,
-        left: &Box<dyn Any>,
-        right: &Box<dyn Any>,
-    ) -> Result<()> {
-        if left.is::<f64>() && right.is::<f64>() {
-            return Ok(());
+        left: &Value,
+        right: &Value,
+    ) -> Result<(f64, f64), RuntimeError> {
+        match (left, right) {
+            (Value::Number(left), Value::Number(right)) => Ok((*left, *right)),
+            _ => Err(RuntimeError::new(
+                operator.clone(),
+                &format!(
+                    "Operands must be numbers. Got {} and {} instead.",
+                    left.type_name(),
+                    right.type_name()
+                ),
+            )),
+        }
+    }
+
+    fn compare(&self, operator: &Token, left: &Value, right: &Value) -> Result<bool, RuntimeError> {
+        if let (Value::String(left), Value::String(right)) = (left, right) {
+            return Ok(match operator.token_type {
+                TokenType::Greater => left > right,
+                TokenType::GreaterEqual => left >= right,
+                TokenType::Less => left < right,
+                _ => left <= right,
+            });
+        }
+        if let (Value::BigInt(left), Value::BigInt(right)) = (left, right) {
+            return Ok(match operator.token_type {
+                TokenType::Greater => left > right,
+                TokenType::GreaterEqual => left >= right,
+                TokenType::Less => left < right,
+                _ => left <= right,
+            });
         }
 
-        let message = format!(
-            "Operands must be numbers. Got {:?} and {:?} instead.",
-            left, right
-        );
+        let (left, right) = self.check_number_operands(operator, left, right)?;
+        Ok(match operator.token_type {
+            TokenType::Greater => left > right,
+            TokenType::GreaterEqual => left >= right,
+            TokenType::Less => left < right,
+            _ => left <= right,
+        })
+    }
+
+    /// Applies an arithmetic operator to two BigInts. Numbers and BigInts
+    /// never mix, since converting either way could lose precision.
+    fn bigint_arithmetic(
+        &self,
+        operator: &Token,
+        left: &Value,
+        right: &Value,
+    ) -> Result<Option<Value>, RuntimeError> {
+        let (left, right) = match (left, right) {
+            (Value::BigInt(left), Value::BigInt(right)) => (left, right),
+            (Value::BigInt(_), Value::Number(_)) | (Value::Number(_), Value::BigInt(_)) => {
+                return Err(RuntimeError::new(
+                    operator.clone(),
+                    "Cannot mix BigInt and other types, use explicit conversions.",
+                ))
+            }
+            _ => return Ok(None),
+        };
+        let result = match operator.token_type {
+            TokenType::Plus => Ok(left.add(right)),
+            TokenType::Minus => Ok(left.subtract(right)),
+            TokenType::Star => Ok(left.multiply(right)),
+            TokenType::Slash => left.divide(right),
+            TokenType::Percent => left.remainder(right),
+            TokenType::StarStar => left.pow(right),
+            _ => return Ok(None),
+        };
+        match result {
+            Ok(value) => Ok(Some(Value::BigInt(Rc::new(value)))),
+            Err(failure) => Err(RuntimeError::with_kind(
+                operator.clone(),
+                ErrorKind::RangeError,
+                failure.message(),
+            )),
+        }
+    }
+
+    fn execute_switch(&mut self, value: &Value, cases: &[SwitchCase]) -> Result<(), Unwind> {
+        let mut start = None;
+        for (index, case) in cases.iter().enumerate() {
+            if let Some(test) = &case.test {
+                let test = self.evaluate(test)?;
+                if self.is_equal(value, &test) {
+                    start = Some(index);
+                    break;
+                }
+            }
+        }
+
+        let start = match start.or_else(|| cases.iter().position(|case| case.test.is_none())) {
+            Some(start) => start,
+            None => return Ok(()),
+        };
 
-        // Err(RuntimeError::new(
-        //     operator.clone(),
-        //     Into::<std::str::Split<'_, &str>>::into(
-        //         message.as_str().split("").into_iter().copied(),
-        //     )
-        //     .,
-        // ))
+        // Execution falls through every clause after the matching one until
+        // something breaks out of the switch.
+        for case in &cases[start..] {
+            for statement in &case.body {
+                self.execute(statement)?;
+            }
+        }
         Ok(())
     }
-}
 
-impl ExpressionVisitor<Box<dyn Any>> for Interpreter {
-    fn visit_assign_expression(&mut self, expr: &Expression) -> Box<dyn Any> {
-        todo!()
+    fn check_arity(
+        &self,
+        paren: &Token,
+        function: &dyn Callable,
+        count: usize,
+    ) -> Result<(), RuntimeError> {
+        let min = function.min_arity();
+        let max = function.max_arity();
+
+        let message = if max == Some(min) && count != min {
+            format!("Expected {} arguments but got {}.", min, count)
+        } else if count < min {
+            format!("Expected at least {} arguments but got {}.", min, count)
+        } else {
+            match max {
+                Some(max) if count > max => {
+                    format!("Expected at most {} arguments but got {}.", max, count)
+                }
+                _ => return Ok(()),
+            }
+        };
+        Err(RuntimeError::new(paren.clone(), &message))
+    }
+
+    fn call(
+        &mut self,
+        paren: &Token,
+        function: Rc<dyn Callable>,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Unwind> {
+        self.check_arity(paren, function.as_ref(), arguments.len())?;
+
+        if self.frames.len() >= self.max_call_depth {
+            return Err(RuntimeError::with_kind(
+                paren.clone(),
+                ErrorKind::RangeError,
+                "Maximum call stack size exceeded.",
+            )
+            .into());
+        }
+
+        self.frames.push(Frame {
+            name: function.name(),
+            paren: paren.clone(),
+        });
+        let result = match function.call(self, arguments) {
+            Err(Unwind::Error(error)) => Err(self.throw_error(error)),
+            result => result,
+        };
+        self.frames.pop();
+        result
+    }
+
+    /// Calls a callback with as many of `arguments` as it declares, so that
+    /// `map(x => x * 2)` does not trip over the index and array arguments.
+    fn call_back(
+        &mut self,
+        paren: &Token,
+        callback: Rc<dyn Callable>,
+        mut arguments: Vec<Value>,
+    ) -> Result<Value, Unwind> {
+        if let Some(max) = callback.max_arity() {
+            arguments.truncate(max);
+        }
+        self.call(paren, callback, arguments)
+    }
+
+    /// The call expression that entered the innermost frame, which is where
+    /// errors raised by native functions are reported.
+    fn call_site(&self) -> Token {
+        self.frames
+            .last()
+            .map(|frame| frame.paren.clone())
+            .expect("native functions run inside a call frame")
+    }
+
+    /// Gives a new instance the private methods and fields of `class` and
+    /// its superclasses, base class first.
+    fn initialize_instance(&mut self, class: &Rc<Class>, instance: &Value) -> Result<(), Unwind> {
+        if let Some(superclass) = class.superclass() {
+            self.initialize_instance(&superclass, instance)?;
+        }
+        self.install_private_members(class, false, instance);
+        self.initialize_fields(&class.fields, class.environment.clone(), instance)
+    }
+
+    fn install_private_members(&self, class: &Class, is_static: bool, target: &Value) {
+        if let Value::Object(object) = target {
+            for (key, member) in class.private_members(is_static) {
+                object
+                    .borrow_mut()
+                    .set_private(key, PrivateSlot::Member(member.clone()));
+            }
+        }
+    }
+
+    fn initialize_fields(
+        &mut self,
+        fields: &[FieldInit],
+        closure: Rc<RefCell<Environment>>,
+        this: &Value,
+    ) -> Result<(), Unwind> {
+        let object = match this {
+            Value::Object(object) => object,
+            _ => panic!("Expected object receiver"),
+        };
+
+        let mut environment = Environment::with_enclosing(closure);
+        environment.define("this", this.clone());
+        let environment = Rc::new(RefCell::new(environment));
+
+        for field in fields {
+            let value = match &field.initializer {
+                Some(initializer) => self.evaluate_in(initializer, environment.clone())?,
+                None => Value::Nil,
+            };
+            match &field.key {
+                FieldKey::Public(name) => object.borrow_mut().set(name, value),
+                FieldKey::Private(key) => object
+                    .borrow_mut()
+                    .set_private(key, PrivateSlot::Field(value)),
+            }
+        }
+        Ok(())
+    }
+
+    fn private_key(&self, name: &Token) -> Result<String, RuntimeError> {
+        Ok(self.environment.borrow().get(name)?.to_string())
+    }
+
+    fn get_private(&mut self, name: &Token, object: &Value) -> Result<Value, Unwind> {
+        let key = self.private_key(name)?;
+        let slot = match object {
+            Value::Object(object) => object.borrow().get_private(&key),
+            _ => None,
+        };
+        match slot {
+            Some(PrivateSlot::Field(value)) => Ok(value),
+            Some(PrivateSlot::Member(member)) => self.read_member(name, object, Some(member)),
+            None => Err(RuntimeError::new(
+                name.clone(),
+                &format!(
+                    "Cannot read private member {} from an object whose class did not declare it.",
+                    name.lexeme
+                ),
+            )
+            .into()),
+        }
+    }
+
+    fn set_private(&mut self, name: &Token, object: &Value, value: Value) -> Result<(), Unwind> {
+        let key = self.private_key(name)?;
+        let slot = match object {
+            Value::Object(reference) => reference.borrow().get_private(&key),
+            _ => None,
+        };
+
+        let message = match slot {
+            Some(PrivateSlot::Field(_)) => {
+                if let Value::Object(reference) = object {
+                    reference
+                        .borrow_mut()
+                        .set_private(&key, PrivateSlot::Field(value));
+                }
+                return Ok(());
+            }
+            Some(PrivateSlot::Member(Member::Accessor {
+                getter: _,
+                setter: Some(setter),
+            })) => {
+                self.call(name, Rc::new(setter.bind(object.clone())), vec![value])?;
+                return Ok(());
+            }
+            Some(PrivateSlot::Member(Member::Accessor { .. })) => {
+                format!("'{}' was defined without a setter.", name.lexeme)
+            }
+            Some(PrivateSlot::Member(Member::Method(_))) => {
+                format!("Private method {} is not writable.", name.lexeme)
+            }
+            None => format!(
+                "Cannot write private member {} to an object whose class did not declare it.",
+                name.lexeme
+            ),
+        };
+        Err(RuntimeError::new(name.clone(), &message).into())
+    }
+
+    /// Evaluates the callee of a call, along with the object it was read
+    /// from when it is a property, which methods of object literals take
+    /// as `this`.
+    fn evaluate_callee(&mut self, callee: &Expression) -> Result<(Value, Option<Value>), Unwind> {
+        match callee {
+            Expression::Get {
+                object,
+                name,
+                optional,
+            } if name.token_type != TokenType::PrivateName => {
+                let object = self.evaluate(object)?;
+                if *optional && matches!(object, Value::Nil) {
+                    return Err(Unwind::ShortCircuit);
+                }
+                let callee = self.get_property(name, &object, &name.lexeme)?;
+                Ok((callee, Some(object)))
+            }
+            Expression::Index {
+                object,
+                bracket,
+                index,
+                optional,
+            } => {
+                let object = self.evaluate(object)?;
+                if *optional && matches!(object, Value::Nil) {
+                    return Err(Unwind::ShortCircuit);
+                }
+                let index = self.evaluate(index)?;
+                let key = self.property_key(&index);
+                let callee = self.get_property(bracket, &object, &key)?;
+                Ok((callee, Some(object)))
+            }
+            _ => Ok((self.evaluate(callee)?, None)),
+        }
     }
 
-    fn visit_binary_expression(&mut self, expr: &Expression) -> Box<dyn Any> {
+    /// Creates the function a function expression evaluates to. Methods of
+    /// object literals take `this` from the object they are called on.
+    fn function(&self, expr: &Expression, is_method: bool) -> Value {
         match expr {
-            Expression::Binary {
-                left,
-                operator,
-                right,
+            Expression::Function {
+                name,
+                params,
+                return_type: _,
+                body,
+                arrow: _,
+                is_generator,
+                is_async,
             } => {
-                let left = left.accept(self);
-                let right = right.accept(self);
+                // A named function expression can refer to itself, but the
+                // name is not visible in the surrounding scope.
+                let closure = match name {
+                    Some(_) => Rc::new(RefCell::new(Environment::with_enclosing(
+                        self.environment.clone(),
+                    ))),
+                    None => self.environment.clone(),
+                };
 
-                match operator.token_type {
-                    TokenType::Minus => {
-                        let left = left.downcast_ref::<f64>().unwrap();
-                        let right = right.downcast_ref::<f64>().unwrap();
-                        Box::new(left - right)
-                    }
-                    TokenType::Plus => {
-                        if left.is::<String>() || right.is::<String>() {
-                            let left = left.downcast_ref::<String>().unwrap();
-                            let right = right.downcast_ref::<String>().unwrap();
-                            Box::new(format!("{}{}", left, right))
-                        } else {
-                            let left = left.downcast_ref::<f64>().unwrap();
-                            let right = right.downcast_ref::<f64>().unwrap();
-                            Box::new(left + right)
-                        }
-                    }
-                    TokenType::Slash => {
-                        if let Err(err) = self.check_number_operands(&operator, &left, &right) {
-                            panic!("{}", err);
-                        }
-                        let left = left.downcast_ref::<f64>().unwrap();
-                        let right = right.downcast_ref::<f64>().unwrap();
-                        Box::new(left / right)
-                    }
-                    TokenType::Star => {
-                        if let Err(err) = self.check_number_operands(&operator, &left, &right) {
-                            panic!("{}", err);
-                        }
-                        let left = left.downcast_ref::<f64>().unwrap();
-                        let right = right.downcast_ref::<f64>().unwrap();
-                        Box::new(left * right)
-                    }
-                    TokenType::Greater => {
-                        if let Err(err) = self.check_number_operands(&operator, &left, &right) {
-                            panic!("{}", err);
-                        }
-                        let left = left.downcast_ref::<f64>().unwrap();
-                        let right = right.downcast_ref::<f64>().unwrap();
-                        Box::new(left > right)
-                    }
-                    TokenType::GreaterEqual => {
-                        if let Err(err) = self.check_number_operands(&operator, &left, &right) {
-                            panic!("{}", err);
-                        }
-                        let left = left.downcast_ref::<f64>().unwrap();
-                        let right = right.downcast_ref::<f64>().unwrap();
-                        Box::new(left >= right)
-                    }
-                    TokenType::Less => {
-                        if let Err(err) = self.check_number_operands(&operator, &left, &right) {
-                            panic!("{}", err);
-                        }
-                        let left = left.downcast_ref::<f64>().unwrap();
-                        let right = right.downcast_ref::<f64>().unwrap();
-                        Box::new(left < right)
-                    }
-                    TokenType::LessEqual => {
-                        if let Err(err) = self.check_number_operands(&operator, &left, &right) {
-                            panic!("{}", err);
-                        }
-                        let left = left.downcast_ref::<f64>().unwrap();
-                        let right = right.downcast_ref::<f64>().unwrap();
-                        Box::new(left <= right)
-                    }
-                    TokenType::BangEqual => Box::new(!self.is_equal(&left, &right)),
-                    TokenType::EqualEqual => Box::new(self.is_equal(&left, &right)),
-                    _ => Box::new(()),
+                let function = Function::new(
+                    name.clone(),
+                    params.clone(),
+                    body.clone(),
+                    closure.clone(),
+                    *is_generator,
+                    *is_async,
+                );
+                let function = Value::Callable(Rc::new(match is_method {
+                    true => function.into_method(),
+                    false => function,
+                }));
+                if let Some(name) = name {
+                    closure.borrow_mut().define(&name.lexeme, function.clone());
                 }
+                function
+            }
+            _ => panic!("Expected function expression"),
+        }
+    }
+
+    fn evaluate_arguments(&mut self, arguments: &[Expression]) -> Result<Vec<Value>, Unwind> {
+        let mut values = Vec::new();
+        for argument in arguments {
+            match argument {
+                Expression::Spread {
+                    ellipsis,
+                    expression,
+                } => {
+                    let iterable = self.evaluate(expression)?;
+                    values.extend(self.iterate(ellipsis, &iterable)?);
+                }
+                _ => values.push(self.evaluate(argument)?),
             }
-            _ => panic!("Expected binary expression"),
         }
+        Ok(values)
     }
 
-    fn visit_call_expression(&mut self, expr: &Expression) -> Box<dyn Any> {
-        todo!()
+    /// Expands an iterable value into its elements, as spreading does.
+    fn iterate(&mut self, token: &Token, value: &Value) -> Result<Vec<Value>, Unwind> {
+        let mut iteration = Iteration::new(self, token, value)?;
+        let mut values = Vec::new();
+        while let Some(value) = iteration.next(self, token)? {
+            values.push(value);
+        }
+        Ok(values)
     }
 
-    fn visit_get_expression(&mut self, expr: &Expression) -> Box<dyn Any> {
-        todo!()
+    /// The keys a `for...in` loop visits: array indices and then the own
+    /// properties of an object, or the indices of a string.
+    fn enumerable_keys(&self, value: &Value) -> Vec<Value> {
+        let (length, keys) = match value {
+            Value::String(value) => (value.chars().count(), Vec::new()),
+            Value::Object(object) => {
+                let object = object.borrow();
+                let length = match &object.kind {
+                    ObjectKind::Array(elements) | ObjectKind::Tuple(elements) => elements.len(),
+                    _ => 0,
+                };
+                let keys = match &object.kind {
+                    ObjectKind::Module(namespace) => namespace
+                        .exports
+                        .iter()
+                        .map(|(name, _)| Value::from(name.as_str()))
+                        .collect(),
+                    _ => object
+                        .keys()
+                        .iter()
+                        .filter(|key| !symbol::is_symbol_key(key))
+                        .map(|key| Value::from(symbol::display_key(key)))
+                        .collect(),
+                };
+                (length, keys)
+            }
+            _ => (0, Vec::new()),
+        };
+        (0..length)
+            .map(|index| Value::from(index.to_string()))
+            .chain(keys)
+            .collect()
     }
 
-    fn visit_grouping_expression(&mut self, expr: &Expression) -> Box<dyn Any> {
-        match expr {
-            Expression::Grouping { expression: _ } => expr.accept(self),
-            _ => panic!("Expected grouping expression"),
+    /// Converts a computed key into the string it is stored under.
+    fn property_key(&self, key: &Value) -> String {
+        match key {
+            Value::Number(value) => format_number(*value),
+            Value::String(key) => symbol::string_key(key),
+            _ => match symbol::symbol_key(key) {
+                Some(key) => key.to_string(),
+                None => key.to_string(),
+            },
         }
     }
 
-    fn visit_literal_expression(&mut self, expr: &Expression) -> Box<dyn Any> {
-        match expr {
-            Expression::Literal { value } => Box::new(value.clone()),
-            _ => panic!("Expected literal expression"),
+    /// Enforces the `private` and `protected` modifiers of the member `key`
+    /// of `object`. A private member is only visible inside the class that
+    /// declares it, and a protected one also inside the subclasses between
+    /// that class and the class of `object`.
+    fn check_access(&self, token: &Token, object: &Value, key: &str) -> Result<(), RuntimeError> {
+        let (mut class, is_static) = match (instance_class(object), class_of(object)) {
+            (Some(class), _) => (Some(class), false),
+            (None, Some(class)) => (Some(class), true),
+            _ => return Ok(()),
+        };
+
+        let environment = self.environment.borrow();
+        let mut in_subclass = false;
+        while let Some(current) = class {
+            let inside = environment.is_within(&current.environment);
+            let Some(access) = current.access(key, is_static) else {
+                in_subclass = in_subclass || inside;
+                class = current.superclass();
+                continue;
+            };
+            let allowed = match access {
+                Access::Public => true,
+                Access::Private => inside,
+                Access::Protected => inside || in_subclass,
+            };
+            if allowed {
+                return Ok(());
+            }
+            let message = match access {
+                Access::Protected => format!(
+                    "Property '{}' is protected and only accessible within class '{}' and its subclasses.",
+                    key, current.name
+                ),
+                _ => format!(
+                    "Property '{}' is private and only accessible within class '{}'.",
+                    key, current.name
+                ),
+            };
+            return Err(RuntimeError::new(token.clone(), &message));
         }
+        Ok(())
     }
 
-    fn visit_logical_expression(&mut self, expr: &Expression) -> Box<dyn Any> {
-        todo!()
+    fn get_property(&mut self, token: &Token, object: &Value, key: &str) -> Result<Value, Unwind> {
+        self.check_access(token, object, key)?;
+        let reference = match object {
+            Value::Object(reference) => reference,
+            Value::Nil => {
+                return Err(RuntimeError::new(
+                    token.clone(),
+                    &format!("Cannot read property '{}' of nil.", key),
+                )
+                .into())
+            }
+            Value::String(_) => {
+                return Ok(callable::method(string::METHODS, object, key).unwrap_or(Value::Nil))
+            }
+            _ => return Ok(Value::Nil),
+        };
+
+        let member = {
+            let reference = reference.borrow();
+            if let Some(value) = reference.get(key) {
+                return Ok(value);
+            }
+            match &reference.kind {
+                ObjectKind::Array(elements) => {
+                    return Ok(if key == "length" {
+                        Value::from(elements.len() as f64)
+                    } else if let Some(index) = array::index(key) {
+                        elements.get(index).cloned().unwrap_or(Value::Nil)
+                    } else {
+                        callable::method(array::METHODS, object, key).unwrap_or(Value::Nil)
+                    })
+                }
+                ObjectKind::Class(class) if key == "name" => {
+                    return Ok(Value::from(class.name.as_str()))
+                }
+                ObjectKind::Class(class) => class.find(key, true),
+                ObjectKind::Instance(class) => class.find(key, false),
+                ObjectKind::Generator(_) => {
+                    return Ok(
+                        callable::method(generator::METHODS, object, key).unwrap_or(Value::Nil)
+                    )
+                }
+                ObjectKind::Iterator(_) => {
+                    return Ok(
+                        callable::method(iterator::METHODS, object, key).unwrap_or(Value::Nil)
+                    )
+                }
+                ObjectKind::Map(entries) | ObjectKind::Set(entries) if key == "size" => {
+                    return Ok(Value::from(entries.len() as f64))
+                }
+                ObjectKind::Map(_) => {
+                    return Ok(callable::method(collection::MAP_METHODS, object, key)
+                        .unwrap_or(Value::Nil))
+                }
+                ObjectKind::Set(_) => {
+                    return Ok(callable::method(collection::SET_METHODS, object, key)
+                        .unwrap_or(Value::Nil))
+                }
+                ObjectKind::WeakMap(_) => {
+                    return Ok(callable::method(collection::WEAK_MAP_METHODS, object, key)
+                        .unwrap_or(Value::Nil))
+                }
+                ObjectKind::WeakSet(_) => {
+                    return Ok(callable::method(collection::WEAK_SET_METHODS, object, key)
+                        .unwrap_or(Value::Nil))
+                }
+                ObjectKind::Promise(_) => {
+                    return Ok(callable::method(promise::METHODS, object, key).unwrap_or(Value::Nil))
+                }
+                ObjectKind::Module(namespace) => {
+                    return Ok(namespace.get(key).unwrap_or(Value::Nil))
+                }
+                ObjectKind::RegExp(regex) => {
+                    return Ok(regexp::property(regex, key)
+                        .or_else(|| callable::method(regexp::METHODS, object, key))
+                        .unwrap_or(Value::Nil))
+                }
+                ObjectKind::Tuple(elements) => {
+                    return Ok(if key == "length" {
+                        Value::from(elements.len() as f64)
+                    } else if let Some(index) = array::index(key) {
+                        elements.get(index).cloned().unwrap_or(Value::Nil)
+                    } else {
+                        Value::Nil
+                    })
+                }
+                _ => None,
+            }
+        };
+        self.read_member(token, object, member)
     }
 
-    fn visit_set_expression(&mut self, expr: &Expression) -> Box<dyn Any> {
-        todo!()
+    /// Produces the value of a class member looked up on `this`: methods come
+    /// back bound and getters are called.
+    fn read_member(
+        &mut self,
+        token: &Token,
+        this: &Value,
+        member: Option<Member>,
+    ) -> Result<Value, Unwind> {
+        match member {
+            Some(Member::Method(method)) => Ok(Value::Callable(Rc::new(method.bind(this.clone())))),
+            Some(Member::Accessor {
+                getter: Some(getter),
+                setter: _,
+            }) => self.call(token, Rc::new(getter.bind(this.clone())), Vec::new()),
+            _ => Ok(Value::Nil),
+        }
     }
 
-    fn visit_unary_expression(&mut self, expr: &Expression) -> Box<dyn Any> {
-        match expr {
-            Expression::Unary { operator, right } => {
-                let right = right.accept(self);
+    fn set_property(
+        &mut self,
+        token: &Token,
+        object: &Value,
+        key: &str,
+        value: Value,
+    ) -> Result<(), Unwind> {
+        self.check_access(token, object, key)?;
+        let reference = match object {
+            Value::Object(reference) => reference,
+            _ => {
+                return Err(RuntimeError::new(
+                    token.clone(),
+                    &format!("Cannot set property '{}' of {}.", key, object.type_name()),
+                )
+                .into())
+            }
+        };
 
-                match operator.token_type {
-                    TokenType::Minus => {
-                        if let Err(err) = self.check_number_operand(&operator, &right) {
-                            panic!("{}", err);
+        if reference.borrow().is_frozen() {
+            return Err(RuntimeError::new(
+                token.clone(),
+                &format!(
+                    "Cannot assign to read only property '{}' of object.",
+                    symbol::display_key(key)
+                ),
+            )
+            .into());
+        }
+
+        let member = {
+            let mut reference = reference.borrow_mut();
+            match &mut reference.kind {
+                ObjectKind::Array(elements) => {
+                    if key == "length" {
+                        return match value {
+                            Value::Number(length) if length >= 0.0 && length.fract() == 0.0 => {
+                                elements.resize(length as usize, Value::Nil);
+                                Ok(())
+                            }
+                            _ => Err(RuntimeError::with_kind(
+                                token.clone(),
+                                ErrorKind::RangeError,
+                                "Invalid array length.",
+                            )
+                            .into()),
+                        };
+                    }
+                    if let Some(index) = array::index(key) {
+                        if index >= elements.len() {
+                            elements.resize(index + 1, Value::Nil);
                         }
-                        let right = right.downcast_ref::<f64>().unwrap();
-                        Box::new(-right)
+                        elements[index] = value;
+                        return Ok(());
                     }
-                    TokenType::Bang => Box::new(!self.is_truthy(&right)),
-                    _ => Box::new(()),
+                    None
+                }
+                ObjectKind::Class(class) => class.find(key, true),
+                ObjectKind::Instance(class) => class.find(key, false),
+                ObjectKind::Enum(enumeration) => {
+                    return Err(RuntimeError::new(
+                        token.clone(),
+                        &format!(
+                            "Cannot assign to read only property '{}' of {}.",
+                            key, enumeration.name
+                        ),
+                    )
+                    .into())
+                }
+                ObjectKind::Record | ObjectKind::Tuple(_) | ObjectKind::Module(_) => {
+                    let kind = match reference.kind {
+                        ObjectKind::Record => "record",
+                        ObjectKind::Tuple(_) => "tuple",
+                        _ => "module namespace",
+                    };
+                    return Err(RuntimeError::new(
+                        token.clone(),
+                        &format!("Cannot assign to read only property '{}' of {}.", key, kind),
+                    )
+                    .into());
                 }
+                ObjectKind::Variant(variant) => {
+                    return Err(RuntimeError::new(
+                        token.clone(),
+                        &format!(
+                            "Cannot assign to read only property '{}' of {}.",
+                            key,
+                            variant.qualified_name()
+                        ),
+                    )
+                    .into())
+                }
+                _ => None,
+            }
+        };
+
+        match member {
+            Some(Member::Accessor {
+                getter: _,
+                setter: Some(setter),
+            }) => {
+                self.call(token, Rc::new(setter.bind(object.clone())), vec![value])?;
+                Ok(())
+            }
+            Some(Member::Accessor {
+                getter: _,
+                setter: None,
+            }) => Err(RuntimeError::new(
+                token.clone(),
+                &format!("Cannot set property '{}' which has only a getter.", key),
+            )
+            .into()),
+            _ => {
+                reference.borrow_mut().set(key, value);
+                Ok(())
             }
-            _ => panic!("Expected unary expression"),
         }
     }
 
-    fn visit_variable_expression(&mut self, expr: &Expression) -> Box<dyn Any> {
-        todo!()
+    /// Tests `value` against a pattern from a `match` arm, collecting the
+    /// names it binds into `bindings`.
+    fn match_pattern(
+        &mut self,
+        pattern: &MatchPattern,
+        value: &Value,
+        bindings: &mut Vec<(String, Value)>,
+    ) -> Result<bool, Unwind> {
+        match pattern {
+            MatchPattern::Wildcard { token: _ } => Ok(true),
+            MatchPattern::Literal {
+                token: _,
+                value: literal,
+            } => {
+                let literal = match literal {
+                    None | Some(Literal::Nil) => Value::Nil,
+                    Some(Literal::Bool(literal)) => Value::Bool(*literal),
+                    Some(Literal::Number(literal)) => Value::Number(*literal),
+                    Some(Literal::BigInt(literal)) => bigint_literal(literal),
+                    Some(Literal::String(literal)) => Value::from(literal.as_str()),
+                };
+                Ok(self.is_equal(value, &literal))
+            }
+            MatchPattern::Binding { name } => {
+                bindings.push((name.lexeme.clone(), value.clone()));
+                Ok(true)
+            }
+            MatchPattern::Array {
+                bracket: _,
+                elements,
+                rest,
+            } => {
+                let values = match value {
+                    Value::Object(object) => match &object.borrow().kind {
+                        ObjectKind::Array(values) | ObjectKind::Tuple(values) => values.clone(),
+                        _ => return Ok(false),
+                    },
+                    _ => return Ok(false),
+                };
+                let fits = match rest {
+                    Some(_) => values.len() >= elements.len(),
+                    None => values.len() == elements.len(),
+                };
+                if !fits {
+                    return Ok(false);
+                }
+                for (element, value) in elements.iter().zip(&values) {
+                    if !self.match_pattern(element, value, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                match rest {
+                    Some(rest) => {
+                        let rest_values = Value::array(values[elements.len()..].to_vec());
+                        self.match_pattern(rest, &rest_values, bindings)
+                    }
+                    None => Ok(true),
+                }
+            }
+            MatchPattern::Object {
+                brace: _,
+                properties,
+            } => {
+                if !matches!(value, Value::Object(_)) {
+                    return Ok(false);
+                }
+                for property in properties {
+                    // A property the pattern names has to be there at all,
+                    // though an own property may hold nil.
+                    let key = symbol::string_key(&property.name);
+                    let found = self.get_property(&property.key, value, &key)?;
+                    let present = match value {
+                        Value::Object(object) => object.borrow().get(&key).is_some(),
+                        _ => false,
+                    };
+                    if (!present && matches!(found, Value::Nil))
+                        || !self.match_pattern(&property.pattern, &found, bindings)?
+                    {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            MatchPattern::Variant {
+                enumeration,
+                name,
+                fields,
+            } => {
+                let variant = match self.evaluate(enumeration)? {
+                    Value::Object(object) => match &object.borrow().kind {
+                        ObjectKind::Enum(enumeration) => {
+                            enumeration.variant(&name.lexeme).ok_or_else(|| {
+                                RuntimeError::new(
+                                    name.clone(),
+                                    &format!(
+                                        "Enum '{}' has no variant '{}'.",
+                                        enumeration.name, name.lexeme
+                                    ),
+                                )
+                            })?
+                        }
+                        _ => return Err(RuntimeError::new(name.clone(), "Expect an enum.").into()),
+                    },
+                    _ => return Err(RuntimeError::new(name.clone(), "Expect an enum.").into()),
+                };
+                let values = match value {
+                    Value::Object(object) => {
+                        let object = object.borrow();
+                        match &object.kind {
+                            ObjectKind::Variant(candidate) if Rc::ptr_eq(candidate, &variant) => {
+                                object
+                                    .keys()
+                                    .iter()
+                                    .map(|key| object.get(key).unwrap_or(Value::Nil))
+                                    .collect::<Vec<Value>>()
+                            }
+                            _ => return Ok(false),
+                        }
+                    }
+                    _ => return Ok(false),
+                };
+                let Some(fields) = fields else {
+                    return Ok(true);
+                };
+                if fields.len() != values.len() {
+                    return Err(RuntimeError::new(
+                        name.clone(),
+                        &format!(
+                            "Variant '{}' carries {} values but the pattern has {}.",
+                            variant.qualified_name(),
+                            values.len(),
+                            fields.len()
+                        ),
+                    )
+                    .into());
+                }
+                for (field, value) in fields.iter().zip(&values) {
+                    if !self.match_pattern(field, value, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            MatchPattern::Or { patterns } => {
+                for pattern in patterns {
+                    let bound = bindings.len();
+                    if self.match_pattern(pattern, value, bindings)? {
+                        return Ok(true);
+                    }
+                    bindings.truncate(bound);
+                }
+                Ok(false)
+            }
+        }
+    }
+
+    fn bind_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value: Value,
+        binding: Binding,
+    ) -> Result<(), Unwind> {
+        match pattern {
+            Pattern::Identifier { name } => match binding {
+                Binding::Let => self.environment.borrow_mut().define(&name.lexeme, value),
+                Binding::Const => self
+                    .environment
+                    .borrow_mut()
+                    .define_const(&name.lexeme, value),
+                Binding::Assign => self.environment.borrow_mut().assign(name, value)?,
+            },
+            Pattern::Member { expression } => match expression.as_ref() {
+                Expression::Get {
+                    object,
+                    name,
+                    optional: _,
+                } => {
+                    let object = self.evaluate(object)?;
+                    match name.token_type {
+                        TokenType::PrivateName => self.set_private(name, &object, value)?,
+                        _ => self.set_property(name, &object, &name.lexeme, value)?,
+                    }
+                }
+                Expression::Index {
+                    object,
+                    bracket,
+                    index,
+                    optional: _,
+                } => {
+                    let object = self.evaluate(object)?;
+                    let index = self.evaluate(index)?;
+                    let key = self.property_key(&index);
+                    self.set_property(bracket, &object, &key, value)?;
+                }
+                _ => panic!("Expected member pattern"),
+            },
+            Pattern::Array {
+                bracket,
+                elements,
+                rest,
+            } => {
+                // Only as many values as the pattern needs are taken, unless
+                // it ends with a rest element. An iterator that is left with
+                // values to spare is closed, as a loop left early closes it.
+                let mut iteration = Iteration::new(self, bracket, &value)?;
+                let mut done = false;
+                for element in elements {
+                    let value = match done {
+                        true => None,
+                        false => iteration.next(self, bracket)?,
+                    };
+                    done = value.is_none();
+                    let Some(element) = element else {
+                        continue;
+                    };
+                    let value = value.unwrap_or(Value::Nil);
+                    if let Err(unwind) =
+                        self.bind_element(&element.target, &element.default, value, binding)
+                    {
+                        if !done {
+                            let _ = iteration.close(self, bracket);
+                        }
+                        return Err(unwind);
+                    }
+                }
+                if let Some(rest) = rest {
+                    let mut values = Vec::new();
+                    if !done {
+                        while let Some(value) = iteration.next(self, bracket)? {
+                            values.push(value);
+                        }
+                    }
+                    self.bind_pattern(rest, Value::array(values), binding)?;
+                } else if !done {
+                    iteration.close(self, bracket)?;
+                }
+            }
+            Pattern::Object {
+                brace,
+                properties,
+                rest,
+            } => {
+                if let Value::Nil = value {
+                    return Err(RuntimeError::new(brace.clone(), "Cannot destructure nil.").into());
+                }
+
+                let mut taken = Vec::new();
+                for property in properties {
+                    let (key, token) = match &property.key {
+                        PropertyKey::Named { name, token } => (symbol::string_key(name), token),
+                        PropertyKey::Computed(key) => {
+                            let key = self.evaluate(key)?;
+                            (self.property_key(&key), brace)
+                        }
+                    };
+                    let element = self.get_property(token, &value, &key)?;
+                    taken.push(key);
+                    self.bind_element(&property.target, &property.default, element, binding)?;
+                }
+
+                if let Some(rest) = rest {
+                    let mut remaining = Object::new(ObjectKind::Ordinary);
+                    if let Value::Object(object) = &value {
+                        let object = object.borrow();
+                        for key in object.keys() {
+                            if !taken.contains(key) {
+                                remaining.set(key, object.get(key).unwrap_or(Value::Nil));
+                            }
+                        }
+                    }
+                    let remaining = Value::Object(Rc::new(RefCell::new(remaining)));
+                    self.bind_pattern(rest, remaining, binding)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Binds one element of a pattern, falling back on its default when the
+    /// value is nil.
+    fn bind_element(
+        &mut self,
+        target: &Pattern,
+        default: &Option<Expression>,
+        value: Value,
+        binding: Binding,
+    ) -> Result<(), Unwind> {
+        let value = match (value, default) {
+            (Value::Nil, Some(default)) => self.evaluate(default)?,
+            (value, _) => value,
+        };
+        self.bind_pattern(target, value, binding)
+    }
+
+    fn loop_owns(&self, label: &Option<Token>, target: &Option<String>) -> bool {
+        match target {
+            None => true,
+            Some(target) => matches!(label, Some(label) if &label.lexeme == target),
+        }
+    }
+}
+
+fn construct_error(
+    interpreter: &mut Interpreter,
+    arguments: Vec<Value>,
+    kind: ErrorKind,
+) -> Result<Value, Unwind> {
+    let message = match arguments.first() {
+        Some(Value::Nil) | None => String::new(),
+        Some(message) => message.to_string(),
+    };
+
+    // Leave the constructor's own frame out of the trace.
+    let depth = interpreter.frames.len().saturating_sub(1);
+    let location = interpreter
+        .frames
+        .last()
+        .map_or_else(|| String::from("line 0"), |frame| frame.paren.location());
+    let stack = interpreter.stack_trace(depth, location);
+    Ok(interpreter.error_object(kind, &message, &stack))
+}
+
+fn native_error(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    construct_error(interpreter, arguments, ErrorKind::Error)
+}
+
+fn native_type_error(
+    interpreter: &mut Interpreter,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    construct_error(interpreter, arguments, ErrorKind::TypeError)
+}
+
+fn native_reference_error(
+    interpreter: &mut Interpreter,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    construct_error(interpreter, arguments, ErrorKind::ReferenceError)
+}
+
+fn native_range_error(
+    interpreter: &mut Interpreter,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    construct_error(interpreter, arguments, ErrorKind::RangeError)
+}
+
+fn native_syntax_error(
+    interpreter: &mut Interpreter,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    construct_error(interpreter, arguments, ErrorKind::SyntaxError)
+}
+
+/// `BigInt(value)`, which converts an integral number, a string of decimal
+/// digits or a boolean.
+fn native_bigint(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    let value = arguments.into_iter().next().unwrap_or(Value::Nil);
+    let converted = match &value {
+        Value::BigInt(_) => return Ok(value),
+        Value::Bool(value) => Some(BigInt::from_u64(*value as u64)),
+        Value::Number(number) => match BigInt::from_f64(*number) {
+            Some(converted) => Some(converted),
+            None => {
+                return Err(RuntimeError::with_kind(
+                    interpreter.call_site(),
+                    ErrorKind::RangeError,
+                    &format!(
+                        "Cannot convert {} to a BigInt because it is not an integer.",
+                        value
+                    ),
+                )
+                .into())
+            }
+        },
+        Value::String(text) => BigInt::parse(text),
+        _ => None,
+    };
+    match converted {
+        Some(converted) => Ok(Value::BigInt(Rc::new(converted))),
+        None => Err(RuntimeError::new(
+            interpreter.call_site(),
+            &format!("Cannot convert {:?} to a BigInt.", value),
+        )
+        .into()),
+    }
+}
+
+fn bigint_literal(digits: &str) -> Value {
+    Value::BigInt(Rc::new(BigInt::parse(digits).unwrap()))
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExpressionVisitor<Result<Value, Unwind>> for Interpreter {
+    fn visit_array_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Array {
+                bracket: _,
+                elements,
+            } => {
+                let mut values = Vec::new();
+                for element in elements {
+                    match element {
+                        None => values.push(Value::Nil),
+                        Some(Expression::Spread {
+                            ellipsis,
+                            expression,
+                        }) => {
+                            let iterable = self.evaluate(expression)?;
+                            values.extend(self.iterate(ellipsis, &iterable)?);
+                        }
+                        Some(element) => values.push(self.evaluate(element)?),
+                    }
+                }
+                Ok(Value::array(values))
+            }
+            _ => panic!("Expected array expression"),
+        }
+    }
+
+    fn visit_assign_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Assign { name, value } => {
+                let value = self.evaluate(value)?;
+                self.environment.borrow_mut().assign(name, value.clone())?;
+                Ok(value)
+            }
+            _ => panic!("Expected assign expression"),
+        }
+    }
+
+    fn visit_assign_pattern_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::AssignPattern {
+                target,
+                equals: _,
+                value,
+            } => {
+                let value = self.evaluate(value)?;
+                self.bind_pattern(target, value.clone(), Binding::Assign)?;
+                Ok(value)
+            }
+            _ => panic!("Expected assign pattern expression"),
+        }
+    }
+
+    fn visit_await_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Await { keyword, value } => {
+                // Async function bodies run like generators, which hand each
+                // awaited value to the promise that resumes them.
+                let value = self.evaluate(value)?;
+                generator::suspend(self, keyword, value)
+            }
+            _ => panic!("Expected await expression"),
+        }
+    }
+
+    fn visit_binary_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+
+                if let Some(value) = self.overloaded_operator(operator, &left, &right)? {
+                    return Ok(value);
+                }
+                self.check_not_overloaded(operator, &left, &right)?;
+                if !matches!(
+                    operator.token_type,
+                    TokenType::EqualEqual
+                        | TokenType::BangEqual
+                        | TokenType::EqualEqualEqual
+                        | TokenType::BangEqualEqual
+                ) {
+                    if let Some(value) = self.bigint_arithmetic(operator, &left, &right)? {
+                        return Ok(value);
+                    }
+                }
+
+                match operator.token_type {
+                    TokenType::Minus => {
+                        let (left, right) = self.check_number_operands(operator, &left, &right)?;
+                        Ok(Value::Number(left - right))
+                    }
+                    TokenType::Plus => match (&left, &right) {
+                        (Value::Number(left), Value::Number(right)) => {
+                            Ok(Value::Number(left + right))
+                        }
+                        (Value::String(_), _) | (_, Value::String(_)) => {
+                            Ok(Value::from(format!("{}{}", left, right)))
+                        }
+                        _ => Err(RuntimeError::new(
+                            operator.clone(),
+                            "Operands must be two numbers or two strings.",
+                        )
+                        .into()),
+                    },
+                    TokenType::Slash => {
+                        let (left, right) = self.check_number_operands(operator, &left, &right)?;
+                        Ok(Value::Number(left / right))
+                    }
+                    TokenType::Star => {
+                        let (left, right) = self.check_number_operands(operator, &left, &right)?;
+                        Ok(Value::Number(left * right))
+                    }
+                    TokenType::Percent => {
+                        let (left, right) = self.check_number_operands(operator, &left, &right)?;
+                        Ok(Value::Number(left % right))
+                    }
+                    TokenType::StarStar => {
+                        let (left, right) = self.check_number_operands(operator, &left, &right)?;
+                        Ok(Value::Number(left.powf(right)))
+                    }
+                    TokenType::Greater
+                    | TokenType::GreaterEqual
+                    | TokenType::Less
+                    | TokenType::LessEqual => {
+                        Ok(Value::Bool(self.compare(operator, &left, &right)?))
+                    }
+                    TokenType::BangEqual | TokenType::BangEqualEqual => {
+                        Ok(Value::Bool(!self.is_equal(&left, &right)))
+                    }
+                    TokenType::EqualEqual | TokenType::EqualEqualEqual => {
+                        Ok(Value::Bool(self.is_equal(&left, &right)))
+                    }
+                    _ => Ok(Value::Nil),
+                }
+            }
+            _ => panic!("Expected binary expression"),
+        }
+    }
+
+    fn visit_call_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Call {
+                callee,
+                paren,
+                arguments,
+                optional,
+            } => {
+                let (callee, receiver) = self.evaluate_callee(callee)?;
+                if *optional && matches!(callee, Value::Nil) {
+                    return Err(Unwind::ShortCircuit);
+                }
+                let values = self.evaluate_arguments(arguments)?;
+
+                let function = match callee {
+                    Value::Callable(function) => receiver
+                        .and_then(|receiver| function.with_receiver(&receiver))
+                        .unwrap_or(function),
+                    _ => {
+                        if let Some(class) = class_of(&callee) {
+                            return Err(RuntimeError::new(
+                                paren.clone(),
+                                &format!(
+                                    "Class constructor {} cannot be invoked without 'new'.",
+                                    class.name
+                                ),
+                            )
+                            .into());
+                        }
+                        return Err(RuntimeError::new(
+                            paren.clone(),
+                            "Can only call functions and classes.",
+                        )
+                        .into());
+                    }
+                };
+
+                self.call(paren, function, values)
+            }
+            _ => panic!("Expected call expression"),
+        }
+    }
+
+    fn visit_function_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        Ok(self.function(expr, false))
+    }
+
+    fn visit_get_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Get {
+                object,
+                name,
+                optional,
+            } => {
+                let object = self.evaluate(object)?;
+                if *optional && matches!(object, Value::Nil) {
+                    return Err(Unwind::ShortCircuit);
+                }
+                match name.token_type {
+                    TokenType::PrivateName => self.get_private(name, &object),
+                    _ => self.get_property(name, &object, &name.lexeme),
+                }
+            }
+            _ => panic!("Expected get expression"),
+        }
+    }
+
+    fn visit_grouping_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Grouping { expression } => self.evaluate(expression),
+            _ => panic!("Expected grouping expression"),
+        }
+    }
+
+    fn visit_index_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Index {
+                object,
+                bracket,
+                index,
+                optional,
+            } => {
+                let object = self.evaluate(object)?;
+                if *optional && matches!(object, Value::Nil) {
+                    return Err(Unwind::ShortCircuit);
+                }
+                let index = self.evaluate(index)?;
+                let key = self.property_key(&index);
+                self.get_property(bracket, &object, &key)
+            }
+            _ => panic!("Expected index expression"),
+        }
+    }
+
+    fn visit_literal_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Literal { value } => Ok(match value {
+                None => Value::Nil,
+                Some(Literal::Nil) => Value::Nil,
+                Some(Literal::Bool(value)) => Value::Bool(*value),
+                Some(Literal::Number(value)) => Value::Number(*value),
+                Some(Literal::BigInt(value)) => bigint_literal(value),
+                Some(Literal::String(value)) => Value::from(value.as_str()),
+            }),
+            _ => panic!("Expected literal expression"),
+        }
+    }
+
+    fn visit_logical_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Logical {
+                left,
+                operator,
+                right,
+            } => {
+                let left = self.evaluate(left)?;
+
+                if operator.token_type == TokenType::Or {
+                    if self.is_truthy(&left) {
+                        return Ok(left);
+                    }
+                } else if !self.is_truthy(&left) {
+                    return Ok(left);
+                }
+
+                self.evaluate(right)
+            }
+            _ => panic!("Expected logical expression"),
+        }
+    }
+
+    fn visit_match_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Match {
+                keyword,
+                subject,
+                arms,
+            } => {
+                let value = self.evaluate(subject)?;
+                for arm in arms {
+                    let mut bindings = Vec::new();
+                    if !self.match_pattern(&arm.pattern, &value, &mut bindings)? {
+                        continue;
+                    }
+
+                    // Every arm has its own scope, even one that binds nothing,
+                    // to line up with the resolver.
+                    let mut environment = Environment::with_enclosing(self.environment.clone());
+                    for (name, value) in bindings {
+                        environment.define(&name, value);
+                    }
+                    let environment = Rc::new(RefCell::new(environment));
+                    if let Some(guard) = &arm.guard {
+                        let passed = self.evaluate_in(guard, environment.clone())?;
+                        if !self.is_truthy(&passed) {
+                            continue;
+                        }
+                    }
+                    return self.evaluate_in(&arm.body, environment);
+                }
+                Err(RuntimeError::new(
+                    keyword.clone(),
+                    &format!("No match arm matches {:?}.", value),
+                )
+                .into())
+            }
+            _ => panic!("Expected match expression"),
+        }
+    }
+
+    fn visit_new_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::New {
+                keyword,
+                callee,
+                arguments,
+            } => {
+                let callee = self.evaluate(callee)?;
+                let arguments = self.evaluate_arguments(arguments)?;
+
+                if let Value::Object(object) = &callee {
+                    let constructor = match &object.borrow().kind {
+                        ObjectKind::Constructor(constructor) => Some(constructor.clone()),
+                        _ => None,
+                    };
+                    if let Some(constructor) = constructor {
+                        return self.call(keyword, constructor, arguments);
+                    }
+                }
+
+                let class = match class_of(&callee) {
+                    Some(class) => class,
+                    None => {
+                        return Err(RuntimeError::new(
+                            keyword.clone(),
+                            &format!("{} is not a constructor.", callee),
+                        )
+                        .into())
+                    }
+                };
+
+                let instance = Value::Object(Rc::new(RefCell::new(Object::new(
+                    ObjectKind::Instance(class.clone()),
+                ))));
+                self.initialize_instance(&class, &instance)?;
+                if let Some(Member::Method(constructor)) = class.find("constructor", false) {
+                    let constructor = Rc::new(constructor.bind(instance.clone()));
+                    // A constructor may hand back a different object in place
+                    // of the one being built.
+                    if let result @ Value::Object(_) = self.call(keyword, constructor, arguments)? {
+                        return Ok(result);
+                    }
+                }
+                Ok(instance)
+            }
+            _ => panic!("Expected new expression"),
+        }
+    }
+
+    fn visit_object_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Object {
+                brace: _,
+                properties,
+            } => {
+                let mut object = Object::new(ObjectKind::Ordinary);
+                for property in properties {
+                    let key = match &property.key {
+                        PropertyKey::Named { name, token: _ } => symbol::string_key(name),
+                        PropertyKey::Computed(key) => {
+                            let key = self.evaluate(key)?;
+                            self.property_key(&key)
+                        }
+                    };
+                    let value = match &property.value {
+                        Expression::Function { arrow: false, .. } => {
+                            self.function(&property.value, true)
+                        }
+                        value => self.evaluate(value)?,
+                    };
+                    object.set(&key, value);
+                }
+                Ok(Value::Object(Rc::new(RefCell::new(object))))
+            }
+            _ => panic!("Expected object expression"),
+        }
+    }
+
+    fn visit_optional_chain_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::OptionalChain { expression } => match self.evaluate(expression) {
+                Err(Unwind::ShortCircuit) => Ok(Value::Nil),
+                result => result,
+            },
+            _ => panic!("Expected optional chain expression"),
+        }
+    }
+
+    fn visit_record_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Record { hash, object } => match self.evaluate(object)? {
+                Value::Object(object) => record::record(hash, &object.borrow()),
+                _ => panic!("Expected an object literal to make an object"),
+            },
+            _ => panic!("Expected record expression"),
+        }
+    }
+
+    fn visit_regex_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Regex { token: _, regex } => Ok(regexp::create(regex.clone())),
+            _ => panic!("Expected regex expression"),
+        }
+    }
+
+    fn visit_set_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Set {
+                object,
+                name,
+                value,
+            } => {
+                let object = self.evaluate(object)?;
+                let value = self.evaluate(value)?;
+                match name.token_type {
+                    TokenType::PrivateName => self.set_private(name, &object, value.clone())?,
+                    _ => self.set_property(name, &object, &name.lexeme, value.clone())?,
+                }
+                Ok(value)
+            }
+            _ => panic!("Expected set expression"),
+        }
+    }
+
+    fn visit_set_index_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::SetIndex {
+                object,
+                bracket,
+                index,
+                value,
+            } => {
+                let object = self.evaluate(object)?;
+                let index = self.evaluate(index)?;
+                let key = self.property_key(&index);
+                let value = self.evaluate(value)?;
+                self.set_property(bracket, &object, &key, value.clone())?;
+                Ok(value)
+            }
+            _ => panic!("Expected set index expression"),
+        }
+    }
+
+    fn visit_spread_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Spread {
+                ellipsis,
+                expression: _,
+            } => Err(RuntimeError::new(ellipsis.clone(), "Unexpected spread.").into()),
+            _ => panic!("Expected spread expression"),
+        }
+    }
+
+    fn visit_super_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Super { keyword, method } => {
+                let superclass = self.environment.borrow().get(keyword)?;
+                let superclass = class_of(&superclass).expect("'super' is bound to a class");
+                let this = self.environment.borrow().get(&Token {
+                    lexeme: String::from("this"),
+                    ..keyword.clone()
+                })?;
+
+                match method {
+                    // `super(...)` runs the inherited constructor on `this`.
+                    None => match superclass.find("constructor", false) {
+                        Some(Member::Method(constructor)) => {
+                            Ok(Value::Callable(Rc::new(constructor.bind(this))))
+                        }
+                        _ => Ok(Value::Callable(Rc::new(NativeFunction::with_arity(
+                            "constructor",
+                            0,
+                            None,
+                            |_, _| Ok(Value::Nil),
+                        )))),
+                    },
+                    Some(method) => {
+                        let is_static = class_of(&this).is_some();
+                        let member = superclass.find(&method.lexeme, is_static);
+                        self.read_member(method, &this, member)
+                    }
+                }
+            }
+            _ => panic!("Expected super expression"),
+        }
+    }
+
+    fn visit_this_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::This { keyword } => Ok(self.environment.borrow().get(keyword)?),
+            _ => panic!("Expected this expression"),
+        }
+    }
+
+    fn visit_tuple_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Tuple { hash, array } => {
+                let elements = match self.evaluate(array)? {
+                    Value::Object(array) => match &array.borrow().kind {
+                        ObjectKind::Array(elements) => elements.clone(),
+                        _ => panic!("Expected an array literal to make an array"),
+                    },
+                    _ => panic!("Expected an array literal to make an array"),
+                };
+                record::tuple(hash, elements)
+            }
+            _ => panic!("Expected tuple expression"),
+        }
+    }
+
+    fn visit_unary_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Unary { operator, right } => {
+                let right = self.evaluate(right)?;
+
+                match operator.token_type {
+                    TokenType::Minus => {
+                        if let Value::BigInt(right) = right {
+                            return Ok(Value::BigInt(Rc::new(right.negate())));
+                        }
+                        let right = self.check_number_operand(operator, &right)?;
+                        Ok(Value::Number(-right))
+                    }
+                    TokenType::Bang => Ok(Value::Bool(!self.is_truthy(&right))),
+                    _ => Ok(Value::Nil),
+                }
+            }
+            _ => panic!("Expected unary expression"),
+        }
+    }
+
+    fn visit_variable_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Variable { name } => Ok(self.environment.borrow().get(name)?),
+            _ => panic!("Expected variable expression"),
+        }
+    }
+
+    fn visit_yield_expression(&mut self, expr: &Expression) -> Result<Value, Unwind> {
+        match expr {
+            Expression::Yield {
+                keyword,
+                value,
+                delegate,
+            } => {
+                let value = match value {
+                    Some(value) => self.evaluate(value)?,
+                    None => Value::Nil,
+                };
+                match delegate {
+                    true => generator::delegate(self, keyword, &value),
+                    false => generator::suspend(self, keyword, value),
+                }
+            }
+            _ => panic!("Expected yield expression"),
+        }
+    }
+}
+
+impl StatementVisitor<Result<(), Unwind>> for Interpreter {
+    fn visit_block_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Block { statements } => {
+                let environment = Rc::new(RefCell::new(Environment::with_enclosing(
+                    self.environment.clone(),
+                )));
+                self.execute_block(statements, environment)
+            }
+            _ => panic!("Expected block statement"),
+        }
+    }
+
+    fn visit_break_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Break { keyword: _, label } => Err(Unwind::Break(
+                label.as_ref().map(|label| label.lexeme.clone()),
+            )),
+            _ => panic!("Expected break statement"),
+        }
+    }
+
+    fn visit_class_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Class {
+                name,
+                superclass,
+                methods,
+                fields,
+            } => {
+                let superclass = match superclass {
+                    Some(superclass) => {
+                        let value = self.evaluate(superclass)?;
+                        match (class_of(&value), value) {
+                            (Some(_), Value::Object(object)) => Some(object),
+                            _ => {
+                                return Err(RuntimeError::new(
+                                    name.clone(),
+                                    "Superclass must be a class.",
+                                )
+                                .into())
+                            }
+                        }
+                    }
+                    None => None,
+                };
+
+                self.environment
+                    .borrow_mut()
+                    .define(&name.lexeme, Value::Nil);
+
+                // Methods close over a scope that binds `super` and gives
+                // each private name a key unique to this class.
+                let mut environment = Environment::with_enclosing(self.environment.clone());
+                if let Some(superclass) = &superclass {
+                    environment.define("super", Value::Object(superclass.clone()));
+                }
+                let private_names = methods
+                    .iter()
+                    .map(|method| &method.name)
+                    .chain(fields.iter().map(|field| &field.name))
+                    .filter(|name| name.token_type == TokenType::PrivateName);
+                for private_name in private_names {
+                    self.private_names.set(self.private_names.get() + 1);
+                    let key = format!("{}@{}", private_name.lexeme, self.private_names.get());
+                    environment.define(&private_name.lexeme, Value::from(key));
+                }
+                let environment = Rc::new(RefCell::new(environment));
+
+                let mut class = Class::new(name.lexeme.clone(), superclass, environment.clone());
+                for method in methods {
+                    let mut name = method.name.clone();
+                    if let Some(computed) = &method.computed {
+                        let key = self.evaluate_in(computed, environment.clone())?;
+                        name.lexeme = self.property_key(&key);
+                    }
+                    let function = Function::new(
+                        Some(name.clone()),
+                        method.params.clone(),
+                        method.body.clone(),
+                        environment.clone(),
+                        method.is_generator,
+                        method.is_async,
+                    );
+                    match name.token_type {
+                        TokenType::PrivateName => {
+                            let key = environment.borrow().get(&name)?.to_string();
+                            class.define_private(&key, method.kind, method.is_static, function);
+                        }
+                        _ => {
+                            class.declare_access(&name.lexeme, method.is_static, method.access);
+                            class.define(&name.lexeme, method.kind, method.is_static, function);
+                        }
+                    }
+                }
+
+                let mut static_fields = Vec::new();
+                for field in fields {
+                    let key = match field.name.token_type {
+                        TokenType::PrivateName => {
+                            FieldKey::Private(environment.borrow().get(&field.name)?.to_string())
+                        }
+                        _ => {
+                            class.declare_access(&field.name.lexeme, field.is_static, field.access);
+                            FieldKey::Public(field.name.lexeme.clone())
+                        }
+                    };
+                    let field_init = FieldInit {
+                        key,
+                        initializer: field.initializer.clone(),
+                    };
+                    match field.is_static {
+                        true => static_fields.push(field_init),
+                        false => class.fields.push(field_init),
+                    }
+                }
+
+                let class = Rc::new(class);
+                let value = Value::Object(Rc::new(RefCell::new(Object::new(ObjectKind::Class(
+                    class.clone(),
+                )))));
+                self.environment.borrow_mut().assign(name, value.clone())?;
+
+                self.install_private_members(&class, true, &value);
+                self.initialize_fields(&static_fields, environment, &value)?;
+                Ok(())
+            }
+            _ => panic!("Expected class statement"),
+        }
+    }
+
+    fn visit_continue_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Continue { keyword: _, label } => Err(Unwind::Continue(
+                label.as_ref().map(|label| label.lexeme.clone()),
+            )),
+            _ => panic!("Expected continue statement"),
+        }
+    }
+
+    fn visit_do_while_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::DoWhile {
+                body,
+                condition,
+                label,
+            } => {
+                loop {
+                    match self.execute(body) {
+                        Ok(()) => {}
+                        Err(Unwind::Break(target)) if self.loop_owns(label, &target) => break,
+                        Err(Unwind::Continue(target)) if self.loop_owns(label, &target) => {}
+                        Err(unwind) => return Err(unwind),
+                    }
+
+                    let value = self.evaluate(condition)?;
+                    if !self.is_truthy(&value) {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            _ => panic!("Expected do-while statement"),
+        }
+    }
+
+    fn visit_enum_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Enum { name, variants } => {
+                let value = enumeration::create(&name.lexeme, variants);
+                self.environment.borrow_mut().define(&name.lexeme, value);
+                Ok(())
+            }
+            _ => panic!("Expected enum statement"),
+        }
+    }
+
+    fn visit_export_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Export {
+                keyword: _,
+                declaration,
+                default,
+            } => {
+                self.execute(declaration)?;
+                for name in declaration.declared_names() {
+                    let exported = match default {
+                        true => String::from("default"),
+                        false => name.lexeme.clone(),
+                    };
+                    self.exports.push((exported, name.clone()));
+                }
+                Ok(())
+            }
+            _ => panic!("Expected export statement"),
+        }
+    }
+
+    fn visit_export_default_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::ExportDefault { keyword, value } => {
+                let value = self.evaluate(value)?;
+                // Stored under a name that no identifier can spell.
+                let mut local = keyword.clone();
+                local.lexeme = String::from("*default*");
+                self.environment
+                    .borrow_mut()
+                    .define_const(&local.lexeme, value);
+                self.exports.push((String::from("default"), local));
+                Ok(())
+            }
+            _ => panic!("Expected export default statement"),
+        }
+    }
+
+    fn visit_export_names_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::ExportNames {
+                keyword: _,
+                specifiers,
+            } => {
+                for specifier in specifiers {
+                    self.exports
+                        .push((specifier.alias.lexeme.clone(), specifier.name.clone()));
+                }
+                Ok(())
+            }
+            _ => panic!("Expected export statement"),
+        }
+    }
+
+    fn visit_expression_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Expression { expression } => {
+                self.evaluate(expression)?;
+                Ok(())
+            }
+            _ => panic!("Expected expression statement"),
+        }
+    }
+
+    fn visit_for_each_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::ForEach {
+                keyword,
+                kind,
+                binding,
+                target,
+                iterable,
+                body,
+                label,
+            } => {
+                let iterable = self.evaluate(iterable)?;
+                let mut iteration = match kind {
+                    ForKind::Of => Iteration::new(self, keyword, &iterable)?,
+                    ForKind::In => Iteration::Values(self.enumerable_keys(&iterable).into_iter()),
+                };
+
+                while let Some(value) = iteration.next(self, keyword)? {
+                    // Every iteration binds its target in a scope of its own,
+                    // so closures created in the body keep the value they saw.
+                    let environment = Rc::new(RefCell::new(Environment::with_enclosing(
+                        self.environment.clone(),
+                    )));
+                    let previous = std::mem::replace(&mut self.environment, environment);
+                    let mut result = self.bind_pattern(target, value, *binding);
+                    if result.is_ok() {
+                        result = self.execute(body);
+                    }
+                    self.environment = previous;
+
+                    // Leaving the loop early tells the iterator so. When an
+                    // error is leaving it, that error wins over any that
+                    // closing the iterator raises.
+                    match result {
+                        Ok(()) => {}
+                        Err(Unwind::Break(target)) if self.loop_owns(label, &target) => {
+                            iteration.close(self, keyword)?;
+                            break;
+                        }
+                        Err(Unwind::Continue(target)) if self.loop_owns(label, &target) => {}
+                        Err(unwind @ (Unwind::Error(_) | Unwind::Throw { .. })) => {
+                            let _ = iteration.close(self, keyword);
+                            return Err(unwind);
+                        }
+                        Err(unwind) => {
+                            iteration.close(self, keyword)?;
+                            return Err(unwind);
+                        }
+                    }
+                }
+                Ok(())
+            }
+            _ => panic!("Expected for-each statement"),
+        }
+    }
+
+    fn visit_function_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Function {
+                name,
+                params,
+                return_type: _,
+                body,
+                is_generator,
+                is_async,
+            } => {
+                let function = Function::new(
+                    Some(name.clone()),
+                    params.clone(),
+                    body.clone(),
+                    self.environment.clone(),
+                    *is_generator,
+                    *is_async,
+                );
+                self.environment
+                    .borrow_mut()
+                    .define(&name.lexeme, Value::Callable(Rc::new(function)));
+                Ok(())
+            }
+            _ => panic!("Expected function statement"),
+        }
+    }
+
+    fn visit_if_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let condition = self.evaluate(condition)?;
+                if self.is_truthy(&condition) {
+                    self.execute(then_branch)
+                } else if let Some(else_branch) = else_branch {
+                    self.execute(else_branch)
+                } else {
+                    Ok(())
+                }
+            }
+            _ => panic!("Expected if statement"),
+        }
+    }
+
+    fn visit_import_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Import {
+                keyword: _,
+                source,
+                path,
+                default,
+                namespace,
+                specifiers,
+            } => {
+                let module = module_path(&self.directory, path)
+                    .ok()
+                    .and_then(|resolved| self.modules.get(&resolved).cloned());
+                let module = match module {
+                    Some(module) => module,
+                    None => {
+                        return Err(RuntimeError::with_kind(
+                            source.clone(),
+                            ErrorKind::Error,
+                            &format!("Cannot find module '{}'.", path),
+                        )
+                        .into())
+                    }
+                };
+
+                let exports = match &module {
+                    Value::Object(object) => match &object.borrow().kind {
+                        ObjectKind::Module(namespace) => namespace.clone(),
+                        _ => panic!("Expected module namespace object"),
+                    },
+                    _ => panic!("Expected module namespace object"),
+                };
+                let export = |name: &Token, exported: &str| {
+                    exports.local(exported).cloned().ok_or_else(|| {
+                        RuntimeError::new(
+                            name.clone(),
+                            &format!(
+                                "Module '{}' does not provide an export named '{}'.",
+                                path, exported
+                            ),
+                        )
+                    })
+                };
+
+                // Imported names follow the variables they were exported
+                // from, so they see the module's later changes.
+                let mut bindings = Vec::new();
+                if let Some(default) = default {
+                    bindings.push((default, export(default, "default")?));
+                }
+                for specifier in specifiers {
+                    let local = export(&specifier.name, &specifier.name.lexeme)?;
+                    bindings.push((&specifier.alias, local));
+                }
+
+                let mut environment = self.environment.borrow_mut();
+                for (name, local) in bindings {
+                    environment.define_import(&name.lexeme, exports.environment.clone(), local);
+                }
+                if let Some(namespace) = namespace {
+                    environment.define_const(&namespace.lexeme, module.clone());
+                }
+                Ok(())
+            }
+            _ => panic!("Expected import statement"),
+        }
+    }
+
+    fn visit_interface_statement(&mut self, _statement: &Statement) -> Result<(), Unwind> {
+        Ok(())
+    }
+
+    fn visit_labeled_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Labeled { label, body } => match self.execute(body) {
+                Err(Unwind::Break(Some(target))) if target == label.lexeme => Ok(()),
+                result => result,
+            },
+            _ => panic!("Expected labeled statement"),
+        }
+    }
+
+    fn visit_print_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Print { expression } => {
+                let value = self.evaluate(expression)?;
+                println!("{}", console::inspect(&value));
+                Ok(())
+            }
+            _ => panic!("Expected print statement"),
+        }
+    }
+
+    fn visit_return_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Return { keyword: _, value } => {
+                let value = match value {
+                    Some(value) => self.evaluate(value)?,
+                    None => Value::Nil,
+                };
+                Err(Unwind::Return(value))
+            }
+            _ => panic!("Expected return statement"),
+        }
+    }
+
+    fn visit_let_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Let {
+                target,
+                annotation: _,
+                initializer,
+            } => {
+                let value = match initializer {
+                    Some(initializer) => self.evaluate(initializer)?,
+                    None => Value::Nil,
+                };
+                self.bind_pattern(target, value, Binding::Let)
+            }
+            _ => panic!("Expected let statement"),
+        }
+    }
+
+    fn visit_const_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Const {
+                target,
+                annotation: _,
+                initializer,
+            } => {
+                let value = match initializer {
+                    Some(initializer) => self.evaluate(initializer)?,
+                    None => Value::Nil,
+                };
+                self.bind_pattern(target, value, Binding::Const)
+            }
+            _ => panic!("Expected const statement"),
+        }
+    }
+
+    fn visit_switch_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Switch {
+                discriminant,
+                cases,
+            } => {
+                let value = self.evaluate(discriminant)?;
+
+                // All clauses share a single scope, like the braces around them suggest.
+                let environment = Rc::new(RefCell::new(Environment::with_enclosing(
+                    self.environment.clone(),
+                )));
+                let previous = std::mem::replace(&mut self.environment, environment);
+                let result = self.execute_switch(&value, cases);
+                self.environment = previous;
+
+                match result {
+                    Err(Unwind::Break(None)) => Ok(()),
+                    result => result,
+                }
+            }
+            _ => panic!("Expected switch statement"),
+        }
+    }
+
+    fn visit_throw_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Throw { keyword, value } => {
+                let value = self.evaluate(value)?;
+                Err(Unwind::Throw {
+                    value,
+                    token: keyword.clone(),
+                })
+            }
+            _ => panic!("Expected throw statement"),
+        }
+    }
+
+    fn visit_try_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::Try {
+                try_block,
+                catch_param,
+                catch_block,
+                finally_block,
+            } => {
+                let environment = Rc::new(RefCell::new(Environment::with_enclosing(
+                    self.environment.clone(),
+                )));
+                let mut result = self.execute_block(try_block, environment);
+
+                if let Some(catch_block) = catch_block {
+                    if let Err(unwind) = result {
+                        result = match self.exception_value(unwind) {
+                            Ok(exception) => {
+                                let environment = Rc::new(RefCell::new(
+                                    Environment::with_enclosing(self.environment.clone()),
+                                ));
+                                if let Some(param) = catch_param {
+                                    environment.borrow_mut().define(&param.lexeme, exception);
+                                }
+                                self.execute_block(catch_block, environment)
+                            }
+                            Err(unwind) => Err(unwind),
+                        };
+                    }
+                }
+
+                // A `finally` block that completes abruptly replaces whatever
+                // the try or catch block was doing; otherwise that outcome stands.
+                if let Some(finally_block) = finally_block {
+                    let environment = Rc::new(RefCell::new(Environment::with_enclosing(
+                        self.environment.clone(),
+                    )));
+                    self.execute_block(finally_block, environment)?;
+                }
+
+                result
+            }
+            _ => panic!("Expected try statement"),
+        }
+    }
+
+    fn visit_type_alias_statement(&mut self, _statement: &Statement) -> Result<(), Unwind> {
+        Ok(())
+    }
+
+    fn visit_while_statement(&mut self, statement: &Statement) -> Result<(), Unwind> {
+        match statement {
+            Statement::While {
+                condition,
+                body,
+                increment,
+                label,
+            } => {
+                loop {
+                    let value = self.evaluate(condition)?;
+                    if !self.is_truthy(&value) {
+                        break;
+                    }
+
+                    match self.execute(body) {
+                        Ok(()) => {}
+                        Err(Unwind::Break(target)) if self.loop_owns(label, &target) => break,
+                        Err(Unwind::Continue(target)) if self.loop_owns(label, &target) => {}
+                        Err(unwind) => return Err(unwind),
+                    }
+
+                    if let Some(increment) = increment {
+                        self.evaluate(increment)?;
+                    }
+                }
+                Ok(())
+            }
+            _ => panic!("Expected while statement"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{
+        resolver::Resolver,
+        scanner::Scanner,
+        token::{Token, TokenType},
+        Parser,
+    };
+
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::{
+        console, generator,
+        object::{Object, ObjectKind},
+        value::Value,
+        Interpreter,
+    };
+
+    /// Runs `source` on a thread with a roomy stack, like `main` does, and
+    /// returns the debug representation of the requested globals.
+    fn run(source: &str, names: &[&str]) -> Vec<String> {
+        let source = source.to_string();
+        let names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+
+        std::thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(move || {
+                let mut scanner = Scanner::new(source);
+                let tokens = scanner.scan_tokens().clone();
+                let mut parser = Parser::new(tokens);
+                let statements = parser.parse();
+                assert!(!parser.had_error());
+
+                let mut resolver = Resolver::new();
+                resolver.resolve(&statements);
+                assert!(!resolver.had_error());
+
+                let mut interpreter = Interpreter::new();
+                if let Err(error) = interpreter.interpret(&statements) {
+                    panic!("{}", error);
+                }
+                loop {
+                    let job = interpreter.event_loop.borrow_mut().next_job();
+                    match job {
+                        Ok(Some(job)) => {
+                            if let Err(error) = interpreter.run_job(job) {
+                                panic!("{}", error);
+                            }
+                        }
+                        Ok(None) => break,
+                        Err(error) => panic!("{}", error),
+                    }
+                }
+
+                names
+                    .iter()
+                    .map(|name| {
+                        let token = Token::new(TokenType::Identifier, name.clone(), None, 0);
+                        let value = interpreter.globals.borrow().get(&token).unwrap();
+                        format!("{:?}", value)
+                    })
+                    .collect()
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+    }
+
+    fn has_resolve_errors(source: &str) -> bool {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().clone();
+        let mut parser = Parser::new(tokens);
+        let statements = parser.parse();
+        assert!(!parser.had_error());
+
+        let mut resolver = Resolver::new();
+        resolver.resolve(&statements);
+        resolver.had_error()
+    }
+
+    #[test]
+    fn test_break_and_continue() {
+        let globals = run(
+            "
+            let sum = 0;
+            for (let i = 0; i < 10; i = i + 1) {
+                if (i == 2) continue;
+                if (i == 5) break;
+                sum = sum + i;
+            }
+            ",
+            &["sum"],
+        );
+
+        assert_eq!(globals, ["8"]);
+    }
+
+    #[test]
+    fn test_labeled_loops() {
+        let globals = run(
+            "
+            let pairs = \"\";
+            outer: for (let i = 0; i < 3; i = i + 1) {
+                for (let j = 0; j < 3; j = j + 1) {
+                    if (j == 1) continue outer;
+                    if (i == 2) break outer;
+                    pairs = pairs + i + j + \" \";
+                }
+            }
+            let reached = false;
+            block: {
+                break block;
+                reached = true;
+            }
+            ",
+            &["pairs", "reached"],
+        );
+
+        assert_eq!(globals, ["\"00 10 \"", "false"]);
+    }
+
+    #[test]
+    fn test_do_while_and_switch() {
+        let globals = run(
+            "
+            let count = 0;
+            do {
+                count = count + 1;
+            } while (count < 0);
+
+            function describe(n) {
+                let out = \"\";
+                switch (n) {
+                    case 1:
+                        out = out + \"one \";
+                    case 2:
+                        out = out + \"two \";
+                        break;
+                    default:
+                        out = out + \"many \";
+                    case 3:
+                        out = out + \"three\";
+                }
+                return out;
+            }
+            let a = describe(1);
+            let b = describe(3);
+            let c = describe(7);
+            ",
+            &["count", "a", "b", "c"],
+        );
+
+        assert_eq!(
+            globals,
+            ["1", "\"one two \"", "\"three\"", "\"many three\""]
+        );
+    }
+
+    #[test]
+    fn test_try_catch_finally() {
+        let globals = run(
+            "
+            let log = \"\";
+            function risky() {
+                try {
+                    return 1 + nil;
+                } finally {
+                    log = log + \"finally \";
+                }
+            }
+            try {
+                risky();
+            } catch (e) {
+                log = log + e.name;
+            }
+
+            function deep(n) { return deep(n + 1); }
+            let overflow = nil;
+            try { deep(0); } catch (e) { overflow = e.name; }
+
+            let thrown = nil;
+            try { throw Error(\"boom\"); } catch (e) { thrown = e.message; }
+            ",
+            &["log", "overflow", "thrown"],
+        );
+
+        assert_eq!(
+            globals,
+            ["\"finally TypeError\"", "\"RangeError\"", "\"boom\""]
+        );
+    }
+
+    #[test]
+    fn test_function_expressions_and_arrows() {
+        let globals = run(
+            "
+            function apply(f, x) { return f(x); }
+            let double = apply(x => x * 2, 21);
+            let sum = apply((a) => { return a + 1; }, 1);
+            let pair = ((a, b) => a + b)(2, 3);
+            let grouped = (1 + 2) * 3;
+            let fact = function fact(n) {
+                if (n <= 1) return 1;
+                return n * fact(n - 1);
+            };
+            let five = fact(5);
+            let make = () => function () { return \"inner\"; };
+            let inner = make()();
+            ",
+            &["double", "sum", "pair", "grouped", "five", "inner"],
+        );
+
+        assert_eq!(globals, ["42", "2", "5", "9", "120", "\"inner\""]);
+    }
+
+    #[test]
+    fn test_default_rest_and_spread() {
+        let globals = run(
+            "
+            function greet(name, greeting = \"hello \" + name) { return greeting; }
+            let defaulted = greet(\"ann\");
+            let given = greet(\"ann\", \"hi\");
+
+            function collect(first, ...rest) { return rest; }
+            let rest = collect(1, 2, 3);
+            let spread = collect(...rest, ...\"ab\");
+
+            let too_few = nil;
+            try { collect(); } catch (e) { too_few = e.message; }
+            ",
+            &["defaulted", "given", "rest", "spread", "too_few"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "\"hello ann\"",
+                "\"hi\"",
+                "[2, 3]",
+                "[3, \"a\", \"b\"]",
+                "\"Expected at least 1 arguments but got 0.\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_objects() {
+        let globals = run(
+            "
+            let key = \"b\";
+            let x = 3;
+            let object = { a: 1, [key]: 2, \"c d\": 4, x, double() { return 2; } };
+            object.a = object.a + 10;
+            object[\"e\"] = object.double();
+            let a = object.a;
+            let b = object[key];
+            let missing = object.missing;
+
+            let error = nil;
+            try { nil.a; } catch (e) { error = e.message; }
+            ",
+            &["object", "a", "b", "missing", "error"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "{ a: 11, b: 2, c d: 4, x: 3, double: <fn double>, e: 2 }",
+                "11",
+                "2",
+                "nil",
+                "\"Cannot read property 'a' of nil.\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_object_methods() {
+        let globals = run(
+            "
+            let counter = {
+                count: 0,
+                increment() { this.count = this.count + 1; return this; },
+                add: function (amount) { this.count = this.count + amount; },
+                reader() { return () => this.count; },
+            };
+            counter.increment().increment();
+            counter.add(3);
+            counter[\"increment\"]();
+            let read = counter.reader()();
+
+            let other = { count: 10, increment: counter.increment };
+            other.increment();
+
+            let unbound = counter.increment;
+            let error = nil;
+            try { unbound(); } catch (e) { error = e.message; }
+            ",
+            &["counter", "read", "other", "error"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "{ count: 6, increment: <fn increment>, add: <fn anonymous>, reader: <fn reader> }",
+                "6",
+                "{ count: 11, increment: <fn increment> }",
+                "\"Cannot read property 'count' of nil.\""
+            ]
+        );
+
+        assert!(has_resolve_errors("function f() { return this; }"));
+        assert!(has_resolve_errors(
+            "let o = { m() { return function () { return this; }; } };"
+        ));
+    }
+
+    #[test]
+    fn test_circular_objects() {
+        let globals = run(
+            "
+            let object = { a: 1 };
+            object.self = object;
+            let text = \"x\" + object;
+
+            let thrown = nil;
+            try { throw object; } catch (e) { thrown = \"\" + e; }
+            ",
+            &["object", "text", "thrown"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "{ a: 1, self: [Circular] }",
+                "\"x{ a: 1, self: [Circular] }\"",
+                "\"{ a: 1, self: [Circular] }\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arrays() {
+        let globals = run(
+            "
+            let array = [1, , 3, ...[4]];
+            array[5] = 6;
+            let length = array.length;
+            let pushed = array.push(7);
+            let popped = array.pop();
+
+            let mapped = [3, 1, 2].map(x => x * 2).sort((a, b) => a - b);
+            let filtered = [1, 2, 3, 4].filter(x => x > 2);
+            let sum = [1, 2, 3].reduce((total, x) => total + x, 0);
+            let sliced = [1, 2, 3, 4].slice(1, -1);
+            let joined = [1, nil, \"a\"].join(\"-\");
+            let found = [1, 2, 3].indexOf(3);
+            let nested = [1, [2, [3, nil]]].join(\";\");
+            let cycle = [1];
+            cycle.push(cycle);
+            let cyclic = cycle.join();
+            ",
+            &[
+                "array", "length", "pushed", "popped", "mapped", "filtered", "sum", "sliced",
+                "joined", "found", "nested", "cyclic",
+            ],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "[1, nil, 3, 4, nil, 6]",
+                "6",
+                "7",
+                "7",
+                "[2, 4, 6]",
+                "[3, 4]",
+                "6",
+                "[2, 3]",
+                "\"1--a\"",
+                "2",
+                "\"1;2,3,\"",
+                "\"1,\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classes() {
+        let globals = run(
+            "
+            class Shape {
+                constructor(name) { this.name = name; }
+                describe() { return this.name + \" with area \" + this.area; }
+                static named(name) { return new this(name); }
+            }
+
+            class Square extends Shape {
+                constructor(side) { super(\"square\"); this.side = side; }
+                get area() { return this.side * this.side; }
+                set area(value) { this.side = value / this.side; }
+                describe() { return \"a \" + super.describe(); }
+            }
+
+            let square = new Square(3);
+            let described = square.describe();
+            square.area = 12;
+            let side = square.side;
+            let named = Shape.named(\"circle\").name;
+
+            let error = nil;
+            try { Square(1); } catch (e) { error = e.message; }
+            ",
+            &["square", "described", "side", "named", "error"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "Square { name: \"square\", side: 4 }",
+                "\"a square with area 9\"",
+                "4",
+                "\"circle\"",
+                "\"Class constructor Square cannot be invoked without 'new'.\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_private_members() {
+        let globals = run(
+            "
+            class Counter {
+                #count = 0;
+                static #created = 0;
+                constructor() { Counter.#created = Counter.#created + 1; }
+                #add(amount) { this.#count = this.#count + amount; }
+                increment() { this.#add(1); return this; }
+                get count() { return this.#count; }
+                static get created() { return Counter.#created; }
+                read(other) { return other.#count; }
+            }
+
+            let counter = new Counter().increment().increment();
+            let count = counter.count;
+            let created = Counter.created;
+
+            let error = nil;
+            try { counter.read({}); } catch (e) { error = e.message; }
+            ",
+            &["counter", "count", "created", "error"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "Counter {}",
+                "2",
+                "1",
+                "\"Cannot read private member #count from an object whose class did not declare it.\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_access_modifiers() {
+        let globals = run(
+            "
+            class Account {
+                private balance = 0;
+                protected owner = \"Ada\";
+                private static opened = 0;
+                constructor() { Account.opened = Account.opened + 1; }
+                deposit(amount) { this.balance = this.balance + amount; return this; }
+                compare(other) { return this.balance - other.balance; }
+                static count() { return Account.opened; }
+            }
+            class Savings extends Account {
+                describe() { return this.owner; }
+                peek(account) { return account.balance; }
+            }
+
+            let account = new Account().deposit(5);
+            let difference = account.compare(new Account());
+            let count = Account.count();
+            let savings = new Savings();
+            let owner = savings.describe();
+
+            let errors = [];
+            let Bank = Account;
+            let attempts = [
+                () => account.balance,
+                () => { account.balance = 1; },
+                () => account.owner,
+                () => savings.peek(savings),
+                () => Bank.opened,
+            ];
+            for (let attempt of attempts) {
+                try { attempt(); } catch (e) { errors.push(e.message); }
+            }
+
+            class Limited { private size = 0; private name = \"\"; }
+            let user = { name: \"Ada\" };
+            let name = user.name;
+            let size = new Set([1, 2]).size;
+            ",
+            &["difference", "count", "owner", "errors", "name", "size"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "5",
+                "2",
+                "\"Ada\"",
+                "[\"Property 'balance' is private and only accessible within class 'Account'.\", \
+                 \"Property 'balance' is private and only accessible within class 'Account'.\", \
+                 \"Property 'owner' is protected and only accessible within class 'Account' and its subclasses.\", \
+                 \"Property 'balance' is private and only accessible within class 'Account'.\", \
+                 \"Property 'opened' is private and only accessible within class 'Account'.\"]",
+                "\"Ada\"",
+                "2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_access_modifier_diagnostics() {
+        let rejected = [
+            "class A { private x = 1; } class B extends A { m() { return this.x; } }",
+            "class A { private static n = 0; } A.n = 1;",
+            "class A { protected x = 1; } print new A().x;",
+            "class A { protected static m() {} } class B extends A {} B.m();",
+        ];
+        for source in rejected {
+            assert!(has_resolve_errors(source), "{}", source);
+        }
+
+        let accepted = [
+            "class A { protected x = 1; } class B extends A { m() { return this.x; } }",
+            "class A { private x = 1; m() { return new A().x + A.n; } private static n = 0; }",
+            "class A { private static n = 0; } function f(A) { return A.n; }",
+            "class A { private x = 1; } function f(B) { class C extends B { m() { return this.x; } } }",
+        ];
+        for source in accepted {
+            assert!(!has_resolve_errors(source), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_destructuring() {
+        let globals = run(
+            "
+            let source = { a: 1, b: [2], c: 3, d: 4 };
+            let { a, b: [c, d = 10], ...rest } = source;
+            const [first, , third = 3, ...others] = [1, 2, nil, 4, 5];
+
+            let x = 1;
+            let y = 2;
+            [x, y] = [y, x];
+
+            let target = {};
+            [target.left, target[\"right\"] = 7] = [5];
+
+            function describe({ name, age = 30 }, [head, ...tail]) {
+                return name + \" \" + age + \" \" + head + \" \" + tail.length;
+            }
+            let described = describe({ name: \"Ada\" }, [1, 2, 3]);
+            ",
+            &[
+                "a",
+                "c",
+                "d",
+                "rest",
+                "third",
+                "others",
+                "x",
+                "y",
+                "target",
+                "described",
+            ],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "1",
+                "2",
+                "10",
+                "{ c: 3, d: 4 }",
+                "3",
+                "[4, 5]",
+                "2",
+                "1",
+                "{ left: 5, right: 7 }",
+                "\"Ada 30 1 2\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_for_of_and_for_in() {
+        let globals = run(
+            "
+            let sum = 0;
+            for (const x of [1, 2, 3]) sum = sum + x;
+
+            let letters = \"\";
+            for (let letter of \"abc\") letters = letter + letters;
+
+            let keys = \"\";
+            for (const key in { a: 1, b: 2 }) keys = keys + key;
+
+            let closures = [];
+            for (const x of [10, 20]) closures.push(() => x);
+            let captured = closures[0]() + closures[1]();
+
+            class Range {
+                constructor(end) { this.end = end; }
+                [Symbol.iterator]() {
+                    let current = 0;
+                    let end = this.end;
+                    return {
+                        next: () => {
+                            current = current + 1;
+                            return { value: current, done: current > end };
+                        }
+                    };
+                }
+            }
+            let pairs = \"\";
+            for (const [key, value] of [[\"x\", 1], [\"y\", 2]]) pairs = pairs + key + value;
+            let spread = [...new Range(3)];
+            ",
+            &["sum", "letters", "keys", "captured", "pairs", "spread"],
+        );
+
+        assert_eq!(
+            globals,
+            ["6", "\"cba\"", "\"ab\"", "30", "\"x1y2\"", "[1, 2, 3]"]
+        );
+    }
+
+    #[test]
+    fn test_iterator_protocol() {
+        let globals = run(
+            "
+            let closed = 0;
+            class Counter {
+                [Symbol.iterator]() {
+                    let count = 0;
+                    return {
+                        next: () => {
+                            count = count + 1;
+                            return { value: count, done: count > 3 };
+                        },
+                        return: () => {
+                            closed = closed + 1;
+                            return { done: true };
+                        },
+                    };
+                }
+            }
+            for (const n of new Counter()) { if (n == 2) break; }
+            function first() { for (const n of new Counter()) return n; }
+            first();
+            try { for (const n of new Counter()) throw n; } catch (e) {}
+            for (const n of new Counter()) {}
+            let [one] = new Counter();
+            let [a, b, c, d] = new Counter();
+            let [head, ...tail] = new Counter();
+            let finished = false;
+            function* pair() { try { yield 1; yield 2; } finally { finished = true; } }
+            let [first_of_pair] = pair();
+
+            let iterator = [1, 2][Symbol.iterator]();
+            let steps = [iterator.next().value, iterator.next().value, iterator.next().done];
+            let spread = [
+                ...\"ab\"[Symbol.iterator](),
+                ...new Map([[1, 2]])[Symbol.iterator](),
+                ...new Set([3])[Symbol.iterator](),
+            ];
+
+            let errors = [];
+            try { Symbol.iterator = \"@@iterator\"; } catch (e) { errors.push(e.message); }
+            let forged = { \"@@iterator\": () => [][Symbol.iterator]() };
+            try { for (const x of forged) {} } catch (e) { errors.push(e.message); }
+            let matched = [
+                match ([1]) { { \"@@iterator\": f } => f, _ => \"none\" },
+                match (forged) { { \"@@iterator\": f } => \"string key\", _ => \"none\" },
+            ];
+            ",
+            &["closed", "finished", "steps", "spread", "errors", "matched"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "4",
+                "true",
+                "[1, 2, true]",
+                "[\"a\", \"b\", [1, 2], 3]",
+                "[\"Cannot assign to read only property 'iterator' of object.\", \
+                 \"Object is not iterable.\"]",
+                "[\"none\", \"string key\"]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generators() {
+        let globals = run(
+            "
+            function* count(end) {
+                for (let i = 1; i <= end; i = i + 1) {
+                    const skip = yield i;
+                    if (skip) i = i + skip;
+                }
+                return \"done\";
+            }
+            let counter = count(5);
+            let first = counter.next().value;
+            let skipped = counter.next(2).value;
+            let rest = [...counter];
+
+            function* inner() { yield 1; return 2; }
+            function* outer() {
+                const result = yield* inner();
+                yield* [result, 3];
+            }
+            let delegated = [...outer()];
+
+            let cleaned = false;
+            function* guarded() {
+                try { yield 1; yield 2; } finally { cleaned = true; }
+            }
+            let stopped = guarded();
+            stopped.next();
+            let returned = stopped.return(7);
+
+            function* catching() {
+                try { yield 1; } catch (error) { yield \"caught \" + error; }
+            }
+            let thrown = catching();
+            thrown.next();
+            let caught = thrown.throw(\"oops\").value;
+
+            class Pair {
+                constructor(a, b) { this.a = a; this.b = b; }
+                *[Symbol.iterator]() { yield this.a; yield this.b; }
+            }
+            let pair = \"\";
+            for (const x of new Pair(\"x\", \"y\")) pair = pair + x;
+            ",
+            &[
+                "first",
+                "skipped",
+                "rest",
+                "delegated",
+                "cleaned",
+                "returned",
+                "caught",
+                "pair",
+            ],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "1",
+                "4",
+                "[5]",
+                "[1, 2, 3]",
+                "true",
+                "{ value: 7, done: true }",
+                "\"caught oops\"",
+                "\"xy\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_delegated_generators() {
+        let globals = run(
+            "
+            let log = [];
+            function* inner() {
+                try {
+                    log.push(\"got \" + (yield 1));
+                    log.push(\"got \" + (yield 2));
+                    return \"done\";
+                } catch (error) {
+                    yield \"caught \" + error;
+                } finally {
+                    log.push(\"finally\");
+                }
+            }
+            function* outer() { log.push(yield* inner()); }
+
+            let sent = outer();
+            sent.next(\"ignored\");
+            sent.next(\"a\");
+            sent.next(\"b\");
+
+            let thrown = outer();
+            thrown.next();
+            let caught = thrown.throw(\"oops\").value;
+
+            let returned = outer();
+            returned.next();
+            let result = returned.return(7);
+
+            function* array() { yield* [1, 2]; }
+            let plain = array();
+            plain.next();
+            let error = nil;
+            try { plain.throw(\"oops\"); } catch (e) { error = e.message; }
+            ",
+            &["log", "caught", "result", "error"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "[\"got a\", \"got b\", \"finally\", \"done\", \"finally\"]",
+                "\"caught oops\"",
+                "{ value: 7, done: true }",
+                "\"The iterator does not provide a 'throw' method.\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_promises() {
+        let globals = run(
+            "
+            let order = [];
+            function delay(ms, value) {
+                return new Promise((resolve) => setTimeout(resolve, ms, value));
+            }
+            async function stage(name, ms) {
+                order.push(\"start \" + name);
+                const result = await delay(ms, name);
+                order.push(\"end \" + result);
+                return result + \"!\";
+            }
+            let all;
+            let caught;
+            async function main() {
+                all = await Promise.all([stage(\"a\", 30), stage(\"b\", 10), 5]);
+                try { await Promise.reject(\"nope\"); } catch (error) { caught = error; }
+                return await Promise.race([delay(50, \"slow\"), delay(20, \"fast\")]);
+            }
+            let raced;
+            main().then((value) => { raced = value; });
+            setTimeout(() => order.push(\"timer\"));
+            queueMicrotask(() => order.push(\"microtask\"));
+            order.push(\"sync\");
+            let chained;
+            Promise.resolve(1).then((n) => n + 1).then((n) => { chained = n; });
+            ",
+            &["order", "all", "caught", "raced", "chained"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "[\"start a\", \"start b\", \"sync\", \"microtask\", \"timer\", \"end b\", \"end a\"]",
+                "[\"a!\", \"b!\", 5]",
+                "\"nope\"",
+                "\"fast\"",
+                "2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_promise_adoption() {
+        let globals = run(
+            "
+            let chained;
+            Promise.resolve(1)
+                .then((x) => Promise.resolve(x + 1))
+                .then((x) => { chained = x; });
+
+            async function inner() { return 5; }
+            async function outer() { return inner(); }
+            let returned;
+            outer().then((x) => { returned = x; });
+            let awaited;
+            (async () => { awaited = await outer(); })();
+
+            let rejected;
+            new Promise((resolve) => resolve(Promise.reject(\"bad\")))
+                .catch((error) => { rejected = error; });
+            ",
+            &["chained", "returned", "awaited", "rejected"],
+        );
+
+        assert_eq!(globals, ["2", "5", "5", "\"bad\""]);
+    }
+
+    #[test]
+    fn test_unfinished_limit() {
+        let globals = run(
+            "
+            async function job(i) { await nil; return i; }
+            let jobs = [];
+            let error = nil;
+            try { while (true) jobs.push(job(jobs.length)); } catch (e) { error = e.name; }
+            let created = jobs.length;
+            let total;
+            let after;
+            Promise.all(jobs)
+                .then((values) => { total = values.length; return job(0); })
+                .then((x) => { after = x; });
+            ",
+            &["error", "created", "total", "after"],
+        );
+
+        let limit = generator::MAX_UNFINISHED.to_string();
+        assert_eq!(globals, ["\"RangeError\"", &limit, &limit, "0"]);
+    }
+
+    #[test]
+    fn test_optional_chaining() {
+        let globals = run(
+            "
+            let config = { server: { port: 80, tags: [\"a\", \"b\"], name() { return \"web\"; } } };
+            let port = config?.server?.port;
+            let missing = config.client?.port.deeply.nested;
+            let tag = config.server?.[\"tags\"]?.[1];
+            let name = config.server.name?.();
+            let calls = 0;
+            function count() { calls = calls + 1; }
+            let callback = nil;
+            let called = callback?.(count());
+            ",
+            &["port", "missing", "tag", "name", "called", "calls"],
+        );
+
+        assert_eq!(globals, ["80", "nil", "\"b\"", "\"web\"", "nil", "0"]);
+    }
+
+    #[test]
+    fn test_enums() {
+        let globals = run(
+            "
+            enum Color { Red, Green, Blue }
+            enum Shape { Circle(r), Rect(w, h) }
+            let red = Color.Red;
+            let rect = Shape.Rect(2, \"x\");
+            let width = rect.w;
+            let same = Shape.Circle(Color.Blue) == Shape.Circle(Color.Blue);
+            let different = Shape.Circle(1) == Shape.Circle(2);
+            let error = nil;
+            try { rect.w = 3; } catch (e) { error = e.message; }
+            ",
+            &[
+                "Color",
+                "red",
+                "rect",
+                "width",
+                "same",
+                "different",
+                "error",
+            ],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "<enum Color>",
+                "Color.Red",
+                "Shape.Rect(2, \"x\")",
+                "2",
+                "true",
+                "false",
+                "\"Cannot assign to read only property 'w' of Shape.Rect.\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match() {
+        let globals = run(
+            "
+            enum Shape { Circle(r), Rect(w, h) }
+            function area(shape) {
+                return match (shape) {
+                    Shape.Circle(r) => 3 * r * r,
+                    Shape.Rect(w, h) if (w == h) => \"square\",
+                    Shape.Rect(w, h) => w * h,
+                };
+            }
+            function describe(value) {
+                return match (value) {
+                    1 | 2 => \"small\",
+                    { kind: \"point\", x } => x,
+                    { missing } => \"has missing\",
+                    [first, ...rest] => rest.length,
+                    _ => \"other\",
+                };
+            }
+            let areas = [area(Shape.Circle(2)), area(Shape.Rect(2, 2)), area(Shape.Rect(2, 3))];
+            let described = [
+                describe(2),
+                describe({ kind: \"point\", x: 5 }),
+                describe([1, 2, 3]),
+                describe([]),
+                describe({ missing: nil }),
+                describe({}),
+            ];
+            let error = nil;
+            try { match (3) { 1 => 1 }; } catch (e) { error = e.message; }
+            ",
+            &["areas", "described", "error"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "[12, \"square\", 6]",
+                "[\"small\", 5, 2, \"other\", \"has missing\", \"other\"]",
+                "\"No match arm matches 3.\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_records_and_tuples() {
+        let globals = run(
+            "
+            let point = #{ y: 2, x: 1 };
+            let same = point === #{ x: 1, y: #[2][0] };
+            let nested = #[1, #{ nan: 0 / 0 }] == #[1, #{ nan: 0 / 0 }];
+            let objects = {} == {};
+            let spread = [...#[1, 2], 3];
+            let errors = [];
+            try { point.x = 3; } catch (e) { errors.push(e.message); }
+            try { #[[1]]; } catch (e) { errors.push(e.message); }
+            ",
+            &["point", "same", "nested", "objects", "spread", "errors"],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "#{ x: 1, y: 2 }",
+                "true",
+                "true",
+                "false",
+                "[1, 2, 3]",
+                "[\"Cannot assign to read only property 'x' of record.\", \"Records and tuples may only contain primitives, records and tuples.\"]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operator_overloading() {
+        let globals = run(
+            "
+            class Vector {
+                constructor(x, y) { this.x = x; this.y = y; }
+                operator +(other) { return new Vector(this.x + other.x, this.y + other.y); }
+                operator *(k, reversed) { return new Vector(this.x * k, this.y * k); }
+                operator ==(other) { return this.x == other.x and this.y == other.y; }
+            }
+            class Cents {
+                constructor(value) { this.value = value; }
+                operator -(other) { return new Cents(this.value - other); }
+            }
+            class Offset {
+                constructor(value) { this.value = value; }
+                operator -(other, reversed) {
+                    if (reversed) return other - this.value;
+                    return this.value - other;
+                }
+            }
+            let a = new Vector(1, 2);
+            let sum = a + new Vector(3, 4);
+            let scaled = 2 * a;
+            let equal = a == new Vector(1, 2);
+            let unequal = a != new Vector(1, 2);
+            let identical = [a === new Vector(1, 2), a !== new Vector(1, 2), a === a];
+            let differences = [new Offset(3) - 10, 10 - new Offset(3)];
+            let error = nil;
+            try { a - a; } catch (e) { error = e.message; }
+            let unswapped = nil;
+            try { 10 - new Cents(3); } catch (e) { unswapped = e.message; }
+            ",
+            &[
+                "sum",
+                "scaled",
+                "equal",
+                "unequal",
+                "identical",
+                "differences",
+                "error",
+                "unswapped",
+            ],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "Vector { x: 4, y: 6 }",
+                "Vector { x: 2, y: 4 }",
+                "true",
+                "false",
+                "[false, true, true]",
+                "[-7, 7]",
+                "\"Operator '-' is not defined for Vector and Vector.\"",
+                "\"Operator '-' is not defined for number and Cents.\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bigint() {
+        let globals = run(
+            "
+            let id = 9007199254740993n + 1n;
+            let power = 2n ** 100n;
+            let quotient = -7n / 2n;
+            let remainder = -7n % 2n;
+            let ordered = 10n > 9n;
+            let converted = BigInt(\"12345678901234567890\") * BigInt(10);
+            let mixed = nil;
+            try { 1n + 1; } catch (e) { mixed = e.message; }
+            ",
+            &[
+                "id",
+                "power",
+                "quotient",
+                "remainder",
+                "ordered",
+                "converted",
+                "mixed",
+            ],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "9007199254740994n",
+                "1267650600228229401496703205376n",
+                "-3n",
+                "-1n",
+                "true",
+                "123456789012345678900n",
+                "\"Cannot mix BigInt and other types, use explicit conversions.\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_automatic_semicolon_insertion() {
+        let source = "
+            let a = 1
+            let b = a
+              + 2
+            function early() {
+              return
+                42
+            }
+            let returned = early()
+            let total = 0
+            for (let i = 0; i < 4; i = i + 1) {
+              if (i == 2) continue
+              total = total + i
+            }
+            do { a = a + 1 } while (a < 3) print a
+            ";
+        let globals = run(source, &["a", "b", "returned", "total"]);
+        assert_eq!(globals, ["3", "3", "nil", "4"]);
+
+        let mut scanner = Scanner::new(source.to_string());
+        let mut parser = Parser::new(scanner.scan_tokens().clone());
+        parser.set_strict(true);
+        parser.parse();
+        assert!(parser.had_error());
+    }
+
+    #[test]
+    fn test_regular_expressions() {
+        let globals = run(
+            "
+            let phone = /(\\d+)-(\\d+)/;
+            let found = phone.test(\"call 555-1234\");
+            let groups = phone.exec(\"call 555-1234\");
+            let all = \"a1b22c333\".match(/\\d+/g);
+            let date = \"2024-10-18\".replace(/(?<y>\\d+)-(?<m>\\d+)-(?<d>\\d+)/, \"$<d>/$<m>/$<y>\");
+            let shout = \"hello\".replace(/l/g, function (l) { return l + l; });
+            let words = \"one  two three\".split(/\\s+/);
+            let half = 10 / 2 / 5;
+            let error = nil;
+            try { new RegExp(\"(\"); } catch (e) { error = e.name; }
+            let long = \"ab\";
+            for (let i = 0; i < 17; i = i + 1) long = long + long;
+            function* check() { yield /^(ab)*$/.test(long); }
+            let repeated = check().next().value;
+            let backtracking = nil;
+            try { /^(a+)+$/.test(\"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa!\"); } catch (e) { backtracking = e.name; }
+            ",
+            &[
+                "found", "groups", "all", "date", "shout", "words", "half", "error", "repeated",
+                "backtracking",
+            ],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "true",
+                "[\"555-1234\", \"555\", \"1234\"]",
+                "[\"1\", \"22\", \"333\"]",
+                "\"18/10/2024\"",
+                "\"hellllo\"",
+                "[\"one\", \"two\", \"three\"]",
+                "1",
+                "\"SyntaxError\"",
+                "true",
+                "\"RangeError\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_console() {
+        let globals = run(
+            "
+            let print_ = print;
+            print(\"as a call\");
+            print \"as a statement\";
+            let logged = console.log(\"%s: %d\", \"total\", 4);
+            ",
+            &["print_", "logged"],
+        );
+        assert_eq!(globals, ["<fn native print>", "nil"]);
+
+        let mut object = Object::new(ObjectKind::Ordinary);
+        object.set("name", Value::from("Ada"));
+        object.set(
+            "nested",
+            Value::array(vec![Value::array(vec![Value::array(vec![])])]),
+        );
+        let object = Rc::new(RefCell::new(object));
+        object
+            .borrow_mut()
+            .set("self", Value::Object(object.clone()));
+        let object = Value::Object(object);
+
+        assert_eq!(
+            console::format(&[
+                Value::from("%s is %i, %f%% and %o"),
+                Value::from("Ada"),
+                Value::from(36.7),
+                Value::from(0.5),
+                object.clone(),
+                Value::from("extra"),
+            ]),
+            "Ada is 36, 0.5% and { name: \"Ada\", nested: [[[Array]]], self: [Circular] } extra"
+        );
+        assert_eq!(
+            console::render_table(&Value::array(vec![
+                Value::array(vec![Value::from(1.0), Value::from("two")]),
+                Value::from(3.0),
+            ]))
+            .unwrap(),
+            [
+                "┌─────────┬───┬───────┬────────┐",
+                "│ (index) │ 0 │ 1     │ Values │",
+                "├─────────┼───┼───────┼────────┤",
+                "│ 0       │ 1 │ \"two\" │        │",
+                "│ 1       │   │       │ 3      │",
+                "└─────────┴───┴───────┴────────┘",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_collections() {
+        let globals = run(
+            "
+            let cache = new Map([[\"a\", 1], [#[1, 2], \"tuple\"]]);
+            cache.set(-0, \"zero\").set(0 / 0, \"nan\").set(\"a\", 2);
+            let lookups = [cache.get(0), cache.get(0 / 0), cache.get(#[1, 2]), cache.get(\"b\")];
+            cache.delete(#[1, 2]);
+            let pairs = [...cache];
+            let seen = new Set([3, 1, 3, \"3\"]);
+            seen.add(1).add(2);
+            let values = [];
+            seen.forEach(function (value) { values.push(value); });
+            let size = seen.size;
+            seen.clear();
+            let queue = new Set([1, 2, 3, 4, 5]);
+            let visited = [];
+            for (let n of queue) {
+                visited.push(n);
+                if (n == 1) { queue.delete(2); queue.delete(3); queue.delete(4); }
+                if (n < 3) queue.add(n + 10);
+            }
+            let order = new Map([[\"a\", 1], [\"b\", 2]]);
+            let keys = [];
+            order.forEach(function (value, key) {
+                keys.push(key);
+                if (key == \"a\") { order.delete(\"b\"); order.set(\"c\", 3); }
+            });
+            let owners = new WeakMap();
+            let key = {};
+            owners.set(key, \"owner\");
+            let weak = [owners.get(key), owners.has({}), new WeakSet([key]).has(key)];
+            let error = nil;
+            try { owners.set(\"key\", 1); } catch (e) { error = e.name; }
+            ",
+            &[
+                "lookups", "pairs", "values", "size", "seen", "visited", "keys", "weak", "error",
+            ],
+        );
+
+        assert_eq!(
+            globals,
+            [
+                "[\"zero\", \"nan\", \"tuple\", nil]",
+                "[[\"a\", 2], [0, \"zero\"], [NaN, \"nan\"]]",
+                "[3, 1, \"3\", 2]",
+                "4",
+                "Set(0) {}",
+                "[1, 5, 11]",
+                "[\"a\", \"c\"]",
+                "[\"owner\", false, true]",
+                "\"TypeError\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modules() {
+        let directory = std::env::temp_dir().join(format!("jsplusplus-{}", std::process::id()));
+        std::fs::create_dir_all(directory.join("lib")).unwrap();
+        for (file, source) in [
+            (
+                "lib/shapes.jspp",
+                "
+                import { square } from \"./math.jspp\";
+                export default class Square {
+                    constructor(side) { this.area = square(side); }
+                }
+                ",
+            ),
+            (
+                "lib/math.jspp",
+                "
+                export const PI = 3;
+                export function square(x) { return x * x; }
+                let calls = 0;
+                export { calls as count };
+                export let counter = 0;
+                export function increment() { counter = counter + 1; }
+                export function fail() { return nil.x; }
+                ",
+            ),
+            (
+                "a.jspp",
+                "import { b } from \"./b.jspp\"; export let a = 1;",
+            ),
+            (
+                "b.jspp",
+                "import { a } from \"./a.jspp\"; export let b = 1;",
+            ),
+        ] {
+            std::fs::write(directory.join(file), source).unwrap();
+        }
+
+        let (globals, had_error) = std::thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn(move || {
+                let mut jsplusplus = crate::JSPlusPlus::new();
+                jsplusplus.interpreter.set_directory(directory.clone());
+                jsplusplus.run(String::from(
+                    "
+                    import Square from \"./lib/shapes.jspp\";
+                    import * as math from \"./lib/math.jspp\";
+                    import { PI, square as sq, count } from \"./lib/math.jspp\";
+                    let area = new Square(3).area;
+                    let circle = PI * sq(2);
+                    let namespace = math;
+
+                    import { counter, increment } from \"./lib/math.jspp\";
+                    increment();
+                    let live = [counter, math.counter];
+                    let errors = [];
+                    try { math.counter = 5; } catch (e) { errors.push(e.message); }
+                    try { counter = 5; } catch (e) { errors.push(e.message); }
+                    let stack = nil;
+                    try { math.fail(); } catch (e) { stack = e.stack; }
+                    ",
+                ));
+                assert!(!jsplusplus.had_error && !jsplusplus.had_runtime_error);
+
+                let globals: Vec<String> = [
+                    "area",
+                    "circle",
+                    "count",
+                    "namespace",
+                    "live",
+                    "errors",
+                    "stack",
+                ]
+                .iter()
+                .map(|name| {
+                    let token = Token::new(TokenType::Identifier, name.to_string(), None, 0);
+                    let value = jsplusplus.interpreter.globals.borrow().get(&token);
+                    format!("{:?}", value.unwrap())
+                })
+                .collect();
+
+                jsplusplus.run(String::from("import { a } from \"./a.jspp\";"));
+                std::fs::remove_dir_all(&directory).unwrap();
+                (globals, jsplusplus.had_error)
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert_eq!(
+            globals,
+            [
+                "9",
+                "12",
+                "0",
+                "{ PI: 3, square: <fn square>, count: 0, counter: 1, increment: <fn increment>, \
+                 fail: <fn fail> }",
+                "[1, 1]",
+                "[\"Cannot assign to read only property 'counter' of module namespace.\", \
+                 \"Assignment to constant variable 'counter'.\"]",
+                "\"TypeError: Cannot read property 'x' of nil.\\n    at fail (line 8 in math.jspp)\\n    \
+                 at <script> (line 16)\""
+            ]
+        );
+        assert!(had_error);
     }
 }
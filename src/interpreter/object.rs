@@ -0,0 +1,138 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::parser::token::Token;
+
+use super::{
+    callable::Callable,
+    class::{Class, Member},
+    collection::{Entries, WeakEntries},
+    enumeration::{Enum, Variant},
+    environment::Environment,
+    generator::GeneratorState,
+    iterator::Iteration,
+    promise::PromiseState,
+    regex::Regex,
+    value::Value,
+};
+
+pub enum ObjectKind {
+    Ordinary,
+    Error,
+    Array(Vec<Value>),
+    Class(Rc<Class>),
+    Instance(Rc<Class>),
+    /// A built-in such as `Promise`, which `new` calls and whose properties
+    /// are its static methods.
+    Constructor(Rc<dyn Callable>),
+    Generator(GeneratorState),
+    Promise(PromiseState),
+    /// The object an `enum` declaration binds its name to.
+    Enum(Rc<Enum>),
+    /// A value made from an enum variant. Its fields are its properties.
+    Variant(Rc<Variant>),
+    /// `#{ a: 1 }`. Its members are its properties.
+    Record,
+    /// `#[1, 2]`.
+    Tuple(Vec<Value>),
+    /// `/ab+c/g`. Its `lastIndex` is an ordinary property.
+    RegExp(Rc<Regex>),
+    Map(Entries),
+    /// Its entries map each value to itself.
+    Set(Entries),
+    WeakMap(WeakEntries),
+    /// Its entries map each value to `true`.
+    WeakSet(WeakEntries),
+    /// The namespace of a module, as `import * as name` binds it.
+    Module(Rc<Namespace>),
+    /// What a built-in `[Symbol.iterator]()` method returns.
+    Iterator(Box<Iteration>),
+    /// A symbol such as `Symbol.iterator`, holding the key of the property
+    /// it names.
+    Symbol(&'static str),
+}
+
+/// The exports of a module. Reading one gives the current value of the
+/// variable it was exported from, and none can be assigned to.
+pub struct Namespace {
+    /// The scope the module ran in.
+    pub environment: Rc<RefCell<Environment>>,
+    /// Each export's name and the variable it reads.
+    pub exports: Vec<(String, Token)>,
+}
+
+impl Namespace {
+    /// The variable the export `name` reads.
+    pub fn local(&self, name: &str) -> Option<&Token> {
+        self.exports
+            .iter()
+            .find(|(exported, _)| exported == name)
+            .map(|(_, local)| local)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        let local = self.local(name)?;
+        self.environment.borrow().get(local).ok()
+    }
+}
+
+/// The contents of a `#private` slot.
+#[derive(Clone)]
+pub enum PrivateSlot {
+    Field(Value),
+    Member(Member),
+}
+
+/// A heap allocated property bag. Keys keep their insertion order so that
+/// printing and iteration are deterministic.
+pub struct Object {
+    pub kind: ObjectKind,
+    properties: HashMap<String, Value>,
+    keys: Vec<String>,
+    /// Private fields and methods, keyed by their class's runtime name for
+    /// them. They are never enumerated or printed.
+    private: HashMap<String, PrivateSlot>,
+    /// Frozen objects' properties cannot be assigned to.
+    frozen: bool,
+}
+
+impl Object {
+    pub fn new(kind: ObjectKind) -> Object {
+        Object {
+            kind,
+            properties: HashMap::new(),
+            keys: Vec::new(),
+            private: HashMap::new(),
+            frozen: false,
+        }
+    }
+
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.properties.get(key).cloned()
+    }
+
+    pub fn set(&mut self, key: &str, value: Value) {
+        if self.properties.insert(key.to_string(), value).is_none() {
+            self.keys.push(key.to_string());
+        }
+    }
+
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    pub fn get_private(&self, key: &str) -> Option<PrivateSlot> {
+        self.private.get(key).cloned()
+    }
+
+    pub fn set_private(&mut self, key: &str, slot: PrivateSlot) {
+        self.private.insert(key.to_string(), slot);
+    }
+}
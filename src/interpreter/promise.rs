@@ -0,0 +1,525 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{parser::token::Token, RuntimeError};
+
+use super::{
+    callable::{Callable, NativeFn, NativeFunction, NativeMethod, NativeMethodFn},
+    event_loop::Job,
+    generator::{self, Resume},
+    iterator::Step,
+    object::{Object, ObjectKind},
+    value::Value,
+    Interpreter, Unwind,
+};
+
+const STATICS: &[(&str, usize, Option<usize>, NativeFn)] = &[
+    ("all", 1, Some(1), all),
+    ("race", 1, Some(1), race),
+    ("reject", 0, Some(1), reject_static),
+    ("resolve", 0, Some(1), resolve_static),
+];
+
+pub const METHODS: &[(&str, usize, Option<usize>, NativeMethodFn)] =
+    &[("catch", 0, Some(1), catch), ("then", 0, Some(2), then)];
+
+pub enum PromiseState {
+    /// `resolved` is set once the promise has been resolved with another
+    /// promise, whose outcome it is now waiting for.
+    Pending {
+        reactions: Vec<Reaction>,
+        resolved: bool,
+    },
+    Fulfilled(Value),
+    Rejected(Value),
+}
+
+/// What is done with the outcome of a promise once it settles.
+pub struct Reaction {
+    pub kind: ReactionKind,
+    /// The promise that the reaction settles in turn.
+    pub promise: Value,
+    /// Where the reaction was registered, which is where the errors it runs
+    /// into are reported.
+    pub token: Token,
+}
+
+pub enum ReactionKind {
+    /// Registered by `then` and `catch`. A missing handler passes the
+    /// outcome on unchanged.
+    Then {
+        on_fulfilled: Option<Rc<dyn Callable>>,
+        on_rejected: Option<Rc<dyn Callable>>,
+    },
+    /// Settles the promise the same way, for `race` and for promises that
+    /// were resolved with another promise.
+    Settle,
+    /// One of the promises passed to `Promise.all`.
+    All {
+        index: usize,
+        results: Rc<RefCell<AllResults>>,
+    },
+    /// Resumes the body of an async function that is paused at `await`.
+    Await { body: Value },
+}
+
+pub struct AllResults {
+    values: Vec<Value>,
+    remaining: usize,
+}
+
+/// The global `Promise`, which `new` calls with an executor and which holds
+/// the static methods.
+pub fn constructor() -> Value {
+    let mut object = Object::new(ObjectKind::Constructor(Rc::new(NativeFunction::new(
+        "Promise", 1, construct,
+    ))));
+    for (name, min_arity, max_arity, function) in STATICS {
+        object.set(
+            name,
+            Value::Callable(Rc::new(NativeFunction::with_arity(
+                name, *min_arity, *max_arity, *function,
+            ))),
+        );
+    }
+    Value::Object(Rc::new(RefCell::new(object)))
+}
+
+fn pending() -> Value {
+    Value::Object(Rc::new(RefCell::new(Object::new(ObjectKind::Promise(
+        PromiseState::Pending {
+            reactions: Vec::new(),
+            resolved: false,
+        },
+    )))))
+}
+
+fn is_promise(value: &Value) -> bool {
+    match value {
+        Value::Object(object) => matches!(object.borrow().kind, ObjectKind::Promise(_)),
+        _ => false,
+    }
+}
+
+fn with_state<T>(promise: &Value, f: impl FnOnce(&mut PromiseState) -> T) -> T {
+    match promise {
+        Value::Object(object) => match &mut object.borrow_mut().kind {
+            ObjectKind::Promise(state) => f(state),
+            _ => panic!("Expected promise"),
+        },
+        _ => panic!("Expected promise"),
+    }
+}
+
+/// `value` itself if it is a promise, or else a promise fulfilled with it.
+fn promise_resolve(interpreter: &mut Interpreter, value: Value, token: &Token) -> Value {
+    if is_promise(&value) {
+        return value;
+    }
+    let promise = pending();
+    resolve(interpreter, &promise, value, token);
+    promise
+}
+
+/// Fulfills `promise` with `value`, or makes it follow `value` when that is
+/// a promise too. Promises that are already resolved are left alone.
+fn resolve(interpreter: &mut Interpreter, promise: &Value, value: Value, token: &Token) {
+    let is_open = with_state(promise, |state| {
+        matches!(
+            state,
+            PromiseState::Pending {
+                resolved: false,
+                ..
+            }
+        )
+    });
+    if !is_open {
+        return;
+    }
+
+    if !is_promise(&value) {
+        settle(interpreter, promise, Ok(value), token);
+        return;
+    }
+    if interpreter.is_equal(promise, &value) {
+        let error = RuntimeError::new(token.clone(), "Chaining cycle detected for promise.");
+        if let Unwind::Throw { value, token } = interpreter.throw_error(error) {
+            settle(interpreter, promise, Err(value), &token);
+        }
+        return;
+    }
+
+    with_state(promise, |state| {
+        if let PromiseState::Pending { resolved, .. } = state {
+            *resolved = true;
+        }
+    });
+    let reaction = Reaction {
+        kind: ReactionKind::Settle,
+        promise: promise.clone(),
+        token: token.clone(),
+    };
+    subscribe(interpreter, &value, reaction);
+}
+
+fn reject(interpreter: &mut Interpreter, promise: &Value, error: Value, token: &Token) {
+    let is_open = with_state(promise, |state| {
+        matches!(
+            state,
+            PromiseState::Pending {
+                resolved: false,
+                ..
+            }
+        )
+    });
+    if is_open {
+        settle(interpreter, promise, Err(error), token);
+    }
+}
+
+/// Settles `promise` for good and queues the reactions waiting on it.
+fn settle(
+    interpreter: &mut Interpreter,
+    promise: &Value,
+    outcome: Result<Value, Value>,
+    token: &Token,
+) {
+    let settled = match &outcome {
+        Ok(value) => PromiseState::Fulfilled(value.clone()),
+        Err(error) => PromiseState::Rejected(error.clone()),
+    };
+    let reactions = with_state(promise, |state| match std::mem::replace(state, settled) {
+        PromiseState::Pending { reactions, .. } => reactions,
+        _ => panic!("Expected pending promise"),
+    });
+
+    let mut event_loop = interpreter.event_loop.borrow_mut();
+    if let (Err(error), true, Value::Object(object)) = (&outcome, reactions.is_empty(), promise) {
+        event_loop.reject_unhandled(object.clone(), error.clone(), token.clone());
+    }
+    for reaction in reactions {
+        event_loop.queue_microtask(Job::Reaction {
+            reaction,
+            outcome: outcome.clone(),
+        });
+    }
+}
+
+/// Runs `reaction` once `promise` settles, or soon if it already has.
+fn subscribe(interpreter: &mut Interpreter, promise: &Value, reaction: Reaction) {
+    let outcome = with_state(promise, |state| match state {
+        PromiseState::Pending { reactions, .. } => {
+            reactions.push(reaction);
+            None
+        }
+        PromiseState::Fulfilled(value) => Some((reaction, Ok(value.clone()))),
+        PromiseState::Rejected(error) => Some((reaction, Err(error.clone()))),
+    });
+
+    if let Some((reaction, outcome)) = outcome {
+        let mut event_loop = interpreter.event_loop.borrow_mut();
+        if let Value::Object(object) = promise {
+            event_loop.handle(object);
+        }
+        event_loop.queue_microtask(Job::Reaction { reaction, outcome });
+    }
+}
+
+/// Splits an exception into the thrown value and where it was thrown.
+/// Other unwinds are handed back untouched.
+fn exception(interpreter: &Interpreter, unwind: Unwind) -> Result<(Value, Token), Unwind> {
+    match unwind {
+        Unwind::Error(error) => exception(interpreter, interpreter.throw_error(error)),
+        Unwind::Throw { value, token } => Ok((value, token)),
+        unwind => Err(unwind),
+    }
+}
+
+/// Settles `promise` with what a handler returned or threw.
+fn settle_with(
+    interpreter: &mut Interpreter,
+    promise: &Value,
+    result: Result<Value, Unwind>,
+    token: &Token,
+) -> Result<(), Unwind> {
+    match result {
+        Ok(value) => resolve(interpreter, promise, value, token),
+        Err(unwind) => {
+            let (error, token) = exception(interpreter, unwind)?;
+            reject(interpreter, promise, error, &token);
+        }
+    }
+    Ok(())
+}
+
+pub fn run_reaction(
+    interpreter: &mut Interpreter,
+    reaction: Reaction,
+    outcome: Result<Value, Value>,
+) -> Result<(), Unwind> {
+    let Reaction {
+        kind,
+        promise,
+        token,
+    } = reaction;
+
+    match kind {
+        ReactionKind::Then {
+            on_fulfilled,
+            on_rejected,
+        } => {
+            let (handler, argument) = match outcome {
+                Ok(value) => (on_fulfilled, Ok(value)),
+                Err(error) => (on_rejected, Err(error)),
+            };
+            match (handler, argument) {
+                (Some(handler), Ok(argument) | Err(argument)) => {
+                    let result = interpreter.call_back(&token, handler, vec![argument]);
+                    settle_with(interpreter, &promise, result, &token)
+                }
+                (None, Ok(value)) => {
+                    resolve(interpreter, &promise, value, &token);
+                    Ok(())
+                }
+                (None, Err(error)) => {
+                    reject(interpreter, &promise, error, &token);
+                    Ok(())
+                }
+            }
+        }
+        // The promise may already be resolved with the one that settled,
+        // so it is settled directly. Only the first of the promises in a
+        // race gets to.
+        ReactionKind::Settle => {
+            let is_pending = with_state(&promise, |state| {
+                matches!(state, PromiseState::Pending { .. })
+            });
+            if is_pending {
+                settle(interpreter, &promise, outcome, &token);
+            }
+            Ok(())
+        }
+        ReactionKind::All { index, results } => {
+            match outcome {
+                Ok(value) => {
+                    let values = {
+                        let mut results = results.borrow_mut();
+                        results.values[index] = value;
+                        results.remaining -= 1;
+                        match results.remaining {
+                            0 => Some(std::mem::take(&mut results.values)),
+                            _ => None,
+                        }
+                    };
+                    if let Some(values) = values {
+                        resolve(interpreter, &promise, Value::array(values), &token);
+                    }
+                }
+                Err(error) => reject(interpreter, &promise, error, &token),
+            }
+            Ok(())
+        }
+        ReactionKind::Await { body } => {
+            let resume = match outcome {
+                Ok(value) => Resume::Next(value),
+                Err(error) => Resume::Throw(error),
+            };
+            step(interpreter, &token, &body, &promise, resume)
+        }
+    }
+}
+
+/// Starts running the body of an async function, which is paused and
+/// resumed at each `await` the way a generator is at `yield`, and returns
+/// the promise of its result.
+pub fn run_async(interpreter: &mut Interpreter, body: Value) -> Result<Value, Unwind> {
+    let token = interpreter.call_site();
+    let promise = pending();
+    step(
+        interpreter,
+        &token,
+        &body,
+        &promise,
+        Resume::Next(Value::Nil),
+    )?;
+    Ok(promise)
+}
+
+/// Runs an async function's body up to its next `await` or to its end.
+fn step(
+    interpreter: &mut Interpreter,
+    token: &Token,
+    body: &Value,
+    promise: &Value,
+    resume: Resume,
+) -> Result<(), Unwind> {
+    match generator::resume(token, body, resume) {
+        Ok(Step::Value(awaited)) => {
+            let awaited = promise_resolve(interpreter, awaited, token);
+            let reaction = Reaction {
+                kind: ReactionKind::Await { body: body.clone() },
+                promise: promise.clone(),
+                token: token.clone(),
+            };
+            subscribe(interpreter, &awaited, reaction);
+            Ok(())
+        }
+        Ok(Step::Done(value)) => {
+            resolve(interpreter, promise, value, token);
+            Ok(())
+        }
+        Err(unwind) => settle_with(interpreter, promise, Err(unwind), token),
+    }
+}
+
+fn argument(arguments: Vec<Value>) -> Value {
+    arguments.into_iter().next().unwrap_or(Value::Nil)
+}
+
+/// Handlers that are not functions are ignored, as if they were missing.
+fn handler(value: Option<Value>) -> Option<Rc<dyn Callable>> {
+    match value {
+        Some(Value::Callable(handler)) => Some(handler),
+        _ => None,
+    }
+}
+
+fn construct(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    let token = interpreter.call_site();
+    let executor = match &arguments[0] {
+        Value::Callable(executor) => executor.clone(),
+        value => {
+            return Err(RuntimeError::new(
+                token,
+                &format!("Promise resolver {} is not a function.", value),
+            )
+            .into())
+        }
+    };
+
+    let promise = pending();
+    let functions = vec![
+        Value::Callable(Rc::new(NativeMethod::new(
+            "resolve",
+            0,
+            Some(1),
+            promise.clone(),
+            resolve_function,
+        ))),
+        Value::Callable(Rc::new(NativeMethod::new(
+            "reject",
+            0,
+            Some(1),
+            promise.clone(),
+            reject_function,
+        ))),
+    ];
+    if let Err(unwind) = interpreter.call_back(&token, executor, functions) {
+        let (error, token) = exception(interpreter, unwind)?;
+        reject(interpreter, &promise, error, &token);
+    }
+    Ok(promise)
+}
+
+fn resolve_function(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let token = interpreter.call_site();
+    resolve(interpreter, receiver, argument(arguments), &token);
+    Ok(Value::Nil)
+}
+
+fn reject_function(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let token = interpreter.call_site();
+    reject(interpreter, receiver, argument(arguments), &token);
+    Ok(Value::Nil)
+}
+
+fn resolve_static(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    let token = interpreter.call_site();
+    Ok(promise_resolve(interpreter, argument(arguments), &token))
+}
+
+fn reject_static(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    let token = interpreter.call_site();
+    let promise = pending();
+    reject(interpreter, &promise, argument(arguments), &token);
+    Ok(promise)
+}
+
+fn all(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    let token = interpreter.call_site();
+    let values = interpreter.iterate(&token, &arguments[0])?;
+    let promise = pending();
+    if values.is_empty() {
+        resolve(interpreter, &promise, Value::array(Vec::new()), &token);
+        return Ok(promise);
+    }
+
+    let results = Rc::new(RefCell::new(AllResults {
+        values: vec![Value::Nil; values.len()],
+        remaining: values.len(),
+    }));
+    for (index, value) in values.into_iter().enumerate() {
+        let value = promise_resolve(interpreter, value, &token);
+        let reaction = Reaction {
+            kind: ReactionKind::All {
+                index,
+                results: results.clone(),
+            },
+            promise: promise.clone(),
+            token: token.clone(),
+        };
+        subscribe(interpreter, &value, reaction);
+    }
+    Ok(promise)
+}
+
+fn race(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    let token = interpreter.call_site();
+    let values = interpreter.iterate(&token, &arguments[0])?;
+    let promise = pending();
+    for value in values {
+        let value = promise_resolve(interpreter, value, &token);
+        let reaction = Reaction {
+            kind: ReactionKind::Settle,
+            promise: promise.clone(),
+            token: token.clone(),
+        };
+        subscribe(interpreter, &value, reaction);
+    }
+    Ok(promise)
+}
+
+fn then(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let mut arguments = arguments.into_iter();
+    let kind = ReactionKind::Then {
+        on_fulfilled: handler(arguments.next()),
+        on_rejected: handler(arguments.next()),
+    };
+    let promise = pending();
+    let reaction = Reaction {
+        kind,
+        promise: promise.clone(),
+        token: interpreter.call_site(),
+    };
+    subscribe(interpreter, receiver, reaction);
+    Ok(promise)
+}
+
+fn catch(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    then(interpreter, receiver, vec![Value::Nil, argument(arguments)])
+}
@@ -0,0 +1,55 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{parser::token::Token, RuntimeError};
+
+use super::{
+    object::{Object, ObjectKind},
+    value::Value,
+    Unwind,
+};
+
+/// Makes a record out of the object a `#{}` literal evaluated to. Its keys
+/// are sorted, so records with the same members print the same way.
+pub fn record(token: &Token, object: &Object) -> Result<Value, Unwind> {
+    let mut keys = object.keys().to_vec();
+    keys.sort();
+
+    let mut record = Object::new(ObjectKind::Record);
+    for key in keys {
+        let value = object.get(&key).unwrap_or(Value::Nil);
+        check_member(token, &value)?;
+        record.set(&key, value);
+    }
+    Ok(Value::Object(Rc::new(RefCell::new(record))))
+}
+
+/// Makes a tuple out of the elements a `#[]` literal evaluated to.
+pub fn tuple(token: &Token, elements: Vec<Value>) -> Result<Value, Unwind> {
+    for element in &elements {
+        check_member(token, element)?;
+    }
+    Ok(Value::Object(Rc::new(RefCell::new(Object::new(
+        ObjectKind::Tuple(elements),
+    )))))
+}
+
+/// Records and tuples are deeply immutable, so they may only hold values
+/// that cannot change.
+fn check_member(token: &Token, value: &Value) -> Result<(), Unwind> {
+    let allowed = match value {
+        Value::Callable(_) => false,
+        Value::Object(object) => matches!(
+            object.borrow().kind,
+            ObjectKind::Record | ObjectKind::Tuple(_)
+        ),
+        _ => true,
+    };
+    match allowed {
+        true => Ok(()),
+        false => Err(RuntimeError::new(
+            token.clone(),
+            "Records and tuples may only contain primitives, records and tuples.",
+        )
+        .into()),
+    }
+}
@@ -0,0 +1,936 @@
+use std::fmt;
+
+/// How many steps one search may take. Patterns like `/(a+)+b/` backtrack
+/// exponentially, and this stops them with an error rather than a hang.
+pub const MAX_STEPS: usize = 10_000_000;
+
+/// A compiled regular expression, such as `/ab+c/gi`. Patterns are matched
+/// by backtracking, so anything JavaScript allows short of lookbehind and
+/// Unicode property escapes is supported.
+#[derive(Debug)]
+pub struct Regex {
+    pub source: String,
+    pub flags: String,
+    pub global: bool,
+    pub ignore_case: bool,
+    pub multiline: bool,
+    pub dot_all: bool,
+    pub sticky: bool,
+    program: Vec<Inst>,
+    /// How many registers the program keeps group starts and repeat counts in.
+    registers: usize,
+    /// How many capturing groups there are, not counting the whole match.
+    groups: usize,
+    names: Vec<(String, usize)>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class {
+        items: Vec<ClassItem>,
+        negated: bool,
+    },
+    Start,
+    End,
+    WordBoundary {
+        negated: bool,
+    },
+    /// `(...)`, whose match is captured unless it is `(?:...)`.
+    Group {
+        node: Box<Node>,
+        index: Option<usize>,
+    },
+    Lookahead {
+        node: Box<Node>,
+        negated: bool,
+    },
+    Backreference(usize),
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+#[derive(Debug)]
+enum ClassItem {
+    Range(char, char),
+    Digit { negated: bool },
+    Word { negated: bool },
+    Space { negated: bool },
+}
+
+/// Where the whole match and then each group matched, as ranges of
+/// character positions.
+pub type Captures = Vec<Option<(usize, usize)>>;
+
+impl Regex {
+    pub fn new(source: &str, flags: &str) -> Result<Regex, String> {
+        for (index, flag) in flags.char_indices() {
+            if !"gimsy".contains(flag) || flags[..index].contains(flag) {
+                return Err(format!("Invalid regular expression flags '{}'.", flags));
+            }
+        }
+
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            current: 0,
+            groups: 0,
+            names: Vec::new(),
+            backreference: 0,
+        };
+        let node = parser
+            .pattern()
+            .map_err(|message| format!("Invalid regular expression: /{}/: {}.", source, message))?;
+        let mut compiler = Compiler {
+            program: Vec::new(),
+            registers: 0,
+        };
+        compiler.compile(node);
+        compiler.program.push(Inst::Match);
+
+        Ok(Regex {
+            source: source.to_string(),
+            flags: flags.to_string(),
+            global: flags.contains('g'),
+            ignore_case: flags.contains('i'),
+            multiline: flags.contains('m'),
+            dot_all: flags.contains('s'),
+            sticky: flags.contains('y'),
+            program: compiler.program,
+            registers: compiler.registers,
+            groups: parser.groups,
+            names: parser.names,
+        })
+    }
+
+    /// The named groups, each with the index of its capture.
+    pub fn names(&self) -> &[(String, usize)] {
+        &self.names
+    }
+
+    /// Finds the first match that starts at or after `start`, or only at
+    /// `start` when the regex is sticky. A search that takes more than
+    /// `MAX_STEPS` steps gives up with an error instead of running on.
+    pub fn find_at(&self, input: &[char], start: usize) -> Result<Option<Captures>, String> {
+        if start > input.len() {
+            return Ok(None);
+        }
+        let last = if self.sticky { start } else { input.len() };
+        let mut matcher = Matcher {
+            regex: self,
+            input,
+            captures: vec![None; self.groups + 1],
+            registers: vec![0; self.registers],
+            stack: Vec::new(),
+            steps: 0,
+        };
+        for at in start..=last {
+            if let Some(end) = matcher.run(0, at)? {
+                let mut captures = matcher.captures;
+                captures[0] = Some((at, end));
+                return Ok(Some(captures));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source.is_empty() {
+            true => write!(f, "/(?:)/{}", self.flags),
+            false => write!(f, "/{}/{}", self.source, self.flags),
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    current: usize,
+    groups: usize,
+    names: Vec<(String, usize)>,
+    /// The highest group a `\1` style backreference refers to.
+    backreference: usize,
+}
+
+impl Parser {
+    fn pattern(&mut self) -> Result<Node, String> {
+        let node = self.alternation()?;
+        if self.current < self.chars.len() {
+            return Err(String::from("Unmatched ')'"));
+        }
+        if self.backreference > self.groups {
+            return Err(String::from("Invalid backreference"));
+        }
+        Ok(node)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.current += 1;
+        Some(c)
+    }
+
+    fn match_char(&mut self, expected: char) -> bool {
+        if self.peek() != Some(expected) {
+            return false;
+        }
+        self.current += 1;
+        true
+    }
+
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.match_char('|') {
+            alternatives.push(self.sequence()?);
+        }
+        Ok(match alternatives.len() {
+            1 => alternatives.remove(0),
+            _ => Node::Alternate(alternatives),
+        })
+    }
+
+    fn sequence(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+        while !matches!(self.peek(), None | Some('|') | Some(')')) {
+            let atom = self.atom()?;
+            nodes.push(self.quantifier(atom)?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.remove(0),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn quantifier(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => match self.bounds() {
+                Some(bounds) => bounds,
+                None => return Ok(atom),
+            },
+            _ => return Ok(atom),
+        };
+        if !matches!(self.peek(), Some('{')) {
+            self.current += 1;
+        } else {
+            while self.advance() != Some('}') {}
+        }
+
+        if matches!(
+            atom,
+            Node::Start | Node::End | Node::WordBoundary { .. } | Node::Lookahead { .. }
+        ) {
+            return Err(String::from("Nothing to repeat"));
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(String::from("numbers out of order in {} quantifier"));
+        }
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+            greedy: !self.match_char('?'),
+        })
+    }
+
+    /// Reads `{n}`, `{n,}` or `{n,m}` without consuming it. Anything else
+    /// starting with `{` is a literal brace.
+    fn bounds(&self) -> Option<(usize, Option<usize>)> {
+        let rest: String = self.chars[self.current + 1..].iter().collect();
+        let (inside, _) = rest.split_once('}')?;
+        let number = |text: &str| -> Option<usize> {
+            match !text.is_empty() && text.bytes().all(|c| c.is_ascii_digit()) {
+                true => text.parse().ok(),
+                false => None,
+            }
+        };
+        match inside.split_once(',') {
+            None => number(inside).map(|count| (count, Some(count))),
+            Some((min, "")) => Some((number(min)?, None)),
+            Some((min, max)) => Some((number(min)?, Some(number(max)?))),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        match self.advance() {
+            Some('.') => Ok(Node::Any),
+            Some('^') => Ok(Node::Start),
+            Some('$') => Ok(Node::End),
+            Some('(') => self.group(),
+            Some('[') => self.class(),
+            Some('\\') => self.escape(),
+            Some('*' | '+' | '?') => Err(String::from("Nothing to repeat")),
+            Some(c) => Ok(Node::Char(c)),
+            None => Ok(Node::Empty),
+        }
+    }
+
+    fn group(&mut self) -> Result<Node, String> {
+        let node = if self.match_char('?') {
+            match self.advance() {
+                Some(':') => Node::Group {
+                    node: Box::new(self.alternation()?),
+                    index: None,
+                },
+                Some(c @ ('=' | '!')) => Node::Lookahead {
+                    node: Box::new(self.alternation()?),
+                    negated: c == '!',
+                },
+                Some('<') if matches!(self.peek(), Some('=' | '!')) => {
+                    return Err(String::from("Lookbehind is not supported"))
+                }
+                Some('<') => {
+                    let mut name = String::new();
+                    while let Some(c) = self.advance() {
+                        if c == '>' {
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    let valid = name.chars().enumerate().all(|(index, c)| {
+                        c == '_' || c.is_alphabetic() || (index > 0 && c.is_alphanumeric())
+                    });
+                    if name.is_empty() || !valid {
+                        return Err(String::from("Invalid capture group name"));
+                    }
+                    if self.names.iter().any(|(existing, _)| *existing == name) {
+                        return Err(String::from("Duplicate capture group name"));
+                    }
+                    self.groups += 1;
+                    self.names.push((name, self.groups));
+                    self.capture(self.groups)?
+                }
+                _ => return Err(String::from("Invalid group")),
+            }
+        } else {
+            self.groups += 1;
+            self.capture(self.groups)?
+        };
+
+        if !self.match_char(')') {
+            return Err(String::from("Unterminated group"));
+        }
+        Ok(node)
+    }
+
+    fn capture(&mut self, index: usize) -> Result<Node, String> {
+        Ok(Node::Group {
+            node: Box::new(self.alternation()?),
+            index: Some(index),
+        })
+    }
+
+    fn escape(&mut self) -> Result<Node, String> {
+        let c = self
+            .advance()
+            .ok_or_else(|| String::from("\\ at end of pattern"))?;
+        Ok(match c {
+            'b' => Node::WordBoundary { negated: false },
+            'B' => Node::WordBoundary { negated: true },
+            'd' | 'D' | 'w' | 'W' | 's' | 'S' => Node::Class {
+                items: vec![shorthand(c)],
+                negated: false,
+            },
+            '1'..='9' => {
+                let mut index = c.to_digit(10).unwrap() as usize;
+                while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+                    index = index * 10 + digit as usize;
+                    self.current += 1;
+                }
+                self.backreference = self.backreference.max(index);
+                Node::Backreference(index)
+            }
+            _ => Node::Char(self.character_escape(c)?),
+        })
+    }
+
+    fn character_escape(&mut self, c: char) -> Result<char, String> {
+        Ok(match c {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\u{b}',
+            'f' => '\u{c}',
+            '0' => '\0',
+            'x' => self.hex(2)?,
+            'u' => self.hex(4)?,
+            c => c,
+        })
+    }
+
+    fn hex(&mut self, digits: usize) -> Result<char, String> {
+        let text: String = self.chars.iter().skip(self.current).take(digits).collect();
+        let c = match text.len() == digits {
+            true => u32::from_str_radix(&text, 16).ok().and_then(char::from_u32),
+            false => None,
+        };
+        self.current += digits;
+        c.ok_or_else(|| String::from("Invalid escape"))
+    }
+
+    fn class(&mut self) -> Result<Node, String> {
+        let negated = self.match_char('^');
+        let mut items = Vec::new();
+        while !self.match_char(']') {
+            let item = self.class_atom()?;
+            let ClassItem::Range(start, _) = item else {
+                items.push(item);
+                continue;
+            };
+            let is_range = self.peek() == Some('-')
+                && !matches!(self.chars.get(self.current + 1), None | Some(']'));
+            if !is_range {
+                items.push(item);
+                continue;
+            }
+
+            self.current += 1;
+            let ClassItem::Range(end, _) = self.class_atom()? else {
+                return Err(String::from("Invalid character class"));
+            };
+            if end < start {
+                return Err(String::from("Range out of order in character class"));
+            }
+            items.push(ClassItem::Range(start, end));
+        }
+        Ok(Node::Class { items, negated })
+    }
+
+    fn class_atom(&mut self) -> Result<ClassItem, String> {
+        let c = match self.advance() {
+            Some('\\') => match self.advance() {
+                Some(c @ ('d' | 'D' | 'w' | 'W' | 's' | 'S')) => return Ok(shorthand(c)),
+                Some('b') => '\u{8}',
+                Some(c) => self.character_escape(c)?,
+                None => return Err(String::from("\\ at end of pattern")),
+            },
+            Some(c) => c,
+            None => return Err(String::from("Unterminated character class")),
+        };
+        Ok(ClassItem::Range(c, c))
+    }
+}
+
+fn shorthand(c: char) -> ClassItem {
+    let negated = c.is_ascii_uppercase();
+    match c.to_ascii_lowercase() {
+        'd' => ClassItem::Digit { negated },
+        'w' => ClassItem::Word { negated },
+        _ => ClassItem::Space { negated },
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+/// One step of a compiled pattern. Matching runs these against a stack of
+/// places to backtrack to instead of recursing, so a long input can't
+/// overflow the native stack however often a group repeats.
+#[derive(Debug)]
+enum Inst {
+    /// A `Char`, `Any` or `Class` node, which matches one character.
+    Single(Node),
+    Start,
+    End,
+    WordBoundary {
+        negated: bool,
+    },
+    Backreference(usize),
+    /// Goes on at the first target, and at the second if that fails.
+    Split(usize, usize),
+    Jump(usize),
+    /// Records where a capturing group starts in a register.
+    GroupStart {
+        register: usize,
+    },
+    GroupEnd {
+        index: usize,
+        register: usize,
+    },
+    /// Matches the instructions after it up to their `Match` on their own,
+    /// then goes on at `next`.
+    Lookahead {
+        negated: bool,
+        next: usize,
+    },
+    /// Repeats a single character node without going round a loop.
+    RepeatSingle {
+        node: Node,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+    /// Starts the count of iterations kept in `register` at zero.
+    RepeatStart {
+        register: usize,
+    },
+    /// Decides whether to go round again or leave for `exit`.
+    RepeatLoop {
+        register: usize,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+        exit: usize,
+    },
+    /// Records where an iteration starts in `register + 1`.
+    RepeatBody {
+        register: usize,
+    },
+    /// Counts an iteration and goes back to its `RepeatLoop`.
+    RepeatEnd {
+        register: usize,
+        min: usize,
+        repeat: usize,
+    },
+    Match,
+}
+
+/// Turns a parsed pattern into the instructions a `Matcher` runs.
+struct Compiler {
+    program: Vec<Inst>,
+    registers: usize,
+}
+
+impl Compiler {
+    fn emit(&mut self, inst: Inst) -> usize {
+        self.program.push(inst);
+        self.program.len() - 1
+    }
+
+    fn register(&mut self, count: usize) -> usize {
+        self.registers += count;
+        self.registers - count
+    }
+
+    fn compile(&mut self, node: Node) {
+        match node {
+            Node::Empty => {}
+            Node::Char(_) | Node::Any | Node::Class { .. } => {
+                self.emit(Inst::Single(node));
+            }
+            Node::Start => {
+                self.emit(Inst::Start);
+            }
+            Node::End => {
+                self.emit(Inst::End);
+            }
+            Node::WordBoundary { negated } => {
+                self.emit(Inst::WordBoundary { negated });
+            }
+            Node::Backreference(index) => {
+                self.emit(Inst::Backreference(index));
+            }
+            Node::Group { node, index: None } => self.compile(*node),
+            Node::Group {
+                node,
+                index: Some(index),
+            } => {
+                let register = self.register(1);
+                self.emit(Inst::GroupStart { register });
+                self.compile(*node);
+                self.emit(Inst::GroupEnd { index, register });
+            }
+            Node::Lookahead { node, negated } => {
+                let lookahead = self.emit(Inst::Match);
+                self.compile(*node);
+                self.emit(Inst::Match);
+                let next = self.program.len();
+                self.program[lookahead] = Inst::Lookahead { negated, next };
+            }
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.compile(node);
+                }
+            }
+            Node::Alternate(alternatives) => {
+                let count = alternatives.len();
+                let mut jumps = Vec::new();
+                for (index, alternative) in alternatives.into_iter().enumerate() {
+                    if index + 1 == count {
+                        self.compile(alternative);
+                        break;
+                    }
+                    let split = self.emit(Inst::Match);
+                    self.compile(alternative);
+                    jumps.push(self.emit(Inst::Match));
+                    self.program[split] = Inst::Split(split + 1, self.program.len());
+                }
+                let end = self.program.len();
+                for jump in jumps {
+                    self.program[jump] = Inst::Jump(end);
+                }
+            }
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => match *node {
+                node @ (Node::Char(_) | Node::Any | Node::Class { .. }) => {
+                    self.emit(Inst::RepeatSingle {
+                        node,
+                        min,
+                        max,
+                        greedy,
+                    });
+                }
+                node => {
+                    let register = self.register(2);
+                    self.emit(Inst::RepeatStart { register });
+                    let repeat = self.emit(Inst::Match);
+                    self.emit(Inst::RepeatBody { register });
+                    self.compile(node);
+                    self.emit(Inst::RepeatEnd {
+                        register,
+                        min,
+                        repeat,
+                    });
+                    self.program[repeat] = Inst::RepeatLoop {
+                        register,
+                        min,
+                        max,
+                        greedy,
+                        exit: self.program.len(),
+                    };
+                }
+            },
+        }
+    }
+}
+
+/// What to do when the match fails from here: try another way, or undo a
+/// change to the registers or captures made since that way was pushed.
+enum Backtrack {
+    Resume {
+        pc: usize,
+        at: usize,
+    },
+    /// The positions from `from` to `to` that a single character repeat can
+    /// still end at, in the order its greediness tries them.
+    Positions {
+        pc: usize,
+        from: usize,
+        to: usize,
+        greedy: bool,
+    },
+    Register {
+        index: usize,
+        value: usize,
+    },
+    Capture {
+        index: usize,
+        value: Option<(usize, usize)>,
+    },
+}
+
+struct Matcher<'a> {
+    regex: &'a Regex,
+    input: &'a [char],
+    captures: Captures,
+    registers: Vec<usize>,
+    stack: Vec<Backtrack>,
+    steps: usize,
+}
+
+impl Matcher<'_> {
+    /// Runs the program from `pc` at position `at`, giving where the match
+    /// ended. What it captured stays in `captures`, with the changes on the
+    /// stack so that an outer match can still undo them.
+    fn run(&mut self, mut pc: usize, mut at: usize) -> Result<Option<usize>, String> {
+        let program = &self.regex.program;
+        let base = self.stack.len();
+        loop {
+            self.steps += 1;
+            if self.steps > MAX_STEPS {
+                return Err(format!(
+                    "Regular expression /{}/ backtracked too much.",
+                    self.regex.source
+                ));
+            }
+
+            let matched = match &program[pc] {
+                Inst::Single(node) => {
+                    let matched = self.input.get(at).is_some_and(|c| self.single(node, *c));
+                    at += 1;
+                    matched
+                }
+                Inst::Start => {
+                    at == 0 || (self.regex.multiline && is_line_terminator(self.input[at - 1]))
+                }
+                Inst::End => {
+                    at == self.input.len()
+                        || (self.regex.multiline && is_line_terminator(self.input[at]))
+                }
+                Inst::WordBoundary { negated } => {
+                    let before = at > 0 && is_word(self.input[at - 1]);
+                    let after = self.input.get(at).is_some_and(|c| is_word(*c));
+                    (before != after) != *negated
+                }
+                Inst::Backreference(index) => match self.captures[*index] {
+                    None => true,
+                    Some((start, end)) => {
+                        let length = end - start;
+                        let same = at + length <= self.input.len()
+                            && (0..length)
+                                .all(|i| self.same_char(self.input[at + i], self.input[start + i]));
+                        at += length;
+                        same
+                    }
+                },
+                Inst::Split(first, second) => {
+                    self.stack.push(Backtrack::Resume { pc: *second, at });
+                    pc = *first;
+                    continue;
+                }
+                Inst::Jump(target) => {
+                    pc = *target;
+                    continue;
+                }
+                Inst::GroupStart { register } => {
+                    self.set_register(*register, at);
+                    true
+                }
+                Inst::GroupEnd { index, register } => {
+                    let start = self.registers[*register];
+                    let value = self.captures[*index].replace((start, at));
+                    self.stack.push(Backtrack::Capture {
+                        index: *index,
+                        value,
+                    });
+                    true
+                }
+                Inst::Lookahead { negated, next } => {
+                    let before = self.stack.len();
+                    let found = self.run(pc + 1, at)?.is_some();
+                    // Groups inside a lookahead keep what they captured, but
+                    // a negated one has nothing to keep.
+                    if found && *negated {
+                        self.undo(before);
+                    }
+                    pc = *next;
+                    if found != *negated {
+                        continue;
+                    }
+                    false
+                }
+                Inst::RepeatSingle {
+                    node,
+                    min,
+                    max,
+                    greedy,
+                } => {
+                    let limit =
+                        max.map_or(self.input.len(), |max| (at + max).min(self.input.len()));
+                    let mut end = at;
+                    while end < limit && self.single(node, self.input[end]) {
+                        end += 1;
+                    }
+                    let matched = end >= at + min;
+                    if matched {
+                        let (from, to) = (at + min, end);
+                        at = if *greedy { to } else { from };
+                        if from < to {
+                            self.stack.push(match greedy {
+                                true => Backtrack::Positions {
+                                    pc: pc + 1,
+                                    from,
+                                    to: to - 1,
+                                    greedy: true,
+                                },
+                                false => Backtrack::Positions {
+                                    pc: pc + 1,
+                                    from: from + 1,
+                                    to,
+                                    greedy: false,
+                                },
+                            });
+                        }
+                    }
+                    matched
+                }
+                Inst::RepeatStart { register } => {
+                    self.set_register(*register, 0);
+                    true
+                }
+                Inst::RepeatLoop {
+                    register,
+                    min,
+                    max,
+                    greedy,
+                    exit,
+                } => {
+                    let count = self.registers[*register];
+                    let again = max.is_none_or(|max| count < max);
+                    match (again, count >= *min) {
+                        (true, false) => true,
+                        (false, done) => {
+                            pc = *exit;
+                            if done {
+                                continue;
+                            }
+                            false
+                        }
+                        (true, true) => {
+                            let (first, second) = match greedy {
+                                true => (pc + 1, *exit),
+                                false => (*exit, pc + 1),
+                            };
+                            self.stack.push(Backtrack::Resume { pc: second, at });
+                            pc = first;
+                            continue;
+                        }
+                    }
+                }
+                Inst::RepeatBody { register } => {
+                    self.set_register(register + 1, at);
+                    true
+                }
+                Inst::RepeatEnd {
+                    register,
+                    min,
+                    repeat,
+                } => {
+                    // Once the minimum is met, an empty iteration would loop
+                    // forever.
+                    let count = self.registers[*register];
+                    if at == self.registers[register + 1] && count >= *min {
+                        false
+                    } else {
+                        self.set_register(*register, count + 1);
+                        pc = *repeat;
+                        continue;
+                    }
+                }
+                Inst::Match => {
+                    // Other ways to match are dropped, but the changes stay
+                    // undoable.
+                    let changes: Vec<Backtrack> = self
+                        .stack
+                        .drain(base..)
+                        .filter(|entry| {
+                            matches!(
+                                entry,
+                                Backtrack::Register { .. } | Backtrack::Capture { .. }
+                            )
+                        })
+                        .collect();
+                    self.stack.extend(changes);
+                    return Ok(Some(at));
+                }
+            };
+
+            if matched {
+                pc += 1;
+                continue;
+            }
+            loop {
+                if self.stack.len() == base {
+                    return Ok(None);
+                }
+                match self.stack.pop().unwrap() {
+                    Backtrack::Resume { pc: to, at: from } => {
+                        (pc, at) = (to, from);
+                        break;
+                    }
+                    Backtrack::Positions {
+                        pc: to,
+                        from,
+                        to: last,
+                        greedy,
+                    } => {
+                        pc = to;
+                        at = if greedy { last } else { from };
+                        if from < last {
+                            self.stack.push(match greedy {
+                                true => Backtrack::Positions {
+                                    pc,
+                                    from,
+                                    to: last - 1,
+                                    greedy,
+                                },
+                                false => Backtrack::Positions {
+                                    pc,
+                                    from: from + 1,
+                                    to: last,
+                                    greedy,
+                                },
+                            });
+                        }
+                        break;
+                    }
+                    Backtrack::Register { index, value } => self.registers[index] = value,
+                    Backtrack::Capture { index, value } => self.captures[index] = value,
+                }
+            }
+        }
+    }
+
+    fn set_register(&mut self, index: usize, value: usize) {
+        let value = std::mem::replace(&mut self.registers[index], value);
+        self.stack.push(Backtrack::Register { index, value });
+    }
+
+    /// Undoes every change pushed since the stack was `length` long.
+    fn undo(&mut self, length: usize) {
+        while self.stack.len() > length {
+            match self.stack.pop().unwrap() {
+                Backtrack::Register { index, value } => self.registers[index] = value,
+                Backtrack::Capture { index, value } => self.captures[index] = value,
+                Backtrack::Resume { .. } | Backtrack::Positions { .. } => {}
+            }
+        }
+    }
+
+    /// Whether a node that matches one character matches `c`.
+    fn single(&self, node: &Node, c: char) -> bool {
+        match node {
+            Node::Char(expected) => self.same_char(c, *expected),
+            Node::Any => self.regex.dot_all || !is_line_terminator(c),
+            Node::Class { items, negated } => {
+                let found = match self.regex.ignore_case {
+                    true => {
+                        in_class(items, c)
+                            || c.to_lowercase().any(|c| in_class(items, c))
+                            || c.to_uppercase().any(|c| in_class(items, c))
+                    }
+                    false => in_class(items, c),
+                };
+                found != *negated
+            }
+            _ => false,
+        }
+    }
+
+    fn same_char(&self, left: char, right: char) -> bool {
+        left == right || (self.regex.ignore_case && left.to_lowercase().eq(right.to_lowercase()))
+    }
+}
+
+fn in_class(items: &[ClassItem], c: char) -> bool {
+    items.iter().any(|item| match item {
+        ClassItem::Range(start, end) => (*start..=*end).contains(&c),
+        ClassItem::Digit { negated } => c.is_ascii_digit() != *negated,
+        ClassItem::Word { negated } => is_word(c) != *negated,
+        ClassItem::Space { negated } => (c.is_whitespace() || c == '\u{feff}') != *negated,
+    })
+}
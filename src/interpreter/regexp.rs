@@ -0,0 +1,208 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{ErrorKind, RuntimeError};
+
+use super::{
+    callable::{NativeFunction, NativeMethodFn},
+    object::{Object, ObjectKind},
+    regex::{Captures, Regex},
+    value::Value,
+    Interpreter, Unwind,
+};
+
+pub const METHODS: &[(&str, usize, Option<usize>, NativeMethodFn)] =
+    &[("exec", 1, Some(1), exec), ("test", 1, Some(1), test)];
+
+/// The read-only properties of a `RegExp`, which come from its pattern.
+pub fn property(regex: &Regex, key: &str) -> Option<Value> {
+    Some(match key {
+        "source" => Value::from(regex.source.as_str()),
+        "flags" => Value::from(regex.flags.as_str()),
+        "global" => Value::Bool(regex.global),
+        "ignoreCase" => Value::Bool(regex.ignore_case),
+        "multiline" => Value::Bool(regex.multiline),
+        "dotAll" => Value::Bool(regex.dot_all),
+        "sticky" => Value::Bool(regex.sticky),
+        _ => return None,
+    })
+}
+
+/// Makes a `RegExp` object. Each one has its own `lastIndex`, even when it
+/// shares `regex` with others made by the same literal.
+pub fn create(regex: Rc<Regex>) -> Value {
+    let mut object = Object::new(ObjectKind::RegExp(regex));
+    object.set("lastIndex", Value::from(0.0));
+    Value::Object(Rc::new(RefCell::new(object)))
+}
+
+/// Creates the global `RegExp`, which `new` calls.
+pub fn constructor() -> Value {
+    let constructor = NativeFunction::with_arity("RegExp", 1, Some(2), construct);
+    Value::Object(Rc::new(RefCell::new(Object::new(ObjectKind::Constructor(
+        Rc::new(constructor),
+    )))))
+}
+
+/// `new RegExp(pattern, flags)`. The pattern may be a string or another
+/// `RegExp`, whose flags are kept unless new ones are given.
+fn construct(interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, Unwind> {
+    let mut arguments = arguments.into_iter();
+    let pattern = arguments.next().unwrap_or(Value::Nil);
+    let (source, flags) = match regex(&pattern) {
+        Some(regex) => (regex.source.clone(), regex.flags.clone()),
+        None => (pattern.to_string(), String::new()),
+    };
+    let flags = match arguments.next() {
+        None | Some(Value::Nil) => flags,
+        Some(flags) => flags.to_string(),
+    };
+    Ok(create(compile(interpreter, &source, &flags)?))
+}
+
+/// Compiles a pattern that was built at runtime. A bad pattern is a
+/// `SyntaxError`, as it is in a literal.
+pub fn compile(
+    interpreter: &Interpreter,
+    source: &str,
+    flags: &str,
+) -> Result<Rc<Regex>, RuntimeError> {
+    Regex::new(source, flags).map(Rc::new).map_err(|message| {
+        RuntimeError::with_kind(interpreter.call_site(), ErrorKind::SyntaxError, &message)
+    })
+}
+
+/// The compiled pattern of a `RegExp` object.
+pub fn regex(value: &Value) -> Option<Rc<Regex>> {
+    match value {
+        Value::Object(object) => match &object.borrow().kind {
+            ObjectKind::RegExp(regex) => Some(regex.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Finds the first match at or after `start`. A search that backtracks too
+/// much is a `RangeError`.
+pub fn find_at(
+    interpreter: &Interpreter,
+    regex: &Regex,
+    input: &[char],
+    start: usize,
+) -> Result<Option<Captures>, RuntimeError> {
+    regex.find_at(input, start).map_err(|message| {
+        RuntimeError::with_kind(interpreter.call_site(), ErrorKind::RangeError, &message)
+    })
+}
+
+/// Finds the next match of the `RegExp` `receiver`. Global and sticky ones
+/// search from their `lastIndex` and move it past the match, or back to 0
+/// when there is none. Others always search from the start.
+pub fn find(
+    interpreter: &Interpreter,
+    receiver: &Value,
+    regex: &Regex,
+    input: &[char],
+) -> Result<Option<Captures>, RuntimeError> {
+    let Value::Object(object) = receiver else {
+        return find_at(interpreter, regex, input, 0);
+    };
+    if !regex.global && !regex.sticky {
+        return find_at(interpreter, regex, input, 0);
+    }
+
+    let start = match object.borrow().get("lastIndex") {
+        Some(Value::Number(index)) if index > 0.0 => index as usize,
+        _ => 0,
+    };
+    let captures = find_at(interpreter, regex, input, start)?;
+    let last_index = captures.as_ref().map_or(0, end);
+    object
+        .borrow_mut()
+        .set("lastIndex", Value::from(last_index as f64));
+    Ok(captures)
+}
+
+/// Every match in `input` from the start, as a global regex finds them.
+/// An empty match moves the search on by a character so it always ends.
+pub fn find_all(
+    interpreter: &Interpreter,
+    regex: &Regex,
+    input: &[char],
+) -> Result<Vec<Captures>, RuntimeError> {
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while let Some(captures) = find_at(interpreter, regex, input, start)? {
+        start = match captures[0] {
+            Some((from, to)) if from == to => to + 1,
+            _ => end(&captures),
+        };
+        matches.push(captures);
+    }
+    Ok(matches)
+}
+
+pub fn start(captures: &Captures) -> usize {
+    captures[0].map_or(0, |(start, _)| start)
+}
+
+pub fn end(captures: &Captures) -> usize {
+    captures[0].map_or(0, |(_, end)| end)
+}
+
+/// The text a group matched, or nil if it did not take part in the match.
+pub fn group(input: &[char], range: Option<(usize, usize)>) -> Value {
+    match range {
+        Some((start, end)) => Value::from(input[start..end].iter().collect::<String>()),
+        None => Value::Nil,
+    }
+}
+
+/// The array `exec` returns: the match followed by each group, with the
+/// match's `index`, the `input` and any named `groups` as properties.
+pub fn match_array(regex: &Regex, input: &[char], captures: &Captures) -> Value {
+    let array = Value::array(captures.iter().map(|range| group(input, *range)).collect());
+    if let Value::Object(object) = &array {
+        let groups = match regex.names().is_empty() {
+            true => Value::Nil,
+            false => {
+                let mut groups = Object::new(ObjectKind::Ordinary);
+                for (name, index) in regex.names() {
+                    groups.set(name, group(input, captures[*index]));
+                }
+                Value::Object(Rc::new(RefCell::new(groups)))
+            }
+        };
+
+        let mut object = object.borrow_mut();
+        object.set("index", Value::from(start(captures) as f64));
+        object.set("input", Value::from(input.iter().collect::<String>()));
+        object.set("groups", groups);
+    }
+    array
+}
+
+fn exec(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let regex = regex(receiver).expect("Expected RegExp receiver");
+    let input: Vec<char> = arguments[0].to_string().chars().collect();
+    Ok(match find(interpreter, receiver, &regex, &input)? {
+        Some(captures) => match_array(&regex, &input, &captures),
+        None => Value::Nil,
+    })
+}
+
+fn test(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let regex = regex(receiver).expect("Expected RegExp receiver");
+    let input: Vec<char> = arguments[0].to_string().chars().collect();
+    Ok(Value::Bool(
+        find(interpreter, receiver, &regex, &input)?.is_some(),
+    ))
+}
@@ -0,0 +1,263 @@
+use std::rc::Rc;
+
+use crate::RuntimeError;
+
+use super::{
+    callable::NativeMethodFn,
+    iterator,
+    regex::{Captures, Regex},
+    regexp,
+    symbol::ITERATOR,
+    value::Value,
+    Interpreter, Unwind,
+};
+
+pub const METHODS: &[(&str, usize, Option<usize>, NativeMethodFn)] = &[
+    ("match", 1, Some(1), find),
+    ("replace", 2, Some(2), replace),
+    ("split", 0, Some(2), split),
+    (ITERATOR, 0, Some(0), iterator::iterator),
+];
+
+/// The `RegExp` an argument stands for. Strings are compiled as patterns
+/// into a new one.
+fn pattern(interpreter: &Interpreter, value: Value) -> Result<(Value, Rc<Regex>), Unwind> {
+    if let Some(regex) = regexp::regex(&value) {
+        return Ok((value, regex));
+    }
+    let regex = regexp::compile(interpreter, &value.to_string(), "")?;
+    Ok((regexp::create(regex.clone()), regex))
+}
+
+/// `string.match(pattern)`, which is what `exec` returns, or an array of
+/// every match when the pattern is global.
+fn find(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let input: Vec<char> = receiver.to_string().chars().collect();
+    let (object, regex) = pattern(interpreter, arguments.into_iter().next().unwrap())?;
+    if !regex.global {
+        return Ok(match regexp::find(interpreter, &object, &regex, &input)? {
+            Some(captures) => regexp::match_array(&regex, &input, &captures),
+            None => Value::Nil,
+        });
+    }
+
+    let matches: Vec<Value> = regexp::find_all(interpreter, &regex, &input)?
+        .iter()
+        .map(|captures| regexp::group(&input, captures[0]))
+        .collect();
+    Ok(match matches.is_empty() {
+        true => Value::Nil,
+        false => Value::array(matches),
+    })
+}
+
+/// `string.replace(pattern, replacement)`. A string pattern replaces its
+/// first occurrence and a regex its first match, or all of them if it is
+/// global. The replacement is either a function called with the match, its
+/// groups, its index and the whole string, or a string in which `$&`, `$1`
+/// and `$<name>` stand for parts of the match.
+fn replace(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let input: Vec<char> = receiver.to_string().chars().collect();
+    let mut arguments = arguments.into_iter();
+    let pattern = arguments.next().unwrap();
+    let replacement = arguments.next().unwrap();
+
+    let (regex, matches) = match regexp::regex(&pattern) {
+        Some(regex) if regex.global => {
+            let matches = regexp::find_all(interpreter, &regex, &input)?;
+            (Some(regex), matches)
+        }
+        Some(regex) => {
+            let found = regexp::find(interpreter, &pattern, &regex, &input)?;
+            (Some(regex), found.into_iter().collect())
+        }
+        None => {
+            let needle: Vec<char> = pattern.to_string().chars().collect();
+            let found = (0..=input.len().saturating_sub(needle.len()))
+                .find(|start| input[*start..].starts_with(&needle));
+            let matches = found.map(|start| vec![Some((start, start + needle.len()))]);
+            (None, matches.into_iter().collect())
+        }
+    };
+
+    let mut result = String::new();
+    let mut last = 0;
+    for captures in matches {
+        let (start, end) = (regexp::start(&captures), regexp::end(&captures));
+        result.extend(&input[last..start]);
+        match &replacement {
+            Value::Callable(callback) => {
+                let mut arguments: Vec<Value> = captures
+                    .iter()
+                    .map(|range| regexp::group(&input, *range))
+                    .collect();
+                arguments.push(Value::from(start as f64));
+                arguments.push(receiver.clone());
+                let paren = interpreter.call_site();
+                let value = interpreter.call_back(&paren, callback.clone(), arguments)?;
+                result.push_str(&value.to_string());
+            }
+            template => expand(
+                &mut result,
+                &template.to_string(),
+                &input,
+                &captures,
+                regex.as_deref(),
+            ),
+        }
+        last = end;
+    }
+    result.extend(&input[last..]);
+    Ok(Value::from(result))
+}
+
+/// Appends `template` to `result` with its `$` patterns filled in.
+fn expand(
+    result: &mut String,
+    template: &str,
+    input: &[char],
+    captures: &Captures,
+    regex: Option<&Regex>,
+) {
+    let (start, end) = (regexp::start(captures), regexp::end(captures));
+    let template: Vec<char> = template.chars().collect();
+    let mut index = 0;
+    while index < template.len() {
+        let c = template[index];
+        index += 1;
+        if c != '$' || index == template.len() {
+            result.push(c);
+            continue;
+        }
+
+        match template[index] {
+            '$' => result.push('$'),
+            '&' => result.extend(&input[start..end]),
+            '`' => result.extend(&input[..start]),
+            '\'' => result.extend(&input[end..]),
+            '<' if regex.is_some_and(|regex| !regex.names().is_empty()) => {
+                let close = template[index..].iter().position(|c| *c == '>');
+                let Some(close) = close else {
+                    result.push('$');
+                    continue;
+                };
+                let name: String = template[index + 1..index + close].iter().collect();
+                let group = regex
+                    .into_iter()
+                    .flat_map(|regex| regex.names())
+                    .find(|(group, _)| *group == name);
+                if let Some((_, group)) = group {
+                    result.extend(matched(input, captures[*group]));
+                }
+                index += close;
+            }
+            digit if digit.is_ascii_digit() => {
+                // `$12` is group 12 if there is one, and otherwise group 1
+                // followed by a literal 2.
+                let one = digit.to_digit(10).unwrap() as usize;
+                let two = template
+                    .get(index + 1)
+                    .and_then(|c| c.to_digit(10))
+                    .map(|next| one * 10 + next as usize)
+                    .filter(|group| *group < captures.len() && *group > 0);
+                let (group, length) = match two {
+                    Some(group) => (group, 2),
+                    None => (one, 1),
+                };
+                if group == 0 || group >= captures.len() {
+                    result.push('$');
+                    continue;
+                }
+                result.extend(matched(input, captures[group]));
+                index += length - 1;
+            }
+            _ => {
+                result.push('$');
+                continue;
+            }
+        }
+        index += 1;
+    }
+}
+
+/// What a group matched, which is nothing if it did not take part.
+fn matched(input: &[char], range: Option<(usize, usize)>) -> &[char] {
+    range.map_or(&[], |(start, end)| &input[start..end])
+}
+
+/// `string.split(separator, limit)`. The separator may be a string or a
+/// regex, whose groups are spliced into the result.
+fn split(
+    interpreter: &mut Interpreter,
+    receiver: &Value,
+    arguments: Vec<Value>,
+) -> Result<Value, Unwind> {
+    let text = receiver.to_string();
+    let input: Vec<char> = text.chars().collect();
+    let mut arguments = arguments.into_iter();
+    let separator = arguments.next().unwrap_or(Value::Nil);
+    let limit = match arguments.next() {
+        Some(Value::Number(limit)) if limit >= 0.0 => limit as usize,
+        _ => usize::MAX,
+    };
+
+    let mut pieces = match (&separator, regexp::regex(&separator)) {
+        (Value::Nil, _) => vec![Value::from(text)],
+        (_, Some(regex)) => split_regex(interpreter, &regex, &input)?,
+        (separator, None) => match separator.to_string().as_str() {
+            "" => input.iter().map(|c| Value::from(c.to_string())).collect(),
+            separator => text.split(separator).map(Value::from).collect(),
+        },
+    };
+    pieces.truncate(limit);
+    Ok(Value::array(pieces))
+}
+
+fn split_regex(
+    interpreter: &Interpreter,
+    regex: &Regex,
+    input: &[char],
+) -> Result<Vec<Value>, RuntimeError> {
+    if input.is_empty() {
+        return Ok(match regexp::find_at(interpreter, regex, input, 0)? {
+            Some(_) => Vec::new(),
+            None => vec![Value::from("")],
+        });
+    }
+
+    let mut pieces = Vec::new();
+    let mut last = 0;
+    let mut search = 0;
+    while search < input.len() {
+        let Some(captures) = regexp::find_at(interpreter, regex, input, search)? else {
+            break;
+        };
+        let (start, end) = (regexp::start(&captures), regexp::end(&captures));
+        if start >= input.len() {
+            break;
+        }
+        // An empty match where the last piece ended would split nothing.
+        if end == last {
+            search = start + 1;
+            continue;
+        }
+        pieces.push(regexp::group(input, Some((last, start))));
+        pieces.extend(
+            captures[1..]
+                .iter()
+                .map(|range| regexp::group(input, *range)),
+        );
+        last = end;
+        search = end;
+    }
+    pieces.push(regexp::group(input, Some((last, input.len()))));
+    Ok(pieces)
+}
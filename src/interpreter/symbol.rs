@@ -0,0 +1,55 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::{
+    object::{Object, ObjectKind},
+    value::Value,
+};
+
+/// The property key that `Symbol.iterator` stands for.
+pub const ITERATOR: &str = "@@iterator";
+
+/// Creates the global `Symbol`. Its `Symbol.iterator` names the method that
+/// makes an object iterable, and neither can be changed.
+pub fn symbol() -> Value {
+    let mut iterator = Object::new(ObjectKind::Symbol(ITERATOR));
+    iterator.freeze();
+
+    let mut symbol = Object::new(ObjectKind::Ordinary);
+    symbol.set("iterator", Value::Object(Rc::new(RefCell::new(iterator))));
+    symbol.freeze();
+    Value::Object(Rc::new(RefCell::new(symbol)))
+}
+
+/// The key of the property that `value` names when it is a symbol.
+pub fn symbol_key(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::Object(object) => match object.borrow().kind {
+            ObjectKind::Symbol(key) => Some(key),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The key of the property that the string `key` names. Symbols' keys start
+/// with `@@`, so a string that does too is given one more `@` and can never
+/// name a symbol's property.
+pub fn string_key(key: &str) -> String {
+    match key.starts_with("@@") {
+        true => format!("@{}", key),
+        false => key.to_string(),
+    }
+}
+
+pub fn is_symbol_key(key: &str) -> bool {
+    key.starts_with("@@") && !key.starts_with("@@@")
+}
+
+/// Shows a property key as the string or symbol it was made from.
+pub fn display_key(key: &str) -> String {
+    match key.strip_prefix('@') {
+        Some(string) if string.starts_with("@@") => string.to_string(),
+        Some(_) if is_symbol_key(key) => format!("[Symbol(Symbol.{})]", &key[2..]),
+        _ => key.to_string(),
+    }
+}
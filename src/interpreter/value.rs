@@ -0,0 +1,353 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+use super::{
+    bigint::BigInt,
+    callable::Callable,
+    object::{Object, ObjectKind},
+    promise::PromiseState,
+    symbol,
+};
+
+thread_local! {
+    /// The objects whose `Display` is being written, outermost first.
+    static FORMATTING: RefCell<Vec<*const RefCell<Object>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Clone)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    BigInt(Rc<BigInt>),
+    String(Rc<str>),
+    Callable(Rc<dyn Callable>),
+    Object(Rc<RefCell<Object>>),
+}
+
+impl Value {
+    pub fn array(elements: Vec<Value>) -> Value {
+        Value::Object(Rc::new(RefCell::new(Object::new(ObjectKind::Array(
+            elements,
+        )))))
+    }
+
+    /// What `==` compares: primitives by value, enum values, records and
+    /// tuples by their contents, and anything else by identity.
+    pub fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Number(left), Value::Number(right)) => left == right,
+            _ => self.same_value_zero(other),
+        }
+    }
+
+    /// Like [`Value::equals`], except that NaN is equal to itself. This is
+    /// how the members of records and tuples are compared, and map keys.
+    pub fn same_value_zero(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Bool(left), Value::Bool(right)) => left == right,
+            (Value::Number(left), Value::Number(right)) => {
+                left == right || (left.is_nan() && right.is_nan())
+            }
+            (Value::BigInt(left), Value::BigInt(right)) => left == right,
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Callable(left), Value::Callable(right)) => Rc::ptr_eq(left, right),
+            (Value::Object(left), Value::Object(right)) => {
+                if Rc::ptr_eq(left, right) {
+                    return true;
+                }
+                // These are all immutable, so they cannot contain themselves.
+                let (left, right) = (left.borrow(), right.borrow());
+                match (&left.kind, &right.kind) {
+                    (ObjectKind::Variant(a), ObjectKind::Variant(b)) if Rc::ptr_eq(a, b) => {
+                        left.same_members(&right)
+                    }
+                    (ObjectKind::Record, ObjectKind::Record) => {
+                        left.keys().len() == right.keys().len() && left.same_members(&right)
+                    }
+                    (ObjectKind::Tuple(left), ObjectKind::Tuple(right)) => {
+                        left.len() == right.len()
+                            && left
+                                .iter()
+                                .zip(right)
+                                .all(|(left, right)| left.same_value_zero(right))
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::BigInt(_) => "bigint",
+            Value::String(_) => "string",
+            Value::Callable(_) => "function",
+            Value::Object(_) => "object",
+        }
+    }
+}
+
+/// A value used as a key in a hash map. Keys are equal when
+/// [`Value::same_value_zero`] says so, so records and tuples with the same
+/// contents are the same key.
+#[derive(Clone)]
+pub struct HashKey(pub Value);
+
+impl PartialEq for HashKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.same_value_zero(&other.0)
+    }
+}
+
+impl Eq for HashKey {}
+
+impl Hash for HashKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match &self.0 {
+            Value::Nil => 0.hash(state),
+            Value::Bool(value) => value.hash(state),
+            // -0 and NaN have several representations but are one key each.
+            Value::Number(value) if *value == 0.0 => 0u64.hash(state),
+            Value::Number(value) if value.is_nan() => f64::NAN.to_bits().hash(state),
+            Value::Number(value) => value.to_bits().hash(state),
+            Value::BigInt(value) => value.hash(state),
+            Value::String(value) => value.hash(state),
+            Value::Callable(callable) => (Rc::as_ptr(callable) as *const () as usize).hash(state),
+            Value::Object(object) => {
+                let borrowed = object.borrow();
+                match &borrowed.kind {
+                    ObjectKind::Variant(variant) => {
+                        (Rc::as_ptr(variant) as usize).hash(state);
+                        borrowed.hash_members(state);
+                    }
+                    ObjectKind::Record => {
+                        borrowed.keys().hash(state);
+                        borrowed.hash_members(state);
+                    }
+                    ObjectKind::Tuple(elements) => {
+                        for element in elements {
+                            HashKey(element.clone()).hash(state);
+                        }
+                    }
+                    _ => (Rc::as_ptr(object) as usize).hash(state),
+                }
+            }
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(Rc::from(value))
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(Rc::from(value))
+    }
+}
+
+pub fn format_number(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+
+    let text = value.to_string();
+    if text.ends_with(".0") {
+        text[..text.len() - 2].to_string()
+    } else {
+        text
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Number(value) => write!(f, "{}", format_number(*value)),
+            Value::BigInt(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{}", value),
+            Value::Callable(callable) => write!(f, "<fn {}>", callable.name()),
+            Value::Object(object) => {
+                // An object that contains itself is shown as `[Circular]`
+                // where it recurs, rather than followed forever.
+                let pointer = Rc::as_ptr(object);
+                if FORMATTING.with(|formatting| formatting.borrow().contains(&pointer)) {
+                    return write!(f, "[Circular]");
+                }
+                FORMATTING.with(|formatting| formatting.borrow_mut().push(pointer));
+                let result = write!(f, "{}", object.borrow());
+                FORMATTING.with(|formatting| formatting.borrow_mut().pop());
+                result
+            }
+        }
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ObjectKind::Array(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| format!("{:?}", element))
+                    .collect::<Vec<String>>();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            ObjectKind::Error => {
+                let name = self.get("name").unwrap_or(Value::from("Error"));
+                match self.get("message") {
+                    Some(message) if !message.to_string().is_empty() => {
+                        write!(f, "{}: {}", name, message)
+                    }
+                    _ => write!(f, "{}", name),
+                }
+            }
+            ObjectKind::Class(class) => write!(f, "<class {}>", class.name),
+            ObjectKind::Instance(class) => write!(f, "{} {}", class.name, self.properties()),
+            ObjectKind::Constructor(function) => write!(f, "<class {}>", function.name()),
+            ObjectKind::Generator(_) => write!(f, "[object Generator]"),
+            ObjectKind::Iterator(_) => write!(f, "[object Iterator]"),
+            ObjectKind::Promise(PromiseState::Pending { .. }) => {
+                write!(f, "Promise {{ <pending> }}")
+            }
+            ObjectKind::Promise(PromiseState::Fulfilled(value)) => {
+                write!(f, "Promise {{ {:?} }}", value)
+            }
+            ObjectKind::Promise(PromiseState::Rejected(error)) => {
+                write!(f, "Promise {{ <rejected> {:?} }}", error)
+            }
+            ObjectKind::Enum(enumeration) => write!(f, "<enum {}>", enumeration.name),
+            ObjectKind::Variant(variant) => {
+                write!(f, "{}", variant.qualified_name())?;
+                if variant.fields.is_some() {
+                    let fields = self
+                        .keys()
+                        .iter()
+                        .map(|key| format!("{:?}", self.get(key).unwrap_or(Value::Nil)))
+                        .collect::<Vec<String>>();
+                    write!(f, "({})", fields.join(", "))?;
+                }
+                Ok(())
+            }
+            ObjectKind::Record => write!(f, "#{}", self.properties()),
+            ObjectKind::Tuple(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| format!("{:?}", element))
+                    .collect::<Vec<String>>();
+                write!(f, "#[{}]", elements.join(", "))
+            }
+            ObjectKind::RegExp(regex) => write!(f, "{}", regex),
+            ObjectKind::Map(entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|(key, value)| format!("{:?} => {:?}", key, value))
+                    .collect::<Vec<String>>();
+                match entries.is_empty() {
+                    true => write!(f, "Map(0) {{}}"),
+                    false => write!(f, "Map({}) {{ {} }}", entries.len(), entries.join(", ")),
+                }
+            }
+            ObjectKind::Set(entries) => {
+                let values = entries
+                    .iter()
+                    .map(|(value, _)| format!("{:?}", value))
+                    .collect::<Vec<String>>();
+                match values.is_empty() {
+                    true => write!(f, "Set(0) {{}}"),
+                    false => write!(f, "Set({}) {{ {} }}", values.len(), values.join(", ")),
+                }
+            }
+            ObjectKind::WeakMap(_) => write!(f, "WeakMap {{ <items unknown> }}"),
+            ObjectKind::WeakSet(_) => write!(f, "WeakSet {{ <items unknown> }}"),
+            ObjectKind::Symbol(key) => write!(f, "Symbol(Symbol.{})", &key[2..]),
+            ObjectKind::Module(namespace) => {
+                let exports = namespace
+                    .exports
+                    .iter()
+                    .map(|(name, _)| {
+                        let value = namespace.get(name).unwrap_or(Value::Nil);
+                        format!("{}: {:?}", name, value)
+                    })
+                    .collect::<Vec<String>>();
+                match exports.is_empty() {
+                    true => write!(f, "{{}}"),
+                    false => write!(f, "{{ {} }}", exports.join(", ")),
+                }
+            }
+            ObjectKind::Ordinary => write!(f, "{}", self.properties()),
+        }
+    }
+}
+
+impl Object {
+    /// Whether every property of `self` has the same value on `other`.
+    fn same_members(&self, other: &Object) -> bool {
+        self.keys().iter().all(|key| {
+            let left = self.get(key).unwrap_or(Value::Nil);
+            other
+                .get(key)
+                .is_some_and(|right| left.same_value_zero(&right))
+        })
+    }
+
+    fn hash_members<H: Hasher>(&self, state: &mut H) {
+        for key in self.keys() {
+            HashKey(self.get(key).unwrap_or(Value::Nil)).hash(state);
+        }
+    }
+
+    fn properties(&self) -> String {
+        if self.keys().is_empty() {
+            return String::from("{}");
+        }
+
+        let properties = self
+            .keys()
+            .iter()
+            .map(|key| {
+                let value = self.get(key).unwrap_or(Value::Nil);
+                format!("{}: {:?}", symbol::display_key(key), value)
+            })
+            .collect::<Vec<String>>();
+        format!("{{ {} }}", properties.join(", "))
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(value) => write!(f, "{:?}", value),
+            Value::BigInt(value) => write!(f, "{}n", value),
+            _ => write!(f, "{}", self),
+        }
+    }
+}
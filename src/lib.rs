@@ -1,67 +1,324 @@
 use anyhow::Result;
+use interpreter::{event_loop::EventLoop, Interpreter};
 use parser::{
+    resolver::Resolver,
     scanner::Scanner,
+    statement::Statement,
     token::{Token, TokenType},
+    typeck::TypeChecker,
     Parser,
 };
-use std::{fs::File, io::Read, path::PathBuf};
+use std::{
+    cell::RefCell,
+    fmt,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+pub mod ast;
+pub mod interpreter;
+pub mod parser;
+
+/// Stack size for threads that run scripts. Every script call costs several
+/// native frames, so the interpreter needs far more than the default.
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    Error,
+    TypeError,
+    ReferenceError,
+    RangeError,
+    SyntaxError,
+}
+
+impl ErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Error => "Error",
+            ErrorKind::TypeError => "TypeError",
+            ErrorKind::ReferenceError => "ReferenceError",
+            ErrorKind::RangeError => "RangeError",
+            ErrorKind::SyntaxError => "SyntaxError",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub token: Token,
+    pub kind: ErrorKind,
+    /// Boxed rather than a `String` to keep the many `Result`s that carry
+    /// runtime errors small.
+    pub message: Box<str>,
+}
+
+impl RuntimeError {
+    /// Creates a `TypeError`, which is what most failed runtime checks are.
+    pub fn new(token: Token, message: &str) -> RuntimeError {
+        RuntimeError::with_kind(token, ErrorKind::TypeError, message)
+    }
+
+    pub fn with_kind(token: Token, kind: ErrorKind, message: &str) -> RuntimeError {
+        RuntimeError {
+            token,
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n[{}]", self.message, self.token.location())
+    }
+}
+
+impl std::error::Error for RuntimeError {}
 
-mod ast;
-mod interpreter;
-mod parser;
+/// Resolves an import specifier against the directory of the importing file.
+/// The result is canonical, so that it can identify a module in the cache.
+pub fn module_path(directory: &Path, specifier: &str) -> io::Result<PathBuf> {
+    directory.join(specifier).canonicalize()
+}
 
 pub struct JSPlusPlus {
     had_error: bool,
+    had_runtime_error: bool,
+    interpreter: Interpreter,
+    /// Promise reactions and timers, which run once the script has finished.
+    event_loop: Rc<RefCell<EventLoop>>,
+    /// The modules that are being loaded, innermost last, for reporting
+    /// circular imports.
+    loading: Vec<PathBuf>,
+    /// Turns off automatic semicolon insertion.
+    strict: bool,
 }
 
 impl JSPlusPlus {
     pub fn new() -> JSPlusPlus {
-        JSPlusPlus { had_error: false }
+        let event_loop = Rc::new(RefCell::new(EventLoop::new()));
+        JSPlusPlus {
+            had_error: false,
+            had_runtime_error: false,
+            interpreter: Interpreter::with_event_loop(event_loop.clone()),
+            event_loop,
+            loading: Vec::new(),
+            strict: false,
+        }
+    }
+
+    /// Requires statements to end in `;` rather than letting line breaks
+    /// end them.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
     }
 
-    pub fn error(line: usize, message: &str) {
-        JSPlusPlus::report(line, "", message);
+    pub fn error(location: &str, message: &str) {
+        JSPlusPlus::report(location, "", message);
     }
 
     pub fn error_token(token: &Token, message: &str) {
         if token.token_type == TokenType::EOF {
-            JSPlusPlus::report(token.line, " at end", message);
+            JSPlusPlus::report(&token.location(), " at end", message);
         } else {
-            JSPlusPlus::report(token.line, &format!(" at '{}'", token.lexeme), message);
+            JSPlusPlus::report(
+                &token.location(),
+                &format!(" at '{}'", token.lexeme),
+                message,
+            );
         }
     }
 
-    fn report(line: usize, location: &str, message: &str) {
-        println!("[line {}] Error {}: {}", line, location, message);
+    /// Reports something suspicious that does not stop the program running.
+    pub fn warning_token(token: &Token, message: &str) {
+        println!(
+            "[{}] Warning at '{}': {}",
+            token.location(),
+            token.lexeme,
+            message
+        );
     }
 
-    fn run(&self, source: String) {
-        let mut scanner = Scanner::new(source);
-        let tokens = scanner.scan_tokens();
+    pub fn runtime_error(error: &RuntimeError) {
+        println!("{}", error);
+    }
+
+    fn report(line: &str, location: &str, message: &str) {
+        println!("[{}] Error {}: {}", line, location, message);
+    }
+
+    fn run(&mut self, source: String) {
+        let Some(stmts) = self.compile(source, None) else {
+            return;
+        };
+
+        let directory = self.interpreter.directory().to_path_buf();
+        if !self.load_imports(&stmts, &directory) {
+            return;
+        }
+
+        if let Err(error) = self.interpreter.interpret(&stmts) {
+            self.fail(&error);
+            return;
+        }
+        self.run_event_loop();
+    }
+
+    /// Runs queued jobs until there are none left. The first uncaught error
+    /// ends the script, and the jobs still queued are dropped.
+    fn run_event_loop(&mut self) {
+        loop {
+            let job = self.event_loop.borrow_mut().next_job();
+            let result = match job {
+                Ok(Some(job)) => self.interpreter.run_job(job),
+                Ok(None) => return,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                self.fail(&error);
+                return;
+            }
+        }
+    }
 
-        for token in tokens {
-            println!("{}", token.to_string());
+    fn fail(&mut self, error: &RuntimeError) {
+        JSPlusPlus::runtime_error(error);
+        self.had_runtime_error = true;
+        self.event_loop.borrow_mut().clear();
+    }
+
+    /// Parses and checks a script, or the module at `path`, whose name its
+    /// errors then give.
+    fn compile(&mut self, source: String, path: Option<&Path>) -> Option<Vec<Statement>> {
+        let mut scanner = Scanner::new(source);
+        if let Some(path) = path {
+            scanner.set_file(&module_name(path));
+        }
+        let tokens = scanner.scan_tokens().clone();
+        if scanner.had_error() {
+            self.had_error = true;
+            return None;
         }
 
-        let mut parser = Parser::new(tokens.clone());
+        let mut parser = Parser::new(tokens);
+        parser.set_strict(self.strict);
         let stmts = parser.parse();
+        if parser.had_error() {
+            self.had_error = true;
+            return None;
+        }
+
+        let mut resolver = Resolver::new();
+        resolver.resolve(&stmts);
+        if resolver.had_error() {
+            self.had_error = true;
+            return None;
+        }
+
+        // Scripts without annotations are left to fail at runtime as before.
+        if parser.has_annotations() {
+            let mut checker = TypeChecker::new();
+            checker.check(&stmts);
+            if checker.had_error() {
+                self.had_error = true;
+                return None;
+            }
+        }
+
+        Some(stmts)
+    }
+
+    /// Loads and runs every module that `statements` import, so that their
+    /// exports are ready by the time the imports are executed.
+    fn load_imports(&mut self, statements: &[Statement], directory: &Path) -> bool {
+        for statement in statements {
+            let Statement::Import { source, path, .. } = statement else {
+                continue;
+            };
+
+            let Ok(path) = module_path(directory, path) else {
+                self.module_error(source, &format!("Cannot find module '{}'.", path));
+                return false;
+            };
+            if self.interpreter.has_module(&path) {
+                continue;
+            }
+
+            if let Some(start) = self.loading.iter().position(|loading| *loading == path) {
+                let cycle = self.loading[start..]
+                    .iter()
+                    .chain([&path])
+                    .map(|path| module_name(path))
+                    .collect::<Vec<String>>();
+                self.module_error(source, &format!("Circular import: {}.", cycle.join(" -> ")));
+                return false;
+            }
 
-        for stmt in stmts {
-            println!("{:?}", stmt);
-            // println!("{}", ast::printer::AstPrinter.print_statement(&stmt));
+            if !self.load_module(source, path) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn load_module(&mut self, source: &Token, path: PathBuf) -> bool {
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                self.module_error(
+                    source,
+                    &format!("Cannot read module '{}': {}.", module_name(&path), error),
+                );
+                return false;
+            }
+        };
+        let Some(stmts) = self.compile(contents, Some(&path)) else {
+            return false;
+        };
+
+        self.loading.push(path.clone());
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let loaded = self.load_imports(&stmts, &directory);
+        self.loading.pop();
+        if !loaded {
+            return false;
+        }
+
+        if let Err(error) = self.interpreter.interpret_module(&stmts, path) {
+            self.fail(&error);
+            return false;
         }
+        true
+    }
+
+    fn module_error(&mut self, token: &Token, message: &str) {
+        JSPlusPlus::error_token(token, message);
+        self.had_error = true;
     }
 
-    pub fn run_file(&self, path: PathBuf) -> Result<()> {
-        let file = File::open(path);
+    pub fn run_file(&mut self, path: PathBuf) -> Result<()> {
+        let file = File::open(&path);
         let mut contents = String::new();
         file?.read_to_string(&mut contents)?;
 
+        if let Some(directory) = path.parent() {
+            self.interpreter.set_directory(directory.to_path_buf());
+        }
+        if let Ok(path) = path.canonicalize() {
+            self.loading.push(path);
+        }
+
         self.run(contents);
 
         if self.had_error {
             std::process::exit(65);
         }
+        if self.had_runtime_error {
+            std::process::exit(70);
+        }
 
         Ok(())
     }
@@ -81,3 +338,17 @@ impl JSPlusPlus {
         }
     }
 }
+
+/// A module's file name, which is how errors in it and circular imports are
+/// reported.
+fn module_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+impl Default for JSPlusPlus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
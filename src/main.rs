@@ -1,13 +1,20 @@
 use std::path::PathBuf;
 
-use jsplusplus::JSPlusPlus;
+use jsplusplus::{JSPlusPlus, STACK_SIZE};
 
 fn main() {
     let path = PathBuf::from("F:/projects/js++/test.js");
 
-    let jsplusplus = JSPlusPlus::new();
+    let interpreter = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            let mut jsplusplus = JSPlusPlus::new();
 
-    if let Err(e) = jsplusplus.run_file(path) {
-        println!("Error: {}", e);
-    }
+            if let Err(e) = jsplusplus.run_file(path) {
+                println!("Error: {}", e);
+            }
+        })
+        .expect("failed to spawn the interpreter thread");
+
+    interpreter.join().expect("interpreter thread panicked");
 }
@@ -0,0 +1,75 @@
+use std::fmt;
+
+use super::token::Token;
+
+/// A type written after a `:` or named by a `type` or `interface`
+/// declaration. Annotations are only read by the type checker and have no
+/// effect at runtime.
+#[derive(Clone, Debug)]
+pub enum TypeAnnotation {
+    /// `number`, `string`, `bool`, `nil`, `void`, `any`, or the name of a
+    /// type alias, interface or class.
+    Named { name: Token },
+    /// `T[]`.
+    Array { element: Box<TypeAnnotation> },
+    /// `A | B`.
+    Union { types: Vec<TypeAnnotation> },
+    /// `(a: A, b: B) => R`. The parameter names are only documentation.
+    Function {
+        paren: Token,
+        params: Vec<TypeAnnotation>,
+        result: Box<TypeAnnotation>,
+    },
+    /// `{ name: T; other?: U }`.
+    Object {
+        brace: Token,
+        members: Vec<TypeMember>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct TypeMember {
+    pub name: Token,
+    pub annotation: TypeAnnotation,
+    /// Set for `name?: T`, which may be missing.
+    pub optional: bool,
+}
+
+impl fmt::Display for TypeAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeAnnotation::Named { name } => write!(f, "{}", name.lexeme),
+            TypeAnnotation::Array { element } => match element.as_ref() {
+                TypeAnnotation::Union { .. } | TypeAnnotation::Function { .. } => {
+                    write!(f, "({})[]", element)
+                }
+                _ => write!(f, "{}[]", element),
+            },
+            TypeAnnotation::Union { types } => {
+                let types: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+                write!(f, "{}", types.join(" | "))
+            }
+            TypeAnnotation::Function {
+                paren: _,
+                params,
+                result,
+            } => {
+                let params: Vec<String> = params.iter().map(|t| t.to_string()).collect();
+                write!(f, "({}) => {}", params.join(", "), result)
+            }
+            TypeAnnotation::Object { brace: _, members } => {
+                if members.is_empty() {
+                    return write!(f, "{{}}");
+                }
+                let members: Vec<String> = members
+                    .iter()
+                    .map(|member| {
+                        let optional = if member.optional { "?" } else { "" };
+                        format!("{}{}: {}", member.name.lexeme, optional, member.annotation)
+                    })
+                    .collect();
+                write!(f, "{{ {} }}", members.join("; "))
+            }
+        }
+    }
+}
@@ -1,38 +1,81 @@
-use std::collections::HashMap;
+use std::{fmt, rc::Rc};
 
-use super::token::Token;
+use crate::interpreter::regex::Regex;
+
+use super::{
+    annotation::TypeAnnotation,
+    pattern::{MatchPattern, Pattern},
+    statement::{Parameter, Statement},
+    token::Token,
+};
 
 #[derive(Clone, Debug)]
 pub enum Literal {
     Nil,
     Bool(bool),
     Number(f64),
+    /// The decimal digits of a BigInt such as `123n`, without the `n`.
+    BigInt(String),
     String(String),
-    Map(HashMap<String, String>),
 }
 
-impl Literal {
-    pub fn to_string(&self) -> String {
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Literal::Nil => "nil".to_string(),
-            Literal::Bool(value) => value.to_string(),
-            Literal::Number(value) => value.to_string(),
-            Literal::String(value) => value.to_string(),
-            Literal::Map(value) => value
-                .into_iter()
-                .map(|(k, v)| format!("{}: {}", k, v))
-                .collect::<Vec<String>>()
-                .join(", "),
+            Literal::Nil => write!(f, "nil"),
+            Literal::Bool(value) => write!(f, "{}", value),
+            Literal::Number(value) => write!(f, "{}", value),
+            Literal::BigInt(value) => write!(f, "{}n", value),
+            Literal::String(value) => write!(f, "{}", value),
         }
     }
 }
 
+#[derive(Clone, Debug)]
+pub enum PropertyKey {
+    /// An identifier, string or number key, already turned into its name.
+    Named {
+        name: String,
+        token: Token,
+    },
+    Computed(Expression),
+}
+
+#[derive(Clone, Debug)]
+pub struct Property {
+    pub key: PropertyKey,
+    pub value: Expression,
+}
+
+/// `pattern if (guard) => body`, one arm of a `match`.
+#[derive(Clone, Debug)]
+pub struct MatchArm {
+    pub pattern: MatchPattern,
+    pub guard: Option<Expression>,
+    pub body: Expression,
+}
+
 #[derive(Clone, Debug)]
 pub enum Expression {
+    Array {
+        bracket: Token,
+        /// `None` marks a hole such as the middle of `[1, , 3]`.
+        elements: Vec<Option<Expression>>,
+    },
     Assign {
         name: Token,
         value: Box<Expression>,
     },
+    /// `[a, b] = [b, a]` and other assignments that destructure.
+    AssignPattern {
+        target: Pattern,
+        equals: Token,
+        value: Box<Expression>,
+    },
+    Await {
+        keyword: Token,
+        value: Box<Expression>,
+    },
     Binary {
         left: Box<Expression>,
         operator: Token,
@@ -42,14 +85,34 @@ pub enum Expression {
         callee: Box<Expression>,
         paren: Token,
         arguments: Vec<Expression>,
+        /// Set for `f?.()`, which skips the rest of its chain when `f` is nil.
+        optional: bool,
+    },
+    Function {
+        name: Option<Token>,
+        params: Vec<Parameter>,
+        return_type: Option<TypeAnnotation>,
+        body: Vec<Statement>,
+        arrow: bool,
+        is_generator: bool,
+        is_async: bool,
     },
     Get {
         object: Box<Expression>,
         name: Token,
+        /// Set for `a?.b`.
+        optional: bool,
     },
     Grouping {
         expression: Box<Expression>,
     },
+    Index {
+        object: Box<Expression>,
+        bracket: Token,
+        index: Box<Expression>,
+        /// Set for `a?.[k]`.
+        optional: bool,
+    },
     Literal {
         value: Option<Literal>,
     },
@@ -58,11 +121,65 @@ pub enum Expression {
         operator: Token,
         right: Box<Expression>,
     },
+    /// `match (subject) { pattern => value, ... }`, which is the value of
+    /// the first arm whose pattern matches.
+    Match {
+        keyword: Token,
+        subject: Box<Expression>,
+        arms: Vec<MatchArm>,
+    },
+    New {
+        keyword: Token,
+        callee: Box<Expression>,
+        arguments: Vec<Expression>,
+    },
+    Object {
+        brace: Token,
+        properties: Vec<Property>,
+    },
+    /// A chain of calls and property accesses with at least one `?.` in
+    /// it. The whole chain is nil as soon as an optional link finds nil.
+    OptionalChain {
+        expression: Box<Expression>,
+    },
+    /// `#{ a: 1 }`, an object literal made into an immutable record.
+    Record {
+        hash: Token,
+        object: Box<Expression>,
+    },
+    /// `/ab+c/g`, compiled when it is parsed.
+    Regex {
+        token: Token,
+        regex: Rc<Regex>,
+    },
     Set {
         object: Box<Expression>,
         name: Token,
         value: Box<Expression>,
     },
+    SetIndex {
+        object: Box<Expression>,
+        bracket: Token,
+        index: Box<Expression>,
+        value: Box<Expression>,
+    },
+    Spread {
+        ellipsis: Token,
+        expression: Box<Expression>,
+    },
+    /// `super.method`, or `super` on its own as the callee of `super(...)`.
+    Super {
+        keyword: Token,
+        method: Option<Token>,
+    },
+    This {
+        keyword: Token,
+    },
+    /// `#[1, 2]`, an array literal made into an immutable tuple.
+    Tuple {
+        hash: Token,
+        array: Box<Expression>,
+    },
     Unary {
         operator: Token,
         right: Box<Expression>,
@@ -70,25 +187,61 @@ pub enum Expression {
     Variable {
         name: Token,
     },
+    /// `yield value`, or `yield* iterable` when `delegate` is set.
+    Yield {
+        keyword: Token,
+        value: Option<Box<Expression>>,
+        delegate: bool,
+    },
 }
 
 pub trait Visitor<T> {
+    fn visit_array_expression(&mut self, expr: &Expression) -> T;
     fn visit_assign_expression(&mut self, expr: &Expression) -> T;
+    fn visit_assign_pattern_expression(&mut self, expr: &Expression) -> T;
+    fn visit_await_expression(&mut self, expr: &Expression) -> T;
     fn visit_binary_expression(&mut self, expr: &Expression) -> T;
     fn visit_call_expression(&mut self, expr: &Expression) -> T;
+    fn visit_function_expression(&mut self, expr: &Expression) -> T;
     fn visit_get_expression(&mut self, expr: &Expression) -> T;
     fn visit_grouping_expression(&mut self, expr: &Expression) -> T;
+    fn visit_index_expression(&mut self, expr: &Expression) -> T;
     fn visit_literal_expression(&mut self, expr: &Expression) -> T;
     fn visit_logical_expression(&mut self, expr: &Expression) -> T;
+    fn visit_match_expression(&mut self, expr: &Expression) -> T;
+    fn visit_new_expression(&mut self, expr: &Expression) -> T;
+    fn visit_object_expression(&mut self, expr: &Expression) -> T;
+    fn visit_optional_chain_expression(&mut self, expr: &Expression) -> T;
+    fn visit_record_expression(&mut self, expr: &Expression) -> T;
+    fn visit_regex_expression(&mut self, expr: &Expression) -> T;
     fn visit_set_expression(&mut self, expr: &Expression) -> T;
+    fn visit_set_index_expression(&mut self, expr: &Expression) -> T;
+    fn visit_spread_expression(&mut self, expr: &Expression) -> T;
+    fn visit_super_expression(&mut self, expr: &Expression) -> T;
+    fn visit_this_expression(&mut self, expr: &Expression) -> T;
+    fn visit_tuple_expression(&mut self, expr: &Expression) -> T;
     fn visit_unary_expression(&mut self, expr: &Expression) -> T;
     fn visit_variable_expression(&mut self, expr: &Expression) -> T;
+    fn visit_yield_expression(&mut self, expr: &Expression) -> T;
 }
 
 impl Expression {
     pub fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
         match self {
+            Expression::Array {
+                bracket: _,
+                elements: _,
+            } => visitor.visit_array_expression(self),
             Expression::Assign { name: _, value: _ } => visitor.visit_assign_expression(self),
+            Expression::AssignPattern {
+                target: _,
+                equals: _,
+                value: _,
+            } => visitor.visit_assign_pattern_expression(self),
+            Expression::Await {
+                keyword: _,
+                value: _,
+            } => visitor.visit_await_expression(self),
             Expression::Binary {
                 left: _,
                 operator: _,
@@ -98,25 +251,85 @@ impl Expression {
                 callee: _,
                 paren: _,
                 arguments: _,
+                optional: _,
             } => visitor.visit_call_expression(self),
-            Expression::Get { object: _, name: _ } => visitor.visit_get_expression(self),
+            Expression::Function {
+                name: _,
+                params: _,
+                return_type: _,
+                body: _,
+                arrow: _,
+                is_generator: _,
+                is_async: _,
+            } => visitor.visit_function_expression(self),
+            Expression::Get {
+                object: _,
+                name: _,
+                optional: _,
+            } => visitor.visit_get_expression(self),
             Expression::Grouping { expression: _ } => visitor.visit_grouping_expression(self),
+            Expression::Index {
+                object: _,
+                bracket: _,
+                index: _,
+                optional: _,
+            } => visitor.visit_index_expression(self),
             Expression::Literal { value: _ } => visitor.visit_literal_expression(self),
             Expression::Logical {
                 left: _,
                 operator: _,
                 right: _,
             } => visitor.visit_logical_expression(self),
+            Expression::Match {
+                keyword: _,
+                subject: _,
+                arms: _,
+            } => visitor.visit_match_expression(self),
+            Expression::New {
+                keyword: _,
+                callee: _,
+                arguments: _,
+            } => visitor.visit_new_expression(self),
+            Expression::Object {
+                brace: _,
+                properties: _,
+            } => visitor.visit_object_expression(self),
+            Expression::OptionalChain { expression: _ } => {
+                visitor.visit_optional_chain_expression(self)
+            }
+            Expression::Record { hash: _, object: _ } => visitor.visit_record_expression(self),
+            Expression::Regex { token: _, regex: _ } => visitor.visit_regex_expression(self),
             Expression::Set {
                 object: _,
                 name: _,
                 value: _,
             } => visitor.visit_set_expression(self),
+            Expression::SetIndex {
+                object: _,
+                bracket: _,
+                index: _,
+                value: _,
+            } => visitor.visit_set_index_expression(self),
+            Expression::Spread {
+                ellipsis: _,
+                expression: _,
+            } => visitor.visit_spread_expression(self),
+            Expression::Super {
+                keyword: _,
+                method: _,
+            } => visitor.visit_super_expression(self),
+            Expression::This { keyword: _ } => visitor.visit_this_expression(self),
+            Expression::Tuple { hash: _, array: _ } => visitor.visit_tuple_expression(self),
             Expression::Unary {
                 operator: _,
                 right: _,
             } => visitor.visit_unary_expression(self),
             Expression::Variable { name: _ } => visitor.visit_variable_expression(self),
+            Expression::Yield {
+                keyword: _,
+                value: _,
+                delegate: _,
+            } => visitor.visit_yield_expression(self),
         }
     }
 }
@@ -14,19 +14,38 @@ pub static KEYWORDS: Lazy<Mutex<HashMap<&str, TokenType>>> = Lazy::new(|| {
     let mut m: HashMap<&str, TokenType> = HashMap::new();
 
     m.insert("and", TokenType::And);
+    m.insert("async", TokenType::Async);
+    m.insert("await", TokenType::Await);
+    m.insert("break", TokenType::Break);
+    m.insert("case", TokenType::Case);
+    m.insert("catch", TokenType::Catch);
+    m.insert("class", TokenType::Class);
     m.insert("const", TokenType::Const);
+    m.insert("continue", TokenType::Continue);
+    m.insert("default", TokenType::Default);
+    m.insert("do", TokenType::Do);
     m.insert("else", TokenType::Else);
+    m.insert("export", TokenType::Export);
+    m.insert("extends", TokenType::Extends);
     m.insert("false", TokenType::False);
+    m.insert("finally", TokenType::Finally);
     m.insert("for", TokenType::For);
     m.insert("function", TokenType::Function);
     m.insert("if", TokenType::If);
     m.insert("import", TokenType::Import);
     m.insert("let", TokenType::Let);
+    m.insert("new", TokenType::New);
     m.insert("nil", TokenType::Nil);
     m.insert("or", TokenType::Or);
-    m.insert("print", TokenType::Print);
     m.insert("return", TokenType::Return);
+    m.insert("static", TokenType::Static);
+    m.insert("super", TokenType::Super);
+    m.insert("switch", TokenType::Switch);
+    m.insert("this", TokenType::This);
+    m.insert("throw", TokenType::Throw);
     m.insert("true", TokenType::True);
+    m.insert("try", TokenType::Try);
     m.insert("while", TokenType::While);
+    m.insert("yield", TokenType::Yield);
     Mutex::new(m)
 });
@@ -1,28 +1,65 @@
-use crate::JSPlusPlus;
+use std::rc::Rc;
+
+use crate::{
+    interpreter::{bigint::BigInt, class::operator_method, regex::Regex, value::format_number},
+    JSPlusPlus,
+};
 
 use self::{
-    expression::{Expression, Literal},
-    statement::Statement,
+    annotation::{TypeAnnotation, TypeMember},
+    expression::{Expression, Literal, MatchArm, Property, PropertyKey},
+    pattern::{MatchPattern, MatchProperty, Pattern, PatternElement, PatternProperty},
+    statement::{
+        Access, Binding, Field, ForKind, Method, MethodKind, ModuleSpecifier, Parameter, Statement,
+        SwitchCase, Variant,
+    },
     token::{Token, TokenType},
 };
 use anyhow::{bail, Result};
 
-mod environment;
+pub mod annotation;
 pub mod expression;
-mod keyword;
-mod resolver;
+pub mod keyword;
+pub mod pattern;
+pub mod resolver;
 pub mod scanner;
 pub mod statement;
 pub mod token;
+pub mod typeck;
 
 pub struct Parser {
     tokens: Vec<token::Token>,
     current: usize,
+    had_error: bool,
+    /// Whether any type annotation or declaration has been parsed, without
+    /// which there is nothing for the type checker to do.
+    has_annotations: bool,
+    /// Requires every statement to end in `;` instead of inserting missing
+    /// ones at line breaks.
+    strict: bool,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<token::Token>) -> Parser {
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            had_error: false,
+            has_annotations: false,
+            strict: false,
+        }
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn had_error(&self) -> bool {
+        self.had_error
+    }
+
+    pub fn has_annotations(&self) -> bool {
+        self.has_annotations
     }
 
     pub fn parse_expression(&mut self) -> Result<Expression> {
@@ -33,9 +70,8 @@ impl Parser {
         let mut statements = Vec::new();
         while !self.is_at_end() {
             let declaration = self.declaration();
-            match declaration {
-                Some(declaration) => statements.push(declaration),
-                None => {}
+            if let Some(declaration) = declaration {
+                statements.push(declaration)
             }
         }
         statements
@@ -47,8 +83,22 @@ impl Parser {
 
     fn declaration(&mut self) -> Option<Statement> {
         let mut get_stmt = || -> Result<Statement> {
+            if self._match(&[TokenType::Import]) {
+                return self.import_declaration();
+            }
+            if self._match(&[TokenType::Export]) {
+                return self.export_declaration();
+            }
+            if self._match(&[TokenType::Class]) {
+                return self.class_declaration();
+            }
             if self._match(&[TokenType::Function]) {
-                return self.function("function");
+                return self.function("function", false);
+            }
+            if self.check(TokenType::Async) && self.check_next(TokenType::Function) {
+                self.advance();
+                self.advance();
+                return self.function("function", true);
             }
             if self._match(&[TokenType::Let]) {
                 return self.let_declaration();
@@ -56,7 +106,20 @@ impl Parser {
             if self._match(&[TokenType::Const]) {
                 return self.const_declaration();
             }
-            return self.statement();
+            // `type` and `interface` are only keywords in front of a name.
+            if self.check_word("type") && self.check_next(TokenType::Identifier) {
+                self.advance();
+                return self.type_alias_declaration();
+            }
+            if self.check_word("interface") && self.check_next(TokenType::Identifier) {
+                self.advance();
+                return self.interface_declaration();
+            }
+            if self.check_word("enum") && self.check_next(TokenType::Identifier) {
+                self.advance();
+                return self.enum_declaration();
+            }
+            self.statement()
         };
 
         let result = get_stmt();
@@ -68,41 +131,156 @@ impl Parser {
     }
 
     fn statement(&mut self) -> Result<Statement> {
+        if self._match(&[TokenType::Break]) {
+            return self.break_statement();
+        }
+        if self._match(&[TokenType::Continue]) {
+            return self.continue_statement();
+        }
+        if self._match(&[TokenType::Do]) {
+            return self.do_while_statement(None);
+        }
         if self._match(&[TokenType::For]) {
-            return Ok(self.for_statement().unwrap());
+            return self.for_statement(None);
         }
         if self._match(&[TokenType::If]) {
-            return Ok(self.if_statement().unwrap());
+            return self.if_statement();
         }
-        if self._match(&[TokenType::Print]) {
-            return Ok(self.print_statement().unwrap());
+        if self.is_print_statement() {
+            self.advance();
+            return self.print_statement();
         }
         if self._match(&[TokenType::Return]) {
-            return Ok(self.return_statement().unwrap());
+            return self.return_statement();
+        }
+        if self._match(&[TokenType::Switch]) {
+            return self.switch_statement();
+        }
+        if self._match(&[TokenType::Throw]) {
+            return self.throw_statement();
+        }
+        if self._match(&[TokenType::Try]) {
+            return self.try_statement();
         }
         if self._match(&[TokenType::While]) {
-            return Ok(self.while_statement().unwrap());
+            return self.while_statement(None);
         }
         if self._match(&[TokenType::LeftBrace]) {
             return Ok(Statement::Block {
-                statements: self.block().unwrap(),
+                statements: self.block()?,
             });
         }
+        if self.check(TokenType::Identifier) && self.check_next(TokenType::Colon) {
+            return self.labeled_statement();
+        }
         self.expression_statement()
     }
 
-    fn for_statement(&mut self) -> Result<Statement> {
+    fn break_statement(&mut self) -> Result<Statement> {
+        let keyword = self.previous();
+        let mut label: Option<Token> = None;
+        if self.check(TokenType::Identifier) && !self.at_line_break() {
+            label = Some(self.advance());
+        }
+        self.consume_semicolon("Expect ';' after 'break'.")?;
+        Ok(Statement::Break { keyword, label })
+    }
+
+    fn continue_statement(&mut self) -> Result<Statement> {
+        let keyword = self.previous();
+        let mut label: Option<Token> = None;
+        if self.check(TokenType::Identifier) && !self.at_line_break() {
+            label = Some(self.advance());
+        }
+        self.consume_semicolon("Expect ';' after 'continue'.")?;
+        Ok(Statement::Continue { keyword, label })
+    }
+
+    fn labeled_statement(&mut self) -> Result<Statement> {
+        let label = self.advance();
+        self.consume(TokenType::Colon, "Expect ':' after label.")?;
+
+        // Loops own their label so that `continue label` can target them.
+        if self._match(&[TokenType::Do]) {
+            return self.do_while_statement(Some(label));
+        }
+        if self._match(&[TokenType::For]) {
+            return self.for_statement(Some(label));
+        }
+        if self._match(&[TokenType::While]) {
+            return self.while_statement(Some(label));
+        }
+
+        let body = Box::new(self.statement()?);
+        Ok(Statement::Labeled { label, body })
+    }
+
+    fn do_while_statement(&mut self, label: Option<Token>) -> Result<Statement> {
+        let body = Box::new(self.statement()?);
+        self.consume(TokenType::While, "Expect 'while' after do-while body.")?;
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+        // The `;` after a do-while may always be left out.
+        if self.strict {
+            self.consume(TokenType::Semicolon, "Expect ';' after do-while condition.")?;
+        } else {
+            self._match(&[TokenType::Semicolon]);
+        }
+
+        Ok(Statement::DoWhile {
+            body,
+            condition,
+            label,
+        })
+    }
+
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Statement> {
+        let keyword = self.previous();
         self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
 
         let initializer: Option<Statement>;
         if self._match(&[TokenType::Semicolon]) {
             initializer = None;
-        } else if self._match(&[TokenType::Let]) {
-            initializer = Some(self.let_declaration()?);
-        } else if self._match(&[TokenType::Const]) {
-            initializer = Some(self.const_declaration()?);
+        } else if self._match(&[TokenType::Let, TokenType::Const]) {
+            let binding = match self.previous().token_type {
+                TokenType::Let => Binding::Let,
+                _ => Binding::Const,
+            };
+            let target = self.binding_pattern("Expect variable name.")?;
+            if self.check_word("of") || self.check_word("in") {
+                return self.for_each_statement(keyword, binding, target, label);
+            }
+
+            let annotation = self.type_annotation()?;
+            let initializer_value = self.variable_initializer(&target)?;
+            self.consume(
+                TokenType::Semicolon,
+                "Expect ';' after variable declaration.",
+            )?;
+            initializer = Some(match binding {
+                Binding::Const => Statement::Const {
+                    target,
+                    annotation,
+                    initializer: initializer_value,
+                },
+                _ => Statement::Let {
+                    target,
+                    annotation,
+                    initializer: initializer_value,
+                },
+            });
         } else {
-            initializer = Some(self.expression_statement()?);
+            let expression = self.expression()?;
+            if self.check_word("of") || self.check_word("in") {
+                let target = match expression {
+                    Expression::Variable { name } => Pattern::Identifier { name },
+                    expression => self.assignment_pattern(expression)?,
+                };
+                return self.for_each_statement(keyword, Binding::Assign, target, label);
+            }
+            self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+            initializer = Some(Statement::Expression { expression });
         }
 
         let mut condition: Option<Expression> = None;
@@ -117,27 +295,20 @@ impl Parser {
         }
         self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
 
-        let mut body = self.statement()?;
-
-        if let Some(increment) = increment {
-            body = Statement::Block {
-                statements: vec![
-                    body,
-                    Statement::Expression {
-                        expression: increment,
-                    },
-                ],
-            };
-        }
+        let body = self.statement()?;
 
         if condition.is_none() {
             condition = Some(Expression::Literal {
                 value: Some(Literal::Bool(true)),
             });
         }
-        body = Statement::While {
+        // The increment stays on the loop instead of being appended to the
+        // body, so it still runs when the body is left through `continue`.
+        let mut body = Statement::While {
             condition: condition.unwrap(),
             body: Box::new(body),
+            increment,
+            label,
         };
 
         if let Some(initializer) = initializer {
@@ -148,6 +319,34 @@ impl Parser {
         Ok(body)
     }
 
+    /// Parses the rest of a `for...of` or `for...in` loop from its `of` or
+    /// `in`.
+    fn for_each_statement(
+        &mut self,
+        keyword: Token,
+        binding: Binding,
+        target: Pattern,
+        label: Option<Token>,
+    ) -> Result<Statement> {
+        let kind = match self.advance().lexeme.as_str() {
+            "of" => ForKind::Of,
+            _ => ForKind::In,
+        };
+        let iterable = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+        let body = Box::new(self.statement()?);
+
+        Ok(Statement::ForEach {
+            keyword,
+            kind,
+            binding,
+            target,
+            iterable,
+            body,
+            label,
+        })
+    }
+
     fn if_statement(&mut self) -> Result<Statement> {
         self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
         let condition = self.expression()?;
@@ -166,96 +365,1098 @@ impl Parser {
         })
     }
 
+    /// Whether the statement is `print value;` from scripts written when
+    /// `print` was a keyword. Now `print` is a function, so it only starts a
+    /// statement when a value follows it on the same line; `print(value)`
+    /// is a call.
+    fn is_print_statement(&self) -> bool {
+        if !self.check_word("print") {
+            return false;
+        }
+        match self.tokens.get(self.current + 1) {
+            Some(next) if !next.newline_before => matches!(
+                next.token_type,
+                TokenType::Identifier
+                    | TokenType::String
+                    | TokenType::Number
+                    | TokenType::BigInt
+                    | TokenType::Regex
+                    | TokenType::True
+                    | TokenType::False
+                    | TokenType::Nil
+                    | TokenType::This
+                    | TokenType::Super
+                    | TokenType::New
+                    | TokenType::Function
+                    | TokenType::Await
+                    | TokenType::Bang
+                    | TokenType::Minus
+                    | TokenType::LeftBracket
+                    | TokenType::LeftBrace
+                    | TokenType::HashBrace
+                    | TokenType::HashBracket
+            ),
+            _ => false,
+        }
+    }
+
     fn print_statement(&mut self) -> Result<Statement> {
         let value = self.expression()?;
-        self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+        self.consume_semicolon("Expect ';' after value.")?;
         Ok(Statement::Print { expression: value })
     }
 
     fn return_statement(&mut self) -> Result<Statement> {
         let keyword = self.previous();
         let mut value: Option<Expression> = None;
-        if !self.check(TokenType::Semicolon) {
+        if !self.check(TokenType::Semicolon) && !self.can_insert_semicolon() {
             value = Some(self.expression()?);
         }
-        self.consume(TokenType::Semicolon, "Expect ';' after return value.")?;
+        self.consume_semicolon("Expect ';' after return value.")?;
         Ok(Statement::Return { keyword, value })
     }
 
+    fn switch_statement(&mut self) -> Result<Statement> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'switch'.")?;
+        let discriminant = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after switch value.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before switch body.")?;
+
+        let mut cases = Vec::new();
+        let mut has_default = false;
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let test = if self._match(&[TokenType::Case]) {
+                Some(self.expression()?)
+            } else if self._match(&[TokenType::Default]) {
+                if has_default {
+                    self.error(
+                        self.previous(),
+                        "More than one default clause in switch statement.",
+                    );
+                }
+                has_default = true;
+                None
+            } else {
+                bail!(self.error(self.peek(), "Expect 'case' or 'default'."));
+            };
+            self.consume(TokenType::Colon, "Expect ':' after case.")?;
+
+            let mut body = Vec::new();
+            while !self.check(TokenType::Case)
+                && !self.check(TokenType::Default)
+                && !self.check(TokenType::RightBrace)
+                && !self.is_at_end()
+            {
+                if let Some(statement) = self.declaration() {
+                    body.push(statement);
+                }
+            }
+
+            cases.push(SwitchCase { test, body });
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after switch body.")?;
+        Ok(Statement::Switch {
+            discriminant,
+            cases,
+        })
+    }
+
+    fn throw_statement(&mut self) -> Result<Statement> {
+        let keyword = self.previous();
+        if self.at_line_break() {
+            bail!(self.error(keyword, "Illegal newline after 'throw'."));
+        }
+        let value = self.expression()?;
+        self.consume_semicolon("Expect ';' after thrown value.")?;
+        Ok(Statement::Throw { keyword, value })
+    }
+
+    fn try_statement(&mut self) -> Result<Statement> {
+        self.consume(TokenType::LeftBrace, "Expect '{' after 'try'.")?;
+        let try_block = self.block()?;
+
+        let mut catch_param: Option<Token> = None;
+        let mut catch_block: Option<Vec<Statement>> = None;
+        if self._match(&[TokenType::Catch]) {
+            if self._match(&[TokenType::LeftParen]) {
+                catch_param =
+                    Some(self.consume(TokenType::Identifier, "Expect catch parameter name.")?);
+                self.consume(TokenType::RightParen, "Expect ')' after catch parameter.")?;
+            }
+            self.consume(TokenType::LeftBrace, "Expect '{' before catch body.")?;
+            catch_block = Some(self.block()?);
+        }
+
+        let mut finally_block: Option<Vec<Statement>> = None;
+        if self._match(&[TokenType::Finally]) {
+            self.consume(TokenType::LeftBrace, "Expect '{' after 'finally'.")?;
+            finally_block = Some(self.block()?);
+        }
+
+        if catch_block.is_none() && finally_block.is_none() {
+            bail!(self.error(self.peek(), "Expect 'catch' or 'finally' after try block."));
+        }
+
+        Ok(Statement::Try {
+            try_block,
+            catch_param,
+            catch_block,
+            finally_block,
+        })
+    }
+
     fn let_declaration(&mut self) -> Result<Statement> {
-        let name = self.consume(TokenType::Identifier, "Expect variable name.")?;
+        let (target, annotation, initializer) = self.variable_declarator()?;
+        self.consume_semicolon("Expect ';' after variable declaration.")?;
+        Ok(Statement::Let {
+            target,
+            annotation,
+            initializer,
+        })
+    }
+
+    fn const_declaration(&mut self) -> Result<Statement> {
+        let (target, annotation, initializer) = self.variable_declarator()?;
+        self.consume_semicolon("Expect ';' after variable declaration.")?;
+        Ok(Statement::Const {
+            target,
+            annotation,
+            initializer,
+        })
+    }
+
+    fn variable_declarator(
+        &mut self,
+    ) -> Result<(Pattern, Option<TypeAnnotation>, Option<Expression>)> {
+        let target = self.binding_pattern("Expect variable name.")?;
+        let annotation = self.type_annotation()?;
+        let initializer = self.variable_initializer(&target)?;
+        Ok((target, annotation, initializer))
+    }
 
-        let mut initializer: Option<Expression> = None;
+    fn type_alias_declaration(&mut self) -> Result<Statement> {
+        let name = self.consume(TokenType::Identifier, "Expect type name.")?;
+        self.consume(TokenType::Equal, "Expect '=' after type name.")?;
+        let value = self.type_union()?;
+        self.consume_semicolon("Expect ';' after type.")?;
+        self.has_annotations = true;
+        Ok(Statement::TypeAlias { name, value })
+    }
+
+    fn interface_declaration(&mut self) -> Result<Statement> {
+        let name = self.consume(TokenType::Identifier, "Expect interface name.")?;
+        let mut extends = Vec::new();
+        if self._match(&[TokenType::Extends]) {
+            loop {
+                extends.push(self.consume(TokenType::Identifier, "Expect interface name.")?);
+                if !self._match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::LeftBrace, "Expect '{' before interface body.")?;
+        let members = self.type_members()?;
+        self.has_annotations = true;
+        Ok(Statement::Interface {
+            name,
+            extends,
+            members,
+        })
+    }
+
+    fn enum_declaration(&mut self) -> Result<Statement> {
+        let name = self.consume(TokenType::Identifier, "Expect enum name.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before enum body.")?;
+
+        let mut variants = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let name = self.consume(TokenType::Identifier, "Expect variant name.")?;
+            let mut fields = None;
+            if self._match(&[TokenType::LeftParen]) {
+                let mut names = Vec::new();
+                if !self.check(TokenType::RightParen) {
+                    loop {
+                        names.push(self.consume(TokenType::Identifier, "Expect field name.")?);
+                        if !self._match(&[TokenType::Comma]) {
+                            break;
+                        }
+                    }
+                }
+                self.consume(TokenType::RightParen, "Expect ')' after variant fields.")?;
+                fields = Some(names);
+            }
+            variants.push(Variant { name, fields });
+
+            if !self._match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after enum body.")?;
+        Ok(Statement::Enum { name, variants })
+    }
+
+    /// Parses the annotation after a `:`, if there is one.
+    fn type_annotation(&mut self) -> Result<Option<TypeAnnotation>> {
+        if !self._match(&[TokenType::Colon]) {
+            return Ok(None);
+        }
+        self.has_annotations = true;
+        Ok(Some(self.type_union()?))
+    }
+
+    fn type_union(&mut self) -> Result<TypeAnnotation> {
+        let mut types = vec![self.type_postfix()?];
+        while self._match(&[TokenType::Pipe]) {
+            types.push(self.type_postfix()?);
+        }
+        if types.len() == 1 {
+            return Ok(types.remove(0));
+        }
+        Ok(TypeAnnotation::Union { types })
+    }
+
+    fn type_postfix(&mut self) -> Result<TypeAnnotation> {
+        let mut annotation = self.type_primary()?;
+        while self.check(TokenType::LeftBracket) && self.check_next(TokenType::RightBracket) {
+            self.advance();
+            self.advance();
+            annotation = TypeAnnotation::Array {
+                element: Box::new(annotation),
+            };
+        }
+        Ok(annotation)
+    }
+
+    fn type_primary(&mut self) -> Result<TypeAnnotation> {
+        if self._match(&[TokenType::Identifier, TokenType::Nil]) {
+            return Ok(TypeAnnotation::Named {
+                name: self.previous(),
+            });
+        }
+        if self._match(&[TokenType::LeftBrace]) {
+            let brace = self.previous();
+            let members = self.type_members()?;
+            return Ok(TypeAnnotation::Object { brace, members });
+        }
+        if self._match(&[TokenType::LeftParen]) {
+            let paren = self.previous();
+            // `(name: T) => R` is a function type, anything else in
+            // parentheses is a grouping.
+            let is_function = self.check(TokenType::RightParen)
+                || (self.check(TokenType::Identifier) && self.check_next(TokenType::Colon));
+            if !is_function {
+                let annotation = self.type_union()?;
+                self.consume(TokenType::RightParen, "Expect ')' after type.")?;
+                return Ok(annotation);
+            }
+
+            let mut params = Vec::new();
+            while !self.check(TokenType::RightParen) && !self.is_at_end() {
+                self.consume(TokenType::Identifier, "Expect parameter name.")?;
+                self.consume(TokenType::Colon, "Expect ':' after parameter name.")?;
+                params.push(self.type_union()?);
+                if !self._match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+            self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+            self.consume(
+                TokenType::Arrow,
+                "Expect '=>' after function type parameters.",
+            )?;
+            let result = Box::new(self.type_union()?);
+            return Ok(TypeAnnotation::Function {
+                paren,
+                params,
+                result,
+            });
+        }
+
+        bail!(self.error(self.peek(), "Expect type."))
+    }
+
+    /// Parses the members of an object type or interface after the opening
+    /// brace, up to and including the closing one.
+    fn type_members(&mut self) -> Result<Vec<TypeMember>> {
+        let mut members = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let name = self.property_name("Expect member name.")?;
+            let optional = self._match(&[TokenType::Question]);
+            self.consume(TokenType::Colon, "Expect ':' after member name.")?;
+            let annotation = self.type_union()?;
+            members.push(TypeMember {
+                name,
+                annotation,
+                optional,
+            });
+            if !self._match(&[TokenType::Semicolon, TokenType::Comma]) && !self.at_line_break() {
+                break;
+            }
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after type members.")?;
+        Ok(members)
+    }
+
+    fn variable_initializer(&mut self, target: &Pattern) -> Result<Option<Expression>> {
         if self._match(&[TokenType::Equal]) {
-            initializer = Some(self.expression()?);
+            return Ok(Some(self.expression()?));
+        }
+        if !matches!(target, Pattern::Identifier { name: _ }) {
+            self.error(
+                self.peek(),
+                "Missing initializer in destructuring declaration.",
+            );
         }
+        Ok(None)
+    }
 
-        self.consume(
-            TokenType::Semicolon,
-            "Expect ';' after variable declaration.",
-        )?;
-        Ok(Statement::Let { name, initializer })
+    /// Parses what a declaration or parameter binds: a name, or an array or
+    /// object pattern that takes a value apart.
+    fn binding_pattern(&mut self, message: &str) -> Result<Pattern> {
+        if self._match(&[TokenType::LeftBracket]) {
+            return self.array_pattern();
+        }
+        if self._match(&[TokenType::LeftBrace]) {
+            return self.object_pattern();
+        }
+        let name = self.consume(TokenType::Identifier, message)?;
+        Ok(Pattern::Identifier { name })
     }
 
-    fn const_declaration(&mut self) -> Result<Statement> {
-        let name = self.consume(TokenType::Identifier, "Expect variable name.")?;
+    fn array_pattern(&mut self) -> Result<Pattern> {
+        let bracket = self.previous();
+        let mut elements = Vec::new();
+        let mut rest: Option<Box<Pattern>> = None;
+
+        while !self.check(TokenType::RightBracket) && !self.is_at_end() {
+            if self._match(&[TokenType::Comma]) {
+                elements.push(None);
+                continue;
+            }
+            if self._match(&[TokenType::Ellipsis]) {
+                rest = Some(Box::new(self.binding_pattern("Expect name after '...'.")?));
+                if self.check(TokenType::Comma) {
+                    self.error(self.peek(), "Rest element must be last element.");
+                }
+                break;
+            }
+
+            let target = self.binding_pattern("Expect name in array pattern.")?;
+            let default = self.pattern_default()?;
+            elements.push(Some(PatternElement { target, default }));
+            if !self._match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RightBracket, "Expect ']' after array pattern.")?;
+        Ok(Pattern::Array {
+            bracket,
+            elements,
+            rest,
+        })
+    }
+
+    fn object_pattern(&mut self) -> Result<Pattern> {
+        let brace = self.previous();
+        let mut properties = Vec::new();
+        let mut rest: Option<Box<Pattern>> = None;
 
-        let mut initializer: Option<Expression> = None;
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            if self._match(&[TokenType::Ellipsis]) {
+                let name = self.consume(TokenType::Identifier, "Expect name after '...'.")?;
+                rest = Some(Box::new(Pattern::Identifier { name }));
+                if self.check(TokenType::Comma) {
+                    self.error(self.peek(), "Rest element must be last element.");
+                }
+                break;
+            }
+
+            let key = self.property_key()?;
+            let target = if self._match(&[TokenType::Colon]) {
+                self.binding_pattern("Expect name in object pattern.")?
+            } else {
+                match &key {
+                    PropertyKey::Named { name: _, token }
+                        if token.token_type == TokenType::Identifier =>
+                    {
+                        Pattern::Identifier {
+                            name: token.clone(),
+                        }
+                    }
+                    _ => bail!(self.error(self.peek(), "Expect ':' after property name.")),
+                }
+            };
+            let default = self.pattern_default()?;
+            properties.push(PatternProperty {
+                key,
+                target,
+                default,
+            });
+            if !self._match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after object pattern.")?;
+        Ok(Pattern::Object {
+            brace,
+            properties,
+            rest,
+        })
+    }
+
+    fn pattern_default(&mut self) -> Result<Option<Expression>> {
         if self._match(&[TokenType::Equal]) {
-            initializer = Some(self.expression()?);
+            return Ok(Some(self.assignment()?));
         }
+        Ok(None)
+    }
 
-        self.consume(
-            TokenType::Semicolon,
-            "Expect ';' after variable declaration.",
-        )?;
-        Ok(Statement::Const { name, initializer })
+    /// Reinterprets the array or object literal on the left of `=` as the
+    /// pattern it spells.
+    fn assignment_pattern(&mut self, expr: Expression) -> Result<Pattern> {
+        match expr {
+            Expression::Variable { name } => Ok(Pattern::Identifier { name }),
+            Expression::Get { .. } | Expression::Index { .. } => Ok(Pattern::Member {
+                expression: Box::new(expr),
+            }),
+            Expression::Array { bracket, elements } => {
+                let mut targets = Vec::new();
+                let mut rest: Option<Box<Pattern>> = None;
+                let count = elements.len();
+                for (index, element) in elements.into_iter().enumerate() {
+                    match element {
+                        None => targets.push(None),
+                        Some(Expression::Spread {
+                            ellipsis,
+                            expression,
+                        }) => {
+                            if index + 1 < count {
+                                bail!(self.error(ellipsis, "Rest element must be last element."));
+                            }
+                            rest = Some(Box::new(self.assignment_pattern(*expression)?));
+                        }
+                        Some(element) => {
+                            let (target, default) = self.pattern_with_default(element)?;
+                            targets.push(Some(PatternElement { target, default }));
+                        }
+                    }
+                }
+                Ok(Pattern::Array {
+                    bracket,
+                    elements: targets,
+                    rest,
+                })
+            }
+            Expression::Object { brace, properties } => {
+                let mut targets = Vec::new();
+                for property in properties {
+                    let (target, default) = self.pattern_with_default(property.value)?;
+                    targets.push(PatternProperty {
+                        key: property.key,
+                        target,
+                        default,
+                    });
+                }
+                Ok(Pattern::Object {
+                    brace,
+                    properties: targets,
+                    rest: None,
+                })
+            }
+            _ => bail!(self.error(self.previous(), "Invalid destructuring assignment target.")),
+        }
+    }
+
+    /// Splits an element such as `a = 1` into its target and default.
+    fn pattern_with_default(&mut self, expr: Expression) -> Result<(Pattern, Option<Expression>)> {
+        match expr {
+            Expression::Assign { name, value } => Ok((Pattern::Identifier { name }, Some(*value))),
+            Expression::Set {
+                object,
+                name,
+                value,
+            } => Ok((
+                Pattern::Member {
+                    expression: Box::new(Expression::Get {
+                        object,
+                        name,
+                        optional: false,
+                    }),
+                },
+                Some(*value),
+            )),
+            Expression::SetIndex {
+                object,
+                bracket,
+                index,
+                value,
+            } => Ok((
+                Pattern::Member {
+                    expression: Box::new(Expression::Index {
+                        object,
+                        bracket,
+                        index,
+                        optional: false,
+                    }),
+                },
+                Some(*value),
+            )),
+            Expression::AssignPattern {
+                target,
+                equals: _,
+                value,
+            } => Ok((target, Some(*value))),
+            expr => Ok((self.assignment_pattern(expr)?, None)),
+        }
     }
 
-    fn while_statement(&mut self) -> Result<Statement> {
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Statement> {
         self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
         let condition = self.expression()?;
         self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
         let body = Box::new(self.statement()?);
 
-        Ok(Statement::While { condition, body })
+        Ok(Statement::While {
+            condition,
+            body,
+            increment: None,
+            label,
+        })
     }
 
     fn expression_statement(&mut self) -> Result<Statement> {
         let expr = self.expression()?;
-        self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+        self.consume_semicolon("Expect ';' after expression.")?;
         Ok(Statement::Expression { expression: expr })
     }
 
-    fn function(&mut self, kind: &str) -> Result<Statement> {
-        let name = self.consume(TokenType::Identifier, &format!("Expect {} name.", kind))?;
-        self.consume(
-            TokenType::LeftParen,
-            &format!("Expect '(' after {} name.", kind),
-        )?;
-        let mut parameters = Vec::new();
+    fn import_declaration(&mut self) -> Result<Statement> {
+        let keyword = self.previous();
+        let mut default: Option<Token> = None;
+        let mut namespace: Option<Token> = None;
+        let mut specifiers = Vec::new();
+
+        if !self.check(TokenType::String) {
+            if self._match(&[TokenType::Identifier]) {
+                default = Some(self.previous());
+            }
+            if default.is_none() || self._match(&[TokenType::Comma]) {
+                if self._match(&[TokenType::Star]) {
+                    self.consume_word("as", "Expect 'as' after '*'.")?;
+                    namespace =
+                        Some(self.consume(TokenType::Identifier, "Expect namespace name.")?);
+                } else {
+                    self.consume(TokenType::LeftBrace, "Expect '{' or '*' in import.")?;
+                    specifiers = self.module_specifiers(true)?;
+                }
+            }
+            self.consume_word("from", "Expect 'from' after import specifiers.")?;
+        }
+
+        let source = self.consume(TokenType::String, "Expect module path.")?;
+        let path = self.literal_value(&source);
+        self.consume_semicolon("Expect ';' after import.")?;
+        Ok(Statement::Import {
+            keyword,
+            source,
+            path,
+            default,
+            namespace,
+            specifiers,
+        })
+    }
+
+    fn export_declaration(&mut self) -> Result<Statement> {
+        let keyword = self.previous();
+
+        if self._match(&[TokenType::Default]) {
+            // A named function is exported as a declaration, whereas an
+            // anonymous one is just a value.
+            let is_async = self.check(TokenType::Async);
+            let function = usize::from(is_async);
+            let name = match self.check_at(function + 1, TokenType::Star) {
+                true => function + 2,
+                false => function + 1,
+            };
+            if self.check_at(function, TokenType::Function)
+                && self.check_at(name, TokenType::Identifier)
+            {
+                for _ in 0..=function {
+                    self.advance();
+                }
+                return Ok(Statement::Export {
+                    keyword,
+                    declaration: Box::new(self.function("function", is_async)?),
+                    default: true,
+                });
+            }
+            if self._match(&[TokenType::Class]) {
+                return Ok(Statement::Export {
+                    keyword,
+                    declaration: Box::new(self.class_declaration()?),
+                    default: true,
+                });
+            }
+
+            let value = self.assignment()?;
+            // Like a declaration, `export default function() {}` needs no
+            // semicolon.
+            if let Expression::Function { arrow: false, .. } = value {
+                self._match(&[TokenType::Semicolon]);
+            } else {
+                self.consume_semicolon("Expect ';' after export.")?;
+            }
+            return Ok(Statement::ExportDefault { keyword, value });
+        }
+
+        if self._match(&[TokenType::LeftBrace]) {
+            let specifiers = self.module_specifiers(false)?;
+            self.consume_semicolon("Expect ';' after export.")?;
+            return Ok(Statement::ExportNames {
+                keyword,
+                specifiers,
+            });
+        }
+
+        let declaration = if self._match(&[TokenType::Class]) {
+            self.class_declaration()?
+        } else if self._match(&[TokenType::Function]) {
+            self.function("function", false)?
+        } else if self.check(TokenType::Async) && self.check_next(TokenType::Function) {
+            self.advance();
+            self.advance();
+            self.function("function", true)?
+        } else if self._match(&[TokenType::Let]) {
+            self.let_declaration()?
+        } else if self._match(&[TokenType::Const]) {
+            self.const_declaration()?
+        } else if self.check_word("enum") && self.check_next(TokenType::Identifier) {
+            self.advance();
+            self.enum_declaration()?
+        } else {
+            bail!(self.error(self.peek(), "Expect declaration after 'export'."))
+        };
+        Ok(Statement::Export {
+            keyword,
+            declaration: Box::new(declaration),
+            default: false,
+        })
+    }
+
+    /// Parses `a, b as c }` after the opening brace of an import or export
+    /// list. Imported names, like property names, may be reserved words.
+    fn module_specifiers(&mut self, is_import: bool) -> Result<Vec<ModuleSpecifier>> {
+        let mut specifiers = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let name = match is_import {
+                true => self.property_name("Expect imported name.")?,
+                false => self.consume(TokenType::Identifier, "Expect exported name.")?,
+            };
+            let mut alias = name.clone();
+            if self.check_word("as") {
+                self.advance();
+                alias = match is_import {
+                    true => self.consume(TokenType::Identifier, "Expect local name after 'as'.")?,
+                    false => self.property_name("Expect exported name after 'as'.")?,
+                };
+            }
+            specifiers.push(ModuleSpecifier { name, alias });
+
+            if !self._match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after module specifiers.")?;
+        Ok(specifiers)
+    }
+
+    fn class_declaration(&mut self) -> Result<Statement> {
+        let name = self.consume(TokenType::Identifier, "Expect class name.")?;
+
+        let mut superclass: Option<Expression> = None;
+        if self._match(&[TokenType::Extends]) {
+            superclass = Some(self.call()?);
+        }
+
+        self.consume(TokenType::LeftBrace, "Expect '{' before class body.")?;
+        let mut methods = Vec::new();
+        let mut fields = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            if self._match(&[TokenType::Semicolon]) {
+                continue;
+            }
+            self.class_member(&mut methods, &mut fields)?;
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after class body.")?;
+
+        Ok(Statement::Class {
+            name,
+            superclass,
+            methods,
+            fields,
+        })
+    }
+
+    /// Whether the current token is used as a modifier such as `get` or
+    /// `private` rather than as the name of a member.
+    fn is_modifier(&self, modifiers: &[&str]) -> bool {
+        modifiers.contains(&self.peek().lexeme.as_str())
+            && !self.check_next(TokenType::LeftParen)
+            && !self.check_next(TokenType::Equal)
+            && !self.check_next(TokenType::Semicolon)
+            && !self.check_next(TokenType::Colon)
+    }
+
+    fn class_member(&mut self, methods: &mut Vec<Method>, fields: &mut Vec<Field>) -> Result<()> {
+        let mut access = Access::Public;
+        if self.is_modifier(&["public", "private", "protected"]) {
+            access = match self.advance().lexeme.as_str() {
+                "private" => Access::Private,
+                "protected" => Access::Protected,
+                _ => Access::Public,
+            };
+        }
+
+        let is_static = self._match(&[TokenType::Static]);
+
+        let mut kind = MethodKind::Method;
+        if self.is_modifier(&["get", "set"]) {
+            kind = match self.advance().lexeme.as_str() {
+                "get" => MethodKind::Getter,
+                _ => MethodKind::Setter,
+            };
+        }
+        let is_async = kind == MethodKind::Method && self._match(&[TokenType::Async]);
+        let is_generator = kind == MethodKind::Method && self.generator_star(is_async);
+
+        let mut computed: Option<Expression> = None;
+        let overloads = kind == MethodKind::Method
+            && !is_async
+            && !is_generator
+            && self.check_word("operator")
+            && self
+                .tokens
+                .get(self.current + 1)
+                .is_some_and(|next| operator_method(next.token_type).is_some());
+        let name = if overloads {
+            let keyword = self.advance();
+            let operator = self.advance();
+            if is_static {
+                self.error(keyword, "Operators cannot be static.");
+            }
+            Token {
+                token_type: TokenType::Identifier,
+                lexeme: operator_method(operator.token_type).unwrap().to_string(),
+                ..operator
+            }
+        } else if self._match(&[TokenType::LeftBracket]) {
+            let bracket = self.previous();
+            computed = Some(self.assignment()?);
+            self.consume(
+                TokenType::RightBracket,
+                "Expect ']' after computed member name.",
+            )?;
+            if !self.check(TokenType::LeftParen) {
+                bail!(self.error(bracket, "Expect '(' after computed member name."));
+            }
+            bracket
+        } else {
+            self.member_name("Expect member name.")?
+        };
+        if access != Access::Public && name.token_type == TokenType::PrivateName {
+            self.error(
+                name.clone(),
+                "An accessibility modifier cannot be used with a private identifier.",
+            );
+        }
+
+        if (is_async || is_generator) && !self.check(TokenType::LeftParen) {
+            bail!(self.error(self.peek(), "Expect '(' after method name."));
+        }
+        if kind == MethodKind::Method && !self.check(TokenType::LeftParen) {
+            let annotation = self.type_annotation()?;
+            let mut initializer: Option<Expression> = None;
+            if self._match(&[TokenType::Equal]) {
+                initializer = Some(self.expression()?);
+            }
+            self.consume_semicolon("Expect ';' after field declaration.")?;
+            fields.push(Field {
+                name,
+                annotation,
+                initializer,
+                is_static,
+                access,
+            });
+            return Ok(());
+        }
+
+        self.consume(TokenType::LeftParen, "Expect '(' after method name.")?;
+        let params = self.parameters()?;
+        match kind {
+            MethodKind::Getter if !params.is_empty() => {
+                self.error(name.clone(), "Getter must not have parameters.");
+            }
+            MethodKind::Setter if params.len() != 1 => {
+                self.error(name.clone(), "Setter must have exactly one parameter.");
+            }
+            _ if overloads
+                && (!(1..=2).contains(&params.len()) || params.iter().any(|param| param.rest)) =>
+            {
+                self.error(
+                    name.clone(),
+                    "Operator must have one parameter and an optional 'reversed' flag.",
+                );
+            }
+            _ => {}
+        }
+
+        let return_type = self.type_annotation()?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before method body.")?;
+        let body = self.block()?;
+        methods.push(Method {
+            name,
+            computed,
+            kind,
+            is_static,
+            access,
+            is_generator,
+            is_async,
+            params,
+            return_type,
+            body,
+        });
+        Ok(())
+    }
+
+    fn function(&mut self, kind: &str, is_async: bool) -> Result<Statement> {
+        let is_generator = self.generator_star(is_async);
+        let name = self.consume(TokenType::Identifier, &format!("Expect {} name.", kind))?;
+        self.consume(
+            TokenType::LeftParen,
+            &format!("Expect '(' after {} name.", kind),
+        )?;
+        let parameters = self.parameters()?;
+        let return_type = self.type_annotation()?;
+        self.consume(
+            TokenType::LeftBrace,
+            &format!("Expect '{{' before {} body.", kind),
+        )?;
+        let body = self.block()?;
+        Ok(Statement::Function {
+            name,
+            params: parameters,
+            return_type,
+            body,
+            is_generator,
+            is_async,
+        })
+    }
+
+    /// Matches the `*` that makes a function a generator.
+    fn generator_star(&mut self, is_async: bool) -> bool {
+        let is_generator = self._match(&[TokenType::Star]);
+        if is_generator && is_async {
+            self.error(self.previous(), "Async generators are not supported.");
+        }
+        is_generator
+    }
+
+    fn function_expression(&mut self, is_async: bool) -> Result<Expression> {
+        let is_generator = self.generator_star(is_async);
+        let mut name: Option<Token> = None;
+        if self.check(TokenType::Identifier) {
+            name = Some(self.advance());
+        }
+        self.consume(TokenType::LeftParen, "Expect '(' after 'function'.")?;
+        let params = self.parameters()?;
+        let return_type = self.type_annotation()?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before function body.")?;
+        let body = self.block()?;
+        Ok(Expression::Function {
+            name,
+            params,
+            return_type,
+            body,
+            arrow: false,
+            is_generator,
+            is_async,
+        })
+    }
+
+    /// Parses the parameters and return type of an arrow function up to and
+    /// including the `=>`, or returns `None` when the current token does not
+    /// start one.
+    fn arrow_parameters(&mut self) -> Result<Option<(Vec<Parameter>, Option<TypeAnnotation>)>> {
+        if self.check(TokenType::Identifier) && self.check_next(TokenType::Arrow) {
+            let name = self.advance();
+            self.advance();
+            let param = Parameter {
+                target: Pattern::Identifier { name },
+                annotation: None,
+                default: None,
+                rest: false,
+            };
+            return Ok(Some((vec![param], None)));
+        }
+        if self.check(TokenType::LeftParen) && self.is_arrow_parameters() {
+            self.advance();
+            let params = self.parameters()?;
+            let return_type = self.type_annotation()?;
+            self.consume(TokenType::Arrow, "Expect '=>' after parameters.")?;
+            return Ok(Some((params, return_type)));
+        }
+        Ok(None)
+    }
+
+    fn arrow_function(
+        &mut self,
+        (params, return_type): (Vec<Parameter>, Option<TypeAnnotation>),
+        is_async: bool,
+    ) -> Result<Expression> {
+        let arrow = self.previous();
+        let body = if self._match(&[TokenType::LeftBrace]) {
+            self.block()?
+        } else {
+            let value = self.assignment()?;
+            vec![Statement::Return {
+                keyword: arrow,
+                value: Some(value),
+            }]
+        };
+        Ok(Expression::Function {
+            name: None,
+            params,
+            return_type,
+            body,
+            arrow: true,
+            is_generator: false,
+            is_async,
+        })
+    }
+
+    /// Parses a parameter list after its opening parenthesis, up to and
+    /// including the closing one.
+    fn parameters(&mut self) -> Result<Vec<Parameter>> {
+        let mut parameters: Vec<Parameter> = Vec::new();
         if !self.check(TokenType::RightParen) {
             loop {
                 if parameters.len() >= 255 {
                     self.error(self.peek(), "Can't have more than 255 parameters.");
                 }
-                parameters.push(self.consume(TokenType::Identifier, "Expect parameter name.")?);
+
+                let rest = self._match(&[TokenType::Ellipsis]);
+                let target = self.binding_pattern("Expect parameter name.")?;
+                let annotation = self.type_annotation()?;
+                let mut default: Option<Expression> = None;
+                if self._match(&[TokenType::Equal]) {
+                    if rest {
+                        self.error(self.previous(), "Rest parameter may not have a default.");
+                    }
+                    default = Some(self.assignment()?);
+                }
+                parameters.push(Parameter {
+                    target,
+                    annotation,
+                    default,
+                    rest,
+                });
+
                 if !self._match(&[TokenType::Comma]) {
                     break;
                 }
+                if rest {
+                    self.error(
+                        self.previous(),
+                        "Rest parameter must be last formal parameter.",
+                    );
+                }
             }
         }
         self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
-        self.consume(
-            TokenType::LeftBrace,
-            &format!("Expect '{{' before {} body.", kind),
-        )?;
-        let body = self.block()?;
-        Ok(Statement::Function {
-            name,
-            params: parameters,
-            body,
-        })
+        Ok(parameters)
+    }
+
+    /// Looks past the parenthesis at the current position to tell an arrow
+    /// function's parameter list apart from a grouping.
+    fn is_arrow_parameters(&self) -> bool {
+        let mut depth = 0;
+        for (index, token) in self.tokens.iter().enumerate().skip(self.current) {
+            match token.token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return match self.tokens.get(index + 1) {
+                            Some(next) if next.token_type == TokenType::Arrow => true,
+                            Some(next) if next.token_type == TokenType::Colon => {
+                                self.is_return_type(index + 2)
+                            }
+                            _ => false,
+                        };
+                    }
+                }
+                TokenType::EOF => return false,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn is_match_expression(&self) -> bool {
+        if !self.check_next(TokenType::LeftParen) {
+            return false;
+        }
+        let mut depth = 0;
+        for (index, token) in self.tokens.iter().enumerate().skip(self.current + 1) {
+            match token.token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return matches!(
+                            self.tokens.get(index + 1),
+                            Some(next) if next.token_type == TokenType::LeftBrace
+                        );
+                    }
+                }
+                TokenType::EOF => return false,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Whether the tokens from `start` on are a return type annotation
+    /// followed by the `=>` of an arrow function.
+    fn is_return_type(&self, start: usize) -> bool {
+        let mut depth = 0;
+        for token in self.tokens.iter().skip(start) {
+            match token.token_type {
+                TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace => depth += 1,
+                TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => {
+                    if depth == 0 {
+                        return false;
+                    }
+                    depth -= 1;
+                }
+                TokenType::Arrow if depth == 0 => return true,
+                TokenType::Comma | TokenType::Semicolon if depth == 0 => return false,
+                TokenType::Identifier
+                | TokenType::Nil
+                | TokenType::Pipe
+                | TokenType::Colon
+                | TokenType::Question
+                | TokenType::Arrow
+                | TokenType::Comma
+                | TokenType::Semicolon => {}
+                _ => return false,
+            }
+        }
+        false
     }
 
     fn block(&mut self) -> Result<Vec<Statement>> {
@@ -274,25 +1475,91 @@ impl Parser {
     }
 
     fn assignment(&mut self) -> Result<Expression> {
+        if self._match(&[TokenType::Yield]) {
+            return self.yield_expression();
+        }
+
         let expr = self.or()?;
 
         if self._match(&[TokenType::Equal]) {
             let equals = self.previous();
             let value = self.assignment()?;
 
-            if let Expression::Variable { name } = expr {
-                return Ok(Expression::Assign {
+            match expr {
+                Expression::Variable { name } => {
+                    return Ok(Expression::Assign {
+                        name,
+                        value: Box::new(value),
+                    });
+                }
+                Expression::Get {
+                    object,
                     name,
-                    value: Box::new(value),
-                });
+                    optional: _,
+                } => {
+                    return Ok(Expression::Set {
+                        object,
+                        name,
+                        value: Box::new(value),
+                    });
+                }
+                Expression::Index {
+                    object,
+                    bracket,
+                    index,
+                    optional: _,
+                } => {
+                    return Ok(Expression::SetIndex {
+                        object,
+                        bracket,
+                        index,
+                        value: Box::new(value),
+                    });
+                }
+                Expression::Array { .. } | Expression::Object { .. } => {
+                    return Ok(Expression::AssignPattern {
+                        target: self.assignment_pattern(expr)?,
+                        equals,
+                        value: Box::new(value),
+                    });
+                }
+                _ => {}
             }
 
             self.error(equals, "Invalid assignment target.");
+            return Ok(value);
         }
 
         Ok(expr)
     }
 
+    fn yield_expression(&mut self) -> Result<Expression> {
+        let keyword = self.previous();
+        let delegate = self._match(&[TokenType::Star]);
+
+        // A bare `yield` is ended by whatever may follow an expression.
+        let mut value: Option<Box<Expression>> = None;
+        if delegate
+            || !matches!(
+                self.peek().token_type,
+                TokenType::RightParen
+                    | TokenType::RightBracket
+                    | TokenType::RightBrace
+                    | TokenType::Comma
+                    | TokenType::Semicolon
+                    | TokenType::Colon
+                    | TokenType::EOF
+            ) && !self.at_line_break()
+        {
+            value = Some(Box::new(self.assignment()?));
+        }
+        Ok(Expression::Yield {
+            keyword,
+            value,
+            delegate,
+        })
+    }
+
     fn or(&mut self) -> Result<Expression> {
         let mut expr = self.and()?;
 
@@ -328,7 +1595,12 @@ impl Parser {
     fn equality(&mut self) -> Result<Expression> {
         let mut expr = self.comparison()?;
 
-        while self._match(&[TokenType::BangEqual, TokenType::EqualEqual]) {
+        while self._match(&[
+            TokenType::BangEqual,
+            TokenType::BangEqualEqual,
+            TokenType::EqualEqual,
+            TokenType::EqualEqualEqual,
+        ]) {
             let operator = self.previous();
             let right = self.comparison()?;
             expr = Expression::Binary {
@@ -379,11 +1651,11 @@ impl Parser {
     }
 
     fn factor(&mut self) -> Result<Expression> {
-        let mut expr = self.unary()?;
+        let mut expr = self.exponent()?;
 
-        while self._match(&[TokenType::Slash, TokenType::Star]) {
+        while self._match(&[TokenType::Slash, TokenType::Star, TokenType::Percent]) {
             let operator = self.previous();
-            let right = self.unary()?;
+            let right = self.exponent()?;
             expr = Expression::Binary {
                 left: Box::new(expr),
                 operator,
@@ -394,7 +1666,38 @@ impl Parser {
         Ok(expr)
     }
 
+    /// `**` groups to the right, so `2 ** 3 ** 2` is `2 ** 9`.
+    fn exponent(&mut self) -> Result<Expression> {
+        let expr = self.unary()?;
+
+        if self._match(&[TokenType::StarStar]) {
+            let operator = self.previous();
+            if let Expression::Unary { .. } = expr {
+                bail!(self.error(
+                    operator,
+                    "Unary operator before '**' must be in parentheses."
+                ));
+            }
+            let right = self.exponent()?;
+            return Ok(Expression::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        Ok(expr)
+    }
+
     fn unary(&mut self) -> Result<Expression> {
+        if self._match(&[TokenType::Await]) {
+            let keyword = self.previous();
+            let value = self.unary()?;
+            return Ok(Expression::Await {
+                keyword,
+                value: Box::new(value),
+            });
+        }
         if self._match(&[TokenType::Bang, TokenType::Minus]) {
             let operator = self.previous();
             let right = self.unary()?;
@@ -407,43 +1710,254 @@ impl Parser {
         self.call()
     }
 
-    fn finish_call(&mut self, callee: Expression) -> Result<Expression> {
+    fn finish_call(&mut self, callee: Expression, optional: bool) -> Result<Expression> {
+        let arguments = self.arguments()?;
+        Ok(Expression::Call {
+            callee: Box::new(callee),
+            paren: self.previous(),
+            arguments,
+            optional,
+        })
+    }
+
+    /// Parses an argument list after its opening parenthesis, up to and
+    /// including the closing one.
+    fn arguments(&mut self) -> Result<Vec<Expression>> {
         let mut arguments = Vec::new();
         if !self.check(TokenType::RightParen) {
             loop {
                 if arguments.len() >= 255 {
                     self.error(self.peek(), "Can't have more than 255 arguments.");
                 }
-                arguments.push(self.expression()?);
+                arguments.push(self.element()?);
                 if !self._match(&[TokenType::Comma]) {
                     break;
                 }
             }
         }
 
-        let paren = self.consume(TokenType::RightParen, "Expect ')' after arguments.")?;
+        self.consume(TokenType::RightParen, "Expect ')' after arguments.")?;
+        Ok(arguments)
+    }
 
-        Ok(Expression::Call {
+    /// Parses `new Callee(arguments)`, where the callee is a primary
+    /// expression followed by property accesses and the arguments are
+    /// optional.
+    fn new_expression(&mut self) -> Result<Expression> {
+        let keyword = self.previous();
+        let mut callee = if self._match(&[TokenType::New]) {
+            self.new_expression()?
+        } else {
+            self.primary()?
+        };
+        while self._match(&[TokenType::Dot]) {
+            let name = self.member_name("Expect property name after '.'.")?;
+            callee = Expression::Get {
+                object: Box::new(callee),
+                name,
+                optional: false,
+            };
+        }
+
+        let mut arguments = Vec::new();
+        if self._match(&[TokenType::LeftParen]) {
+            arguments = self.arguments()?;
+        }
+        Ok(Expression::New {
+            keyword,
             callee: Box::new(callee),
-            paren,
             arguments,
         })
     }
 
+    /// An argument or array element, either of which may be spread.
+    fn element(&mut self) -> Result<Expression> {
+        if self._match(&[TokenType::Ellipsis]) {
+            let ellipsis = self.previous();
+            return Ok(Expression::Spread {
+                ellipsis,
+                expression: Box::new(self.expression()?),
+            });
+        }
+        self.expression()
+    }
+
     fn call(&mut self) -> Result<Expression> {
-        let mut expr = self.primary()?;
+        let mut expr = if self._match(&[TokenType::New]) {
+            self.new_expression()?
+        } else {
+            self.primary()?
+        };
 
+        // Whether the chain so far has a `?.` in it, which makes the whole
+        // chain short-circuit rather than just the link after it.
+        let mut chain = false;
         loop {
+            let optional = self._match(&[TokenType::QuestionDot]);
+            chain |= optional;
             if self._match(&[TokenType::LeftParen]) {
-                expr = self.finish_call(expr)?;
+                expr = self.finish_call(expr, optional)?;
+            } else if self._match(&[TokenType::LeftBracket]) {
+                let bracket = self.previous();
+                let index = self.expression()?;
+                self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+                expr = Expression::Index {
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                    optional,
+                };
+            } else if optional || self._match(&[TokenType::Dot]) {
+                let name = self.member_name("Expect property name after '.'.")?;
+                expr = Expression::Get {
+                    object: Box::new(expr),
+                    name,
+                    optional,
+                };
             } else {
                 break;
             }
         }
 
+        if chain {
+            expr = Expression::OptionalChain {
+                expression: Box::new(expr),
+            };
+        }
         Ok(expr)
     }
 
+    /// Property names may be any identifier, reserved words included.
+    /// A property name that may also be a `#private` one, as accepted after
+    /// `.` and in class bodies.
+    fn member_name(&mut self, message: &str) -> Result<Token> {
+        if self._match(&[TokenType::PrivateName]) {
+            return Ok(self.previous());
+        }
+        self.property_name(message)
+    }
+
+    fn property_name(&mut self, message: &str) -> Result<Token> {
+        let token = self.peek();
+        let is_word = token.token_type != TokenType::String
+            && token.token_type != TokenType::Number
+            && token
+                .lexeme
+                .starts_with(|c: char| c.is_alphabetic() || c == '_');
+        if is_word {
+            let mut token = self.advance();
+            token.token_type = TokenType::Identifier;
+            return Ok(token);
+        }
+
+        bail!(self.error(token, message))
+    }
+
+    fn array_literal(&mut self) -> Result<Expression> {
+        let bracket = self.previous();
+        let mut elements = Vec::new();
+
+        while !self.check(TokenType::RightBracket) && !self.is_at_end() {
+            if self._match(&[TokenType::Comma]) {
+                elements.push(None);
+                continue;
+            }
+
+            elements.push(Some(self.element()?));
+            if !self._match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RightBracket, "Expect ']' after array elements.")?;
+        Ok(Expression::Array { bracket, elements })
+    }
+
+    /// Parses an identifier, string, number or `[computed]` property key.
+    fn property_key(&mut self) -> Result<PropertyKey> {
+        if self._match(&[TokenType::LeftBracket]) {
+            let key = self.assignment()?;
+            self.consume(
+                TokenType::RightBracket,
+                "Expect ']' after computed property name.",
+            )?;
+            Ok(PropertyKey::Computed(key))
+        } else if self._match(&[TokenType::String]) {
+            let token = self.previous();
+            Ok(PropertyKey::Named {
+                name: self.literal_value(&token),
+                token,
+            })
+        } else if self._match(&[TokenType::Number]) {
+            let token = self.previous();
+            let number: f64 = self.literal_value(&token).parse().unwrap_or(f64::NAN);
+            Ok(PropertyKey::Named {
+                name: format_number(number),
+                token,
+            })
+        } else {
+            let token = self.property_name("Expect property name.")?;
+            Ok(PropertyKey::Named {
+                name: token.lexeme.clone(),
+                token,
+            })
+        }
+    }
+
+    fn object_literal(&mut self) -> Result<Expression> {
+        let brace = self.previous();
+        let mut properties = Vec::new();
+
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let is_async = self._match(&[TokenType::Async]);
+            let is_generator = self.generator_star(is_async);
+            let key = self.property_key()?;
+            if (is_async || is_generator) && !self.check(TokenType::LeftParen) {
+                bail!(self.error(self.peek(), "Expect '(' after method name."));
+            }
+            let value = if self._match(&[TokenType::LeftParen]) {
+                let name = match &key {
+                    PropertyKey::Named { name: _, token } => Some(token.clone()),
+                    PropertyKey::Computed(_) => None,
+                };
+                let params = self.parameters()?;
+                let return_type = self.type_annotation()?;
+                self.consume(TokenType::LeftBrace, "Expect '{' before method body.")?;
+                let body = self.block()?;
+                Expression::Function {
+                    name,
+                    params,
+                    return_type,
+                    body,
+                    arrow: false,
+                    is_generator,
+                    is_async,
+                }
+            } else if self._match(&[TokenType::Colon]) {
+                self.assignment()?
+            } else {
+                match &key {
+                    PropertyKey::Named { name: _, token }
+                        if token.token_type == TokenType::Identifier =>
+                    {
+                        Expression::Variable {
+                            name: token.clone(),
+                        }
+                    }
+                    _ => bail!(self.error(self.peek(), "Expect ':' after property name.")),
+                }
+            };
+
+            properties.push(Property { key, value });
+            if !self._match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after object literal.")?;
+        Ok(Expression::Object { brace, properties })
+    }
+
     fn primary(&mut self) -> Result<Expression> {
         if self._match(&[TokenType::False]) {
             return Ok(Expression::Literal {
@@ -459,11 +1973,105 @@ impl Parser {
             return Ok(Expression::Literal { value: None });
         }
 
-        if self._match(&[TokenType::Number, TokenType::String]) {
+        if self._match(&[TokenType::Number]) {
+            let value = self.literal_value(&self.previous());
+            return Ok(Expression::Literal {
+                value: Some(Literal::Number(value.parse().unwrap_or(f64::NAN))),
+            });
+        }
+        if self._match(&[TokenType::Regex]) {
+            let token = self.previous();
+            let flags = token
+                .literal
+                .as_ref()
+                .and_then(|literal| literal.get("flags"))
+                .cloned()
+                .unwrap_or_default();
+            return match Regex::new(&self.literal_value(&token), &flags) {
+                Ok(regex) => Ok(Expression::Regex {
+                    token,
+                    regex: Rc::new(regex),
+                }),
+                Err(message) => bail!(self.error(token, &message)),
+            };
+        }
+        if self._match(&[TokenType::BigInt]) {
+            let value = self.literal_value(&self.previous());
+            return Ok(Expression::Literal {
+                value: Some(Literal::BigInt(BigInt::parse(&value).unwrap().to_string())),
+            });
+        }
+        if self._match(&[TokenType::String]) {
+            let value = self.literal_value(&self.previous());
             return Ok(Expression::Literal {
-                value: Some(Literal::Map(self.previous().literal.unwrap())),
+                value: Some(Literal::String(value)),
+            });
+        }
+
+        if self._match(&[TokenType::This]) {
+            return Ok(Expression::This {
+                keyword: self.previous(),
+            });
+        }
+        if self._match(&[TokenType::Super]) {
+            let keyword = self.previous();
+            if self.check(TokenType::LeftParen) {
+                return Ok(Expression::Super {
+                    keyword,
+                    method: None,
+                });
+            }
+            self.consume(TokenType::Dot, "Expect '.' or '(' after 'super'.")?;
+            let method = self.property_name("Expect superclass method name.")?;
+            return Ok(Expression::Super {
+                keyword,
+                method: Some(method),
+            });
+        }
+
+        if self._match(&[TokenType::Function]) {
+            return self.function_expression(false);
+        }
+        if self._match(&[TokenType::Async]) {
+            if self._match(&[TokenType::Function]) {
+                return self.function_expression(true);
+            }
+            return match self.arrow_parameters()? {
+                Some(params) => self.arrow_function(params, true),
+                None => bail!(self.error(self.peek(), "Expect function after 'async'.")),
+            };
+        }
+        if self._match(&[TokenType::LeftBrace]) {
+            return self.object_literal();
+        }
+        if self._match(&[TokenType::LeftBracket]) {
+            return self.array_literal();
+        }
+        if self._match(&[TokenType::HashBrace]) {
+            let hash = self.previous();
+            return Ok(Expression::Record {
+                hash,
+                object: Box::new(self.object_literal()?),
             });
         }
+        if self._match(&[TokenType::HashBracket]) {
+            let hash = self.previous();
+            return Ok(Expression::Tuple {
+                hash,
+                array: Box::new(self.array_literal()?),
+            });
+        }
+
+        if let Some(params) = self.arrow_parameters()? {
+            return self.arrow_function(params, false);
+        }
+
+        // `match` is only a keyword when `match (...)` is followed by a
+        // brace, which cannot come after a call.
+        if self.check_word("match") && self.is_match_expression() {
+            self.advance();
+            return self.match_expression();
+        }
 
         if self._match(&[TokenType::Identifier]) {
             return Ok(Expression::Variable {
@@ -482,9 +2090,207 @@ impl Parser {
         bail!(self.error(self.peek(), "Expect expression."),)
     }
 
+    fn match_expression(&mut self) -> Result<Expression> {
+        let keyword = self.previous();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'match'.")?;
+        let subject = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after match value.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before match arms.")?;
+
+        let mut arms = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let pattern = self.match_pattern()?;
+            // The guard is parenthesized like an `if` condition, or
+            // `if x => y` would read as an arrow function.
+            let mut guard = None;
+            if self._match(&[TokenType::If]) {
+                self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+                guard = Some(self.expression()?);
+                self.consume(TokenType::RightParen, "Expect ')' after match guard.")?;
+            }
+            self.consume(TokenType::Arrow, "Expect '=>' after match pattern.")?;
+            let body = self.assignment()?;
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body,
+            });
+
+            if !self._match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after match arms.")?;
+        if arms.is_empty() {
+            self.error(keyword.clone(), "Expect at least one match arm.");
+        }
+        Ok(Expression::Match {
+            keyword,
+            subject: Box::new(subject),
+            arms,
+        })
+    }
+
+    fn match_pattern(&mut self) -> Result<MatchPattern> {
+        let mut patterns = vec![self.match_alternative()?];
+        while self._match(&[TokenType::Pipe]) {
+            patterns.push(self.match_alternative()?);
+        }
+        if patterns.len() == 1 {
+            return Ok(patterns.remove(0));
+        }
+        Ok(MatchPattern::Or { patterns })
+    }
+
+    fn match_alternative(&mut self) -> Result<MatchPattern> {
+        if self._match(&[TokenType::Nil]) {
+            return Ok(MatchPattern::Literal {
+                token: self.previous(),
+                value: None,
+            });
+        }
+        if self._match(&[TokenType::True, TokenType::False]) {
+            let token = self.previous();
+            let value = token.token_type == TokenType::True;
+            return Ok(MatchPattern::Literal {
+                token,
+                value: Some(Literal::Bool(value)),
+            });
+        }
+        if self._match(&[TokenType::String]) {
+            let token = self.previous();
+            let value = self.literal_value(&token);
+            return Ok(MatchPattern::Literal {
+                token,
+                value: Some(Literal::String(value)),
+            });
+        }
+        if self._match(&[TokenType::Minus, TokenType::Number, TokenType::BigInt]) {
+            let negative = self.previous().token_type == TokenType::Minus;
+            let token = match negative {
+                true if self._match(&[TokenType::BigInt]) => self.previous(),
+                true => self.consume(TokenType::Number, "Expect number after '-'.")?,
+                false => self.previous(),
+            };
+            let sign = if negative { "-" } else { "" };
+            let text = format!("{}{}", sign, self.literal_value(&token));
+            let value = match token.token_type {
+                TokenType::BigInt => Literal::BigInt(BigInt::parse(&text).unwrap().to_string()),
+                _ => Literal::Number(text.parse().unwrap_or(f64::NAN)),
+            };
+            return Ok(MatchPattern::Literal {
+                token,
+                value: Some(value),
+            });
+        }
+
+        if self._match(&[TokenType::LeftBracket]) {
+            let bracket = self.previous();
+            let mut elements = Vec::new();
+            let mut rest = None;
+            while !self.check(TokenType::RightBracket) && !self.is_at_end() {
+                if self._match(&[TokenType::Ellipsis]) {
+                    let name = self.consume(TokenType::Identifier, "Expect name after '...'.")?;
+                    rest = Some(Box::new(match name.lexeme.as_str() {
+                        "_" => MatchPattern::Wildcard { token: name },
+                        _ => MatchPattern::Binding { name },
+                    }));
+                    break;
+                }
+                elements.push(self.match_pattern()?);
+                if !self._match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+            self.consume(TokenType::RightBracket, "Expect ']' after array pattern.")?;
+            return Ok(MatchPattern::Array {
+                bracket,
+                elements,
+                rest,
+            });
+        }
+
+        if self._match(&[TokenType::LeftBrace]) {
+            let brace = self.previous();
+            let mut properties = Vec::new();
+            while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+                // Only a key that could be a variable name may stand alone.
+                let shorthand = self.check(TokenType::Identifier);
+                let key = match self._match(&[TokenType::String]) {
+                    true => self.previous(),
+                    false => self.property_name("Expect property name.")?,
+                };
+                let name = match key.token_type {
+                    TokenType::String => self.literal_value(&key),
+                    _ => key.lexeme.clone(),
+                };
+                let pattern = if self._match(&[TokenType::Colon]) {
+                    self.match_pattern()?
+                } else if shorthand {
+                    MatchPattern::Binding { name: key.clone() }
+                } else {
+                    bail!(self.error(self.peek(), "Expect ':' after property name."));
+                };
+                properties.push(MatchProperty { key, name, pattern });
+                if !self._match(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+            self.consume(TokenType::RightBrace, "Expect '}' after object pattern.")?;
+            return Ok(MatchPattern::Object { brace, properties });
+        }
+
+        let name = self.consume(TokenType::Identifier, "Expect pattern.")?;
+        if !self.check(TokenType::Dot) {
+            return Ok(match name.lexeme.as_str() {
+                "_" => MatchPattern::Wildcard { token: name },
+                _ => MatchPattern::Binding { name },
+            });
+        }
+
+        // In `a.b.Variant`, everything before the last name is the enum.
+        let mut path = vec![name];
+        while self._match(&[TokenType::Dot]) {
+            path.push(self.property_name("Expect variant name after '.'.")?);
+        }
+        let name = path.pop().unwrap();
+        let mut path = path.into_iter();
+        let mut enumeration = Expression::Variable {
+            name: path.next().unwrap(),
+        };
+        for name in path {
+            enumeration = Expression::Get {
+                object: Box::new(enumeration),
+                name,
+                optional: false,
+            };
+        }
+
+        let mut fields = None;
+        if self._match(&[TokenType::LeftParen]) {
+            let mut patterns = Vec::new();
+            if !self.check(TokenType::RightParen) {
+                loop {
+                    patterns.push(self.match_pattern()?);
+                    if !self._match(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(TokenType::RightParen, "Expect ')' after variant fields.")?;
+            fields = Some(patterns);
+        }
+        Ok(MatchPattern::Variant {
+            enumeration: Box::new(enumeration),
+            name,
+            fields,
+        })
+    }
+
     fn _match(&mut self, types: &[TokenType]) -> bool {
         for token_type in types {
-            if self.check(token_type.clone()) {
+            if self.check(*token_type) {
                 self.advance();
                 return true;
             }
@@ -493,6 +2299,29 @@ impl Parser {
         false
     }
 
+    /// Consumes the `;` that ends a statement. Unless the parser is strict,
+    /// it may be left out wherever one would be inserted.
+    fn consume_semicolon(&mut self, message: &str) -> Result<()> {
+        if self._match(&[TokenType::Semicolon]) || self.can_insert_semicolon() {
+            return Ok(());
+        }
+        bail!(self.error(self.peek(), message))
+    }
+
+    /// Whether a missing `;` would be inserted before the next token, which
+    /// it is when the token starts a new line, closes a block or ends the
+    /// file.
+    fn can_insert_semicolon(&self) -> bool {
+        !self.strict
+            && (self.peek().newline_before || self.check(TokenType::RightBrace) || self.is_at_end())
+    }
+
+    /// Whether a line break ends a restricted production such as `return`
+    /// or `break` before the next token, which would otherwise continue it.
+    fn at_line_break(&self) -> bool {
+        !self.strict && self.peek().newline_before
+    }
+
     fn consume(&mut self, token_type: TokenType, message: &str) -> Result<Token> {
         if self.check(token_type) {
             return Ok(self.advance());
@@ -508,6 +2337,42 @@ impl Parser {
         self.peek().token_type == token_type
     }
 
+    /// Whether the current token is the contextual keyword `word`, such as
+    /// `from` or `as`, which are otherwise ordinary identifiers.
+    fn check_word(&self, word: &str) -> bool {
+        self.check(TokenType::Identifier) && self.peek().lexeme == word
+    }
+
+    fn consume_word(&mut self, word: &str, message: &str) -> Result<Token> {
+        if self.check_word(word) {
+            return Ok(self.advance());
+        }
+
+        bail!(self.error(self.peek(), message))
+    }
+
+    fn check_next(&self, token_type: TokenType) -> bool {
+        self.check_at(1, token_type)
+    }
+
+    /// Whether the token `offset` places after the current one has the
+    /// given type.
+    fn check_at(&self, offset: usize, token_type: TokenType) -> bool {
+        match self.tokens.get(self.current + offset) {
+            Some(token) => token.token_type == token_type,
+            None => false,
+        }
+    }
+
+    fn literal_value(&self, token: &Token) -> String {
+        token
+            .literal
+            .as_ref()
+            .and_then(|literal| literal.get("value"))
+            .cloned()
+            .unwrap_or_default()
+    }
+
     fn advance(&mut self) -> Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -527,7 +2392,8 @@ impl Parser {
         self.tokens[self.current - 1].clone()
     }
 
-    fn error(&self, token: Token, message: &str) -> String {
+    fn error(&mut self, token: Token, message: &str) -> String {
+        self.had_error = true;
         JSPlusPlus::error_token(&token, message);
         format!("{}: {}", token.line, message)
     }
@@ -541,13 +2407,22 @@ impl Parser {
             }
 
             match self.peek().token_type {
-                TokenType::Function
+                TokenType::Break
+                | TokenType::Class
+                | TokenType::Continue
+                | TokenType::Export
+                | TokenType::Import
+                | TokenType::Do
+                | TokenType::Switch
+                | TokenType::Throw
+                | TokenType::Try
+                | TokenType::Function
+                | TokenType::Async
                 | TokenType::Let
                 | TokenType::Const
                 | TokenType::For
                 | TokenType::If
                 | TokenType::While
-                | TokenType::Print
                 | TokenType::Return => return,
                 _ => {}
             }
@@ -0,0 +1,183 @@
+use super::{
+    expression::{Expression, Literal, PropertyKey},
+    token::Token,
+};
+
+/// The target of a declaration, parameter or assignment, which may take the
+/// value it is given apart.
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Identifier {
+        name: Token,
+    },
+    /// A property such as `this.x` or `list[i]`, which only assignments may
+    /// use as a target.
+    Member {
+        expression: Box<Expression>,
+    },
+    Array {
+        bracket: Token,
+        /// `None` marks a skipped element, as in `[a, , b]`.
+        elements: Vec<Option<PatternElement>>,
+        rest: Option<Box<Pattern>>,
+    },
+    Object {
+        brace: Token,
+        properties: Vec<PatternProperty>,
+        rest: Option<Box<Pattern>>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct PatternElement {
+    pub target: Pattern,
+    /// Used when the element is missing or nil.
+    pub default: Option<Expression>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PatternProperty {
+    pub key: PropertyKey,
+    pub target: Pattern,
+    pub default: Option<Expression>,
+}
+
+impl Pattern {
+    /// The variables the pattern binds, in source order.
+    pub fn names(&self) -> Vec<&Token> {
+        let mut names = Vec::new();
+        self.collect_names(&mut names);
+        names
+    }
+
+    fn collect_names<'a>(&'a self, names: &mut Vec<&'a Token>) {
+        match self {
+            Pattern::Identifier { name } => names.push(name),
+            Pattern::Member { expression: _ } => {}
+            Pattern::Array {
+                bracket: _,
+                elements,
+                rest,
+            } => {
+                for element in elements.iter().flatten() {
+                    element.target.collect_names(names);
+                }
+                if let Some(rest) = rest {
+                    rest.collect_names(names);
+                }
+            }
+            Pattern::Object {
+                brace: _,
+                properties,
+                rest,
+            } => {
+                for property in properties {
+                    property.target.collect_names(names);
+                }
+                if let Some(rest) = rest {
+                    rest.collect_names(names);
+                }
+            }
+        }
+    }
+}
+
+/// A pattern in an arm of a `match` expression. Unlike a [`Pattern`] it
+/// tests the value it is given as well as taking it apart.
+#[derive(Clone, Debug)]
+pub enum MatchPattern {
+    /// `_`, which matches anything.
+    Wildcard { token: Token },
+    /// A number, string, boolean or nil, matched by equality.
+    Literal {
+        token: Token,
+        value: Option<Literal>,
+    },
+    /// A name, which matches anything and is bound to it.
+    Binding { name: Token },
+    /// Matches arrays and tuples of exactly as many elements, or at least as
+    /// many when there is a `...rest`.
+    Array {
+        bracket: Token,
+        elements: Vec<MatchPattern>,
+        rest: Option<Box<MatchPattern>>,
+    },
+    /// Matches objects that have all of the properties it names.
+    Object {
+        brace: Token,
+        properties: Vec<MatchProperty>,
+    },
+    /// `Color.Red`, or `Shape.Circle(r)` which also matches the values the
+    /// variant carries.
+    Variant {
+        enumeration: Box<Expression>,
+        name: Token,
+        fields: Option<Vec<MatchPattern>>,
+    },
+    /// `1 | 2`, which matches if any of its alternatives do.
+    Or { patterns: Vec<MatchPattern> },
+}
+
+/// `key: pattern`, or just `key` to bind the property to its own name.
+#[derive(Clone, Debug)]
+pub struct MatchProperty {
+    pub key: Token,
+    pub name: String,
+    pub pattern: MatchPattern,
+}
+
+impl MatchPattern {
+    /// The token that diagnostics about the pattern point at.
+    pub fn token(&self) -> &Token {
+        match self {
+            MatchPattern::Wildcard { token } | MatchPattern::Literal { token, .. } => token,
+            MatchPattern::Binding { name } | MatchPattern::Variant { name, .. } => name,
+            MatchPattern::Array { bracket, .. } => bracket,
+            MatchPattern::Object { brace, .. } => brace,
+            MatchPattern::Or { patterns } => patterns[0].token(),
+        }
+    }
+
+    /// Whether the pattern matches every value.
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            MatchPattern::Wildcard { .. } | MatchPattern::Binding { .. } => true,
+            MatchPattern::Or { patterns } => patterns.iter().any(MatchPattern::is_irrefutable),
+            _ => false,
+        }
+    }
+
+    /// The variables the pattern binds, in source order. The alternatives
+    /// of an `|` pattern bind the same names, so only the first is used.
+    pub fn names(&self) -> Vec<&Token> {
+        let mut names = Vec::new();
+        self.collect_names(&mut names);
+        names
+    }
+
+    fn collect_names<'a>(&'a self, names: &mut Vec<&'a Token>) {
+        match self {
+            MatchPattern::Wildcard { .. } | MatchPattern::Literal { .. } => {}
+            MatchPattern::Binding { name } => names.push(name),
+            MatchPattern::Array { elements, rest, .. } => {
+                for element in elements {
+                    element.collect_names(names);
+                }
+                if let Some(rest) = rest {
+                    rest.collect_names(names);
+                }
+            }
+            MatchPattern::Object { properties, .. } => {
+                for property in properties {
+                    property.pattern.collect_names(names);
+                }
+            }
+            MatchPattern::Variant { fields, .. } => {
+                for field in fields.iter().flatten() {
+                    field.collect_names(names);
+                }
+            }
+            MatchPattern::Or { patterns } => patterns[0].collect_names(names),
+        }
+    }
+}
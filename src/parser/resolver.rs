@@ -0,0 +1,1524 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::JSPlusPlus;
+
+use super::{
+    expression::{Expression, Literal, MatchArm, PropertyKey, Visitor as ExpressionVisitor},
+    pattern::{MatchPattern, Pattern},
+    statement::{
+        Access, Binding, MethodKind, Parameter, Statement, SwitchCase, Visitor as StatementVisitor,
+    },
+    token::{Token, TokenType},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Method,
+    Initializer,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+struct ClassScope {
+    /// Where the class is in `declared`.
+    id: usize,
+    private_names: HashSet<String>,
+    /// Whether `this` is the class rather than an instance, as it is in
+    /// static methods and static field initializers.
+    is_static: bool,
+}
+
+/// A class's named members and their access modifiers, for checking the
+/// accesses whose receiver's class is known before the program runs.
+struct ClassMembers {
+    name: String,
+    /// `None` also when the superclass is only known at runtime.
+    superclass: Option<usize>,
+    access: HashMap<(String, bool), Access>,
+}
+
+struct Label {
+    name: String,
+    is_loop: bool,
+}
+
+/// Static checks that run between parsing and interpretation.
+pub struct Resolver {
+    scopes: Vec<HashMap<String, bool>>,
+    current_function: FunctionType,
+    /// Whether the innermost function body is a generator's, where `yield`
+    /// may be used.
+    in_generator: bool,
+    /// Whether the innermost function body is an async function's, where
+    /// `await` may be used.
+    in_async: bool,
+    current_class: ClassType,
+    classes: Vec<ClassScope>,
+    declared: Vec<ClassMembers>,
+    /// The classes that the names in each scope, and at the top level, are
+    /// bound to.
+    class_bindings: Vec<HashMap<String, usize>>,
+    global_classes: HashMap<String, usize>,
+    exports: HashSet<String>,
+    /// The variants of every enum and how many values each carries, for
+    /// checking the switches and matches over them.
+    enums: HashMap<String, Vec<(String, Option<usize>)>>,
+    labels: Vec<Label>,
+    loop_depth: usize,
+    switch_depth: usize,
+    had_error: bool,
+}
+
+impl Resolver {
+    pub fn new() -> Resolver {
+        Resolver {
+            scopes: Vec::new(),
+            current_function: FunctionType::None,
+            in_generator: false,
+            in_async: false,
+            current_class: ClassType::None,
+            classes: Vec::new(),
+            declared: Vec::new(),
+            class_bindings: Vec::new(),
+            global_classes: HashMap::new(),
+            exports: HashSet::new(),
+            enums: HashMap::new(),
+            labels: Vec::new(),
+            loop_depth: 0,
+            switch_depth: 0,
+            had_error: false,
+        }
+    }
+
+    pub fn had_error(&self) -> bool {
+        self.had_error
+    }
+
+    pub fn resolve(&mut self, statements: &[Statement]) {
+        self.resolve_statements(statements);
+    }
+
+    fn resolve_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.resolve_statement(statement);
+        }
+    }
+
+    fn resolve_statement(&mut self, statement: &Statement) {
+        statement.accept(self);
+    }
+
+    fn resolve_expression(&mut self, expression: &Expression) {
+        expression.accept(self);
+    }
+
+    fn resolve_function(
+        &mut self,
+        params: &[Parameter],
+        body: &[Statement],
+        kind: FunctionType,
+        is_generator: bool,
+        is_async: bool,
+    ) {
+        let enclosing_function = self.current_function;
+        let enclosing_generator = self.in_generator;
+        let enclosing_async = self.in_async;
+        let enclosing_labels = std::mem::take(&mut self.labels);
+        let enclosing_loop_depth = self.loop_depth;
+        let enclosing_switch_depth = self.switch_depth;
+        self.current_function = kind;
+        // Parameter defaults are evaluated before the body starts, so they
+        // may not yield or await.
+        self.in_generator = false;
+        self.in_async = false;
+        self.loop_depth = 0;
+        self.switch_depth = 0;
+
+        self.begin_scope();
+        for param in params {
+            if let Some(default) = &param.default {
+                self.resolve_expression(default);
+            }
+            self.declare_pattern(&param.target);
+            self.resolve_pattern(&param.target, true);
+        }
+        self.in_generator = is_generator;
+        self.in_async = is_async;
+        self.resolve_statements(body);
+        self.end_scope();
+
+        self.current_function = enclosing_function;
+        self.in_generator = enclosing_generator;
+        self.in_async = enclosing_async;
+        self.labels = enclosing_labels;
+        self.loop_depth = enclosing_loop_depth;
+        self.switch_depth = enclosing_switch_depth;
+    }
+
+    fn resolve_function_expression(&mut self, expr: &Expression, kind: FunctionType) {
+        if let Expression::Function {
+            name,
+            params,
+            return_type: _,
+            body,
+            arrow: _,
+            is_generator,
+            is_async,
+        } = expr
+        {
+            self.begin_scope();
+            if let Some(name) = name {
+                self.declare(name);
+                self.define(name);
+            }
+            self.resolve_function(params, body, kind, *is_generator, *is_async);
+            self.end_scope();
+        }
+    }
+
+    fn declare_pattern(&mut self, pattern: &Pattern) {
+        for name in pattern.names() {
+            self.declare(name);
+        }
+    }
+
+    /// Resolves the defaults, computed keys and member targets inside a
+    /// pattern, defining each name it binds once its default has been seen.
+    fn resolve_pattern(&mut self, pattern: &Pattern, define: bool) {
+        match pattern {
+            Pattern::Identifier { name } => {
+                if define {
+                    self.define(name);
+                }
+            }
+            Pattern::Member { expression } => self.resolve_expression(expression),
+            Pattern::Array {
+                bracket: _,
+                elements,
+                rest,
+            } => {
+                for element in elements.iter().flatten() {
+                    if let Some(default) = &element.default {
+                        self.resolve_expression(default);
+                    }
+                    self.resolve_pattern(&element.target, define);
+                }
+                if let Some(rest) = rest {
+                    self.resolve_pattern(rest, define);
+                }
+            }
+            Pattern::Object {
+                brace: _,
+                properties,
+                rest,
+            } => {
+                for property in properties {
+                    if let PropertyKey::Computed(key) = &property.key {
+                        self.resolve_expression(key);
+                    }
+                    if let Some(default) = &property.default {
+                        self.resolve_expression(default);
+                    }
+                    self.resolve_pattern(&property.target, define);
+                }
+                if let Some(rest) = rest {
+                    self.resolve_pattern(rest, define);
+                }
+            }
+        }
+    }
+
+    fn resolve_loop(&mut self, label: &Option<Token>, body: &Statement) {
+        if let Some(label) = label {
+            self.push_label(label, true);
+        }
+        self.loop_depth += 1;
+        self.resolve_statement(body);
+        self.loop_depth -= 1;
+        if label.is_some() {
+            self.labels.pop();
+        }
+    }
+
+    fn push_label(&mut self, label: &Token, is_loop: bool) {
+        if self.labels.iter().any(|l| l.name == label.lexeme) {
+            self.error(
+                label,
+                &format!("Label '{}' has already been declared.", label.lexeme),
+            );
+        }
+        self.labels.push(Label {
+            name: label.lexeme.clone(),
+            is_loop,
+        });
+    }
+
+    /// Checks a `.name` access: private names must be declared by an
+    /// enclosing class, and `private` and `protected` members must be
+    /// visible here when the class of `object` is known.
+    fn resolve_member(&mut self, object: &Expression, name: &Token) {
+        if name.token_type == TokenType::PrivateName {
+            let declared = self
+                .classes
+                .iter()
+                .any(|class| class.private_names.contains(&name.lexeme));
+            if !declared {
+                self.error(
+                    name,
+                    &format!(
+                        "Private field '{}' must be declared in an enclosing class.",
+                        name.lexeme
+                    ),
+                );
+            }
+            return;
+        }
+        if let Some((class, is_static)) = self.class_of(object) {
+            self.check_access(name, class, is_static);
+        }
+    }
+
+    /// Records whether the class member being resolved is static.
+    fn set_static(&mut self, is_static: bool) {
+        if let Some(class) = self.classes.last_mut() {
+            class.is_static = is_static;
+        }
+    }
+
+    /// The class that `object` is an instance of, or is when the flag is
+    /// set, for `this` in a class body, `new C()` and `C`.
+    fn class_of(&self, object: &Expression) -> Option<(usize, bool)> {
+        match object {
+            Expression::This { keyword: _ } if self.current_class != ClassType::None => {
+                self.classes.last().map(|class| (class.id, class.is_static))
+            }
+            Expression::New {
+                keyword: _,
+                callee,
+                arguments: _,
+            } => match callee.as_ref() {
+                Expression::Variable { name } => Some((self.class_binding(name)?, false)),
+                _ => None,
+            },
+            Expression::Variable { name } => Some((self.class_binding(name)?, true)),
+            _ => None,
+        }
+    }
+
+    /// The class declaration that `name` refers to, if it refers to one.
+    fn class_binding(&self, name: &Token) -> Option<usize> {
+        for (scope, classes) in self.scopes.iter().zip(&self.class_bindings).rev() {
+            if scope.contains_key(&name.lexeme) {
+                return classes.get(&name.lexeme).copied();
+            }
+        }
+        self.global_classes.get(&name.lexeme).copied()
+    }
+
+    /// Reports a `private` or `protected` member of `class` used where it
+    /// is not visible: outside the class that declares it, or for
+    /// `protected`, outside the classes between that one and `class`.
+    fn check_access(&mut self, name: &Token, class: usize, is_static: bool) {
+        let key = (name.lexeme.clone(), is_static);
+        let mut current = class;
+        let mut in_subclass = false;
+        loop {
+            let inside = self.classes.iter().any(|scope| scope.id == current);
+            let members = &self.declared[current];
+            let Some(access) = members.access.get(&key) else {
+                in_subclass = in_subclass || inside;
+                match members.superclass {
+                    Some(superclass) => current = superclass,
+                    None => return,
+                }
+                continue;
+            };
+            let message = match access {
+                Access::Public => return,
+                Access::Private if inside => return,
+                Access::Protected if inside || in_subclass => return,
+                Access::Protected => format!(
+                    "Property '{}' is protected and only accessible within class '{}' and its subclasses.",
+                    name.lexeme, members.name
+                ),
+                Access::Private => format!(
+                    "Property '{}' is private and only accessible within class '{}'.",
+                    name.lexeme, members.name
+                ),
+            };
+            self.error(name, &message);
+            return;
+        }
+    }
+
+    /// Imports and exports are only allowed in a module's outermost scope.
+    fn check_top_level(&mut self, keyword: &Token, kind: &str) {
+        if !self.scopes.is_empty() {
+            self.error(
+                keyword,
+                &format!("{} declarations may only appear at top level.", kind),
+            );
+        }
+    }
+
+    fn export(&mut self, name: &Token, exported: &str) {
+        if !self.exports.insert(exported.to_string()) {
+            self.error(name, &format!("Duplicate export of '{}'.", exported));
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+        self.class_bindings.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+        self.class_bindings.pop();
+    }
+
+    fn declare(&mut self, name: &Token) {
+        // Any other declaration hides a class of the same name. A class
+        // declaration binds the name to itself again afterwards.
+        match self.class_bindings.last_mut() {
+            Some(classes) => classes.remove(&name.lexeme),
+            None => self.global_classes.remove(&name.lexeme),
+        };
+        let already_declared = match self.scopes.last_mut() {
+            Some(scope) => scope.insert(name.lexeme.clone(), false).is_some(),
+            None => false,
+        };
+
+        if already_declared {
+            self.error(name, "Already a variable with this name in this scope.");
+        }
+    }
+
+    fn define(&mut self, name: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), true);
+        }
+    }
+
+    /// Warns about a switch without a `default` whose cases are all variants
+    /// of one enum but that leaves some of its variants out.
+    fn check_exhaustive(&self, cases: &[SwitchCase]) {
+        let mut enumeration: Option<&Token> = None;
+        let mut handled = HashSet::new();
+        for case in cases {
+            let Some(Expression::Get {
+                object,
+                name,
+                optional: false,
+            }) = &case.test
+            else {
+                return;
+            };
+            let Expression::Variable { name: enum_name } = object.as_ref() else {
+                return;
+            };
+            match enumeration {
+                Some(token) if token.lexeme != enum_name.lexeme => return,
+                Some(_) => {}
+                None => enumeration = Some(enum_name),
+            }
+            handled.insert(name.lexeme.as_str());
+        }
+
+        if let Some(token) = enumeration {
+            self.check_handled(token, &token.lexeme, "Switch", &handled);
+        }
+    }
+
+    /// Warns about the variants of `enumeration` that are not in `handled`.
+    fn check_handled(
+        &self,
+        token: &Token,
+        enumeration: &str,
+        construct: &str,
+        handled: &HashSet<&str>,
+    ) {
+        let Some(variants) = self.enums.get(enumeration) else {
+            return;
+        };
+        let missing = variants
+            .iter()
+            .map(|(variant, _)| variant.as_str())
+            .filter(|variant| !handled.contains(variant))
+            .collect::<Vec<&str>>();
+        if !missing.is_empty() {
+            JSPlusPlus::warning_token(
+                token,
+                &format!(
+                    "{} over enum '{}' does not handle {}.",
+                    construct,
+                    enumeration,
+                    missing.join(", ")
+                ),
+            );
+        }
+    }
+
+    /// Warns about arms that an earlier arm leaves no values for, and about
+    /// matches over an enum that leave some of its variants out.
+    fn check_arms(&self, keyword: &Token, arms: &[MatchArm]) {
+        for (index, arm) in arms.iter().enumerate() {
+            let shadowed = arms[..index]
+                .iter()
+                .any(|earlier| earlier.guard.is_none() && covers(&earlier.pattern, &arm.pattern));
+            if shadowed {
+                JSPlusPlus::warning_token(arm.pattern.token(), "Unreachable match arm.");
+            }
+        }
+
+        if arms
+            .iter()
+            .any(|arm| arm.guard.is_none() && arm.pattern.is_irrefutable())
+        {
+            return;
+        }
+        let mut enumeration: Option<&Token> = None;
+        let mut handled = HashSet::new();
+        for arm in arms {
+            let alternatives = match &arm.pattern {
+                MatchPattern::Or { patterns } => patterns.iter().collect(),
+                pattern => vec![pattern],
+            };
+            for pattern in alternatives {
+                let MatchPattern::Variant {
+                    enumeration: path,
+                    name,
+                    fields,
+                } = pattern
+                else {
+                    return;
+                };
+                let Expression::Variable { name: enum_name } = path.as_ref() else {
+                    return;
+                };
+                match enumeration {
+                    Some(token) if token.lexeme != enum_name.lexeme => return,
+                    Some(_) => {}
+                    None => enumeration = Some(enum_name),
+                }
+                let catches_all = fields.iter().flatten().all(MatchPattern::is_irrefutable);
+                if arm.guard.is_none() && catches_all {
+                    handled.insert(name.lexeme.as_str());
+                }
+            }
+        }
+        if let Some(token) = enumeration {
+            self.check_handled(keyword, &token.lexeme, "Match", &handled);
+        }
+    }
+
+    fn resolve_match_pattern(&mut self, pattern: &MatchPattern) {
+        match pattern {
+            MatchPattern::Wildcard { token: _ }
+            | MatchPattern::Literal { token: _, value: _ }
+            | MatchPattern::Binding { name: _ } => {}
+            MatchPattern::Array {
+                bracket: _,
+                elements,
+                rest,
+            } => {
+                for element in elements {
+                    self.resolve_match_pattern(element);
+                }
+                if let Some(rest) = rest {
+                    self.resolve_match_pattern(rest);
+                }
+            }
+            MatchPattern::Object {
+                brace: _,
+                properties,
+            } => {
+                for property in properties {
+                    self.resolve_match_pattern(&property.pattern);
+                }
+            }
+            MatchPattern::Variant {
+                enumeration,
+                name,
+                fields,
+            } => {
+                self.resolve_expression(enumeration);
+                if let Expression::Variable { name: enum_name } = enumeration.as_ref() {
+                    let variant = self.enums.get(&enum_name.lexeme).map(|variants| {
+                        variants
+                            .iter()
+                            .find(|(variant, _)| *variant == name.lexeme)
+                            .map(|(_, arity)| *arity)
+                    });
+                    match (variant, fields) {
+                        (Some(None), _) => self.error(
+                            name,
+                            &format!(
+                                "Enum '{}' has no variant '{}'.",
+                                enum_name.lexeme, name.lexeme
+                            ),
+                        ),
+                        (Some(Some(None)), Some(_)) => {
+                            self.error(name, "This variant does not carry any values.")
+                        }
+                        (Some(Some(Some(arity))), Some(fields)) if arity != fields.len() => self
+                            .error(
+                                name,
+                                &format!(
+                                    "Expected {} patterns for this variant but got {}.",
+                                    arity,
+                                    fields.len()
+                                ),
+                            ),
+                        _ => {}
+                    }
+                }
+                for field in fields.iter().flatten() {
+                    self.resolve_match_pattern(field);
+                }
+            }
+            MatchPattern::Or { patterns } => {
+                let mut expected: Vec<&str> = patterns[0]
+                    .names()
+                    .iter()
+                    .map(|name| name.lexeme.as_str())
+                    .collect();
+                expected.sort_unstable();
+                for pattern in patterns {
+                    let mut names: Vec<&str> = pattern
+                        .names()
+                        .iter()
+                        .map(|name| name.lexeme.as_str())
+                        .collect();
+                    names.sort_unstable();
+                    if names != expected {
+                        self.error(
+                            pattern.token(),
+                            "Every alternative in a pattern must bind the same names.",
+                        );
+                    }
+                    self.resolve_match_pattern(pattern);
+                }
+            }
+        }
+    }
+
+    fn error(&mut self, token: &Token, message: &str) {
+        self.had_error = true;
+        JSPlusPlus::error_token(token, message);
+    }
+}
+
+/// Whether every value that `later` matches is also matched by `earlier`.
+fn covers(earlier: &MatchPattern, later: &MatchPattern) -> bool {
+    if earlier.is_irrefutable() {
+        return true;
+    }
+    match (earlier, later) {
+        (_, MatchPattern::Or { patterns }) => patterns.iter().all(|later| covers(earlier, later)),
+        (MatchPattern::Or { patterns }, _) => patterns.iter().any(|earlier| covers(earlier, later)),
+        (
+            MatchPattern::Literal {
+                token: _,
+                value: earlier,
+            },
+            MatchPattern::Literal {
+                token: _,
+                value: later,
+            },
+        ) => match (earlier, later) {
+            (None | Some(Literal::Nil), None | Some(Literal::Nil)) => true,
+            (Some(Literal::Bool(earlier)), Some(Literal::Bool(later))) => earlier == later,
+            (Some(Literal::Number(earlier)), Some(Literal::Number(later))) => earlier == later,
+            (Some(Literal::BigInt(earlier)), Some(Literal::BigInt(later))) => earlier == later,
+            (Some(Literal::String(earlier)), Some(Literal::String(later))) => earlier == later,
+            _ => false,
+        },
+        (
+            MatchPattern::Array {
+                bracket: _,
+                elements: earlier,
+                rest: earlier_rest,
+            },
+            MatchPattern::Array {
+                bracket: _,
+                elements: later,
+                rest: later_rest,
+            },
+        ) => {
+            let lengths = match (earlier_rest, later_rest) {
+                (Some(_), _) => later.len() >= earlier.len(),
+                (None, None) => later.len() == earlier.len(),
+                (None, Some(_)) => false,
+            };
+            lengths
+                && earlier
+                    .iter()
+                    .zip(later)
+                    .all(|(earlier, later)| covers(earlier, later))
+        }
+        (
+            MatchPattern::Object {
+                brace: _,
+                properties: earlier,
+            },
+            MatchPattern::Object {
+                brace: _,
+                properties: later,
+            },
+        ) => earlier.iter().all(|earlier| {
+            later
+                .iter()
+                .any(|later| later.name == earlier.name && covers(&earlier.pattern, &later.pattern))
+        }),
+        (
+            MatchPattern::Variant {
+                enumeration: earlier_enum,
+                name: earlier_name,
+                fields: earlier_fields,
+            },
+            MatchPattern::Variant {
+                enumeration: later_enum,
+                name: later_name,
+                fields: later_fields,
+            },
+        ) => {
+            same_path(earlier_enum, later_enum)
+                && earlier_name.lexeme == later_name.lexeme
+                && match (earlier_fields, later_fields) {
+                    (None, _) => true,
+                    (Some(earlier), None) => earlier.iter().all(MatchPattern::is_irrefutable),
+                    (Some(earlier), Some(later)) => {
+                        earlier.len() == later.len()
+                            && earlier
+                                .iter()
+                                .zip(later)
+                                .all(|(earlier, later)| covers(earlier, later))
+                    }
+                }
+        }
+        _ => false,
+    }
+}
+
+/// Whether two enum paths in patterns, such as `shapes.Shape`, are spelled
+/// the same.
+fn same_path(left: &Expression, right: &Expression) -> bool {
+    match (left, right) {
+        (Expression::Variable { name: left }, Expression::Variable { name: right }) => {
+            left.lexeme == right.lexeme
+        }
+        (
+            Expression::Get {
+                object: left_object,
+                name: left,
+                optional: _,
+            },
+            Expression::Get {
+                object: right_object,
+                name: right,
+                optional: _,
+            },
+        ) => left.lexeme == right.lexeme && same_path(left_object, right_object),
+        _ => false,
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExpressionVisitor<()> for Resolver {
+    fn visit_array_expression(&mut self, expr: &Expression) {
+        if let Expression::Array {
+            bracket: _,
+            elements,
+        } = expr
+        {
+            for element in elements.iter().flatten() {
+                self.resolve_expression(element);
+            }
+        }
+    }
+
+    fn visit_assign_expression(&mut self, expr: &Expression) {
+        if let Expression::Assign { name: _, value } = expr {
+            self.resolve_expression(value);
+        }
+    }
+
+    fn visit_assign_pattern_expression(&mut self, expr: &Expression) {
+        if let Expression::AssignPattern {
+            target,
+            equals: _,
+            value,
+        } = expr
+        {
+            self.resolve_expression(value);
+            self.resolve_pattern(target, false);
+        }
+    }
+
+    fn visit_await_expression(&mut self, expr: &Expression) {
+        if let Expression::Await { keyword, value } = expr {
+            if !self.in_async {
+                self.error(keyword, "Can't use 'await' outside of an async function.");
+            }
+            self.resolve_expression(value);
+        }
+    }
+
+    fn visit_binary_expression(&mut self, expr: &Expression) {
+        if let Expression::Binary {
+            left,
+            operator: _,
+            right,
+        } = expr
+        {
+            self.resolve_expression(left);
+            self.resolve_expression(right);
+        }
+    }
+
+    fn visit_call_expression(&mut self, expr: &Expression) {
+        if let Expression::Call {
+            callee,
+            paren: _,
+            arguments,
+            optional: _,
+        } = expr
+        {
+            self.resolve_expression(callee);
+            for argument in arguments {
+                self.resolve_expression(argument);
+            }
+        }
+    }
+
+    fn visit_function_expression(&mut self, expr: &Expression) {
+        if let Expression::Function { arrow, .. } = expr {
+            // Arrow functions share `this` and `super` with the method they
+            // are written in.
+            let kind = match self.current_function {
+                FunctionType::Method | FunctionType::Initializer if *arrow => self.current_function,
+                _ => FunctionType::Function,
+            };
+            self.resolve_function_expression(expr, kind);
+        }
+    }
+
+    fn visit_get_expression(&mut self, expr: &Expression) {
+        if let Expression::Get {
+            object,
+            name,
+            optional: _,
+        } = expr
+        {
+            self.resolve_expression(object);
+            self.resolve_member(object, name);
+        }
+    }
+
+    fn visit_grouping_expression(&mut self, expr: &Expression) {
+        if let Expression::Grouping { expression } = expr {
+            self.resolve_expression(expression);
+        }
+    }
+
+    fn visit_index_expression(&mut self, expr: &Expression) {
+        if let Expression::Index {
+            object,
+            bracket: _,
+            index,
+            optional: _,
+        } = expr
+        {
+            self.resolve_expression(object);
+            self.resolve_expression(index);
+        }
+    }
+
+    fn visit_literal_expression(&mut self, _expr: &Expression) {}
+
+    fn visit_logical_expression(&mut self, expr: &Expression) {
+        if let Expression::Logical {
+            left,
+            operator: _,
+            right,
+        } = expr
+        {
+            self.resolve_expression(left);
+            self.resolve_expression(right);
+        }
+    }
+
+    fn visit_match_expression(&mut self, expr: &Expression) {
+        if let Expression::Match {
+            keyword,
+            subject,
+            arms,
+        } = expr
+        {
+            self.resolve_expression(subject);
+            for arm in arms {
+                self.begin_scope();
+                self.resolve_match_pattern(&arm.pattern);
+                for name in arm.pattern.names() {
+                    self.declare(name);
+                    self.define(name);
+                }
+                if let Some(guard) = &arm.guard {
+                    self.resolve_expression(guard);
+                }
+                self.resolve_expression(&arm.body);
+                self.end_scope();
+            }
+            self.check_arms(keyword, arms);
+        }
+    }
+
+    fn visit_new_expression(&mut self, expr: &Expression) {
+        if let Expression::New {
+            keyword: _,
+            callee,
+            arguments,
+        } = expr
+        {
+            self.resolve_expression(callee);
+            for argument in arguments {
+                self.resolve_expression(argument);
+            }
+        }
+    }
+
+    fn visit_object_expression(&mut self, expr: &Expression) {
+        if let Expression::Object {
+            brace: _,
+            properties,
+        } = expr
+        {
+            for property in properties {
+                if let PropertyKey::Computed(key) = &property.key {
+                    self.resolve_expression(key);
+                }
+                match &property.value {
+                    // Methods take `this` from the object they are called
+                    // on, which is never the class around the literal.
+                    Expression::Function { arrow: false, .. } => {
+                        let enclosing_class = self.current_class;
+                        self.current_class = ClassType::None;
+                        self.resolve_function_expression(&property.value, FunctionType::Method);
+                        self.current_class = enclosing_class;
+                    }
+                    value => self.resolve_expression(value),
+                }
+            }
+        }
+    }
+
+    fn visit_optional_chain_expression(&mut self, expr: &Expression) {
+        if let Expression::OptionalChain { expression } = expr {
+            self.resolve_expression(expression);
+        }
+    }
+
+    fn visit_record_expression(&mut self, expr: &Expression) {
+        if let Expression::Record { hash: _, object } = expr {
+            self.resolve_expression(object);
+        }
+    }
+
+    fn visit_regex_expression(&mut self, _expr: &Expression) {}
+
+    fn visit_set_expression(&mut self, expr: &Expression) {
+        if let Expression::Set {
+            object,
+            name,
+            value,
+        } = expr
+        {
+            self.resolve_expression(value);
+            self.resolve_expression(object);
+            self.resolve_member(object, name);
+        }
+    }
+
+    fn visit_set_index_expression(&mut self, expr: &Expression) {
+        if let Expression::SetIndex {
+            object,
+            bracket: _,
+            index,
+            value,
+        } = expr
+        {
+            self.resolve_expression(value);
+            self.resolve_expression(object);
+            self.resolve_expression(index);
+        }
+    }
+
+    fn visit_spread_expression(&mut self, expr: &Expression) {
+        if let Expression::Spread {
+            ellipsis: _,
+            expression,
+        } = expr
+        {
+            self.resolve_expression(expression);
+        }
+    }
+
+    fn visit_super_expression(&mut self, expr: &Expression) {
+        if let Expression::Super { keyword, method } = expr {
+            match self.current_class {
+                ClassType::None => self.error(keyword, "Can't use 'super' outside of a class."),
+                ClassType::Class => {
+                    self.error(keyword, "Can't use 'super' in a class with no superclass.")
+                }
+                ClassType::Subclass => {
+                    if method.is_none() && self.current_function != FunctionType::Initializer {
+                        self.error(keyword, "'super' calls are only allowed in constructors.");
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_this_expression(&mut self, expr: &Expression) {
+        if let Expression::This { keyword } = expr {
+            if self.current_class == ClassType::None
+                && self.current_function != FunctionType::Method
+            {
+                self.error(keyword, "Can't use 'this' outside of a class or method.");
+            }
+        }
+    }
+
+    fn visit_tuple_expression(&mut self, expr: &Expression) {
+        if let Expression::Tuple { hash: _, array } = expr {
+            self.resolve_expression(array);
+        }
+    }
+
+    fn visit_unary_expression(&mut self, expr: &Expression) {
+        if let Expression::Unary { operator: _, right } = expr {
+            self.resolve_expression(right);
+        }
+    }
+
+    fn visit_variable_expression(&mut self, expr: &Expression) {
+        if let Expression::Variable { name } = expr {
+            let in_own_initializer = matches!(
+                self.scopes.last().and_then(|scope| scope.get(&name.lexeme)),
+                Some(false)
+            );
+            if in_own_initializer {
+                self.error(name, "Can't read local variable in its own initializer.");
+            }
+        }
+    }
+
+    fn visit_yield_expression(&mut self, expr: &Expression) {
+        if let Expression::Yield {
+            keyword,
+            value,
+            delegate: _,
+        } = expr
+        {
+            if !self.in_generator {
+                self.error(keyword, "Can't use 'yield' outside of a generator.");
+            }
+            if let Some(value) = value {
+                self.resolve_expression(value);
+            }
+        }
+    }
+}
+
+impl StatementVisitor<()> for Resolver {
+    fn visit_block_statement(&mut self, statement: &Statement) {
+        if let Statement::Block { statements } = statement {
+            self.begin_scope();
+            self.resolve_statements(statements);
+            self.end_scope();
+        }
+    }
+
+    fn visit_break_statement(&mut self, statement: &Statement) {
+        if let Statement::Break { keyword, label } = statement {
+            match label {
+                Some(label) => {
+                    if !self.labels.iter().any(|l| l.name == label.lexeme) {
+                        self.error(label, &format!("Undefined label '{}'.", label.lexeme));
+                    }
+                }
+                None => {
+                    if self.loop_depth == 0 && self.switch_depth == 0 {
+                        self.error(keyword, "Can't use 'break' outside of a loop or switch.");
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_class_statement(&mut self, statement: &Statement) {
+        if let Statement::Class {
+            name,
+            superclass,
+            methods,
+            fields,
+        } = statement
+        {
+            let enclosing_class = self.current_class;
+            self.current_class = ClassType::Class;
+
+            let mut parent_class = None;
+            if let Some(Expression::Variable { name: parent }) = superclass {
+                if parent.lexeme == name.lexeme {
+                    self.error(parent, "A class can't inherit from itself.");
+                } else {
+                    parent_class = self.class_binding(parent);
+                }
+            }
+            let access = methods
+                .iter()
+                .filter(|method| method.computed.is_none())
+                .map(|method| (&method.name, method.is_static, method.access))
+                .chain(
+                    fields
+                        .iter()
+                        .map(|field| (&field.name, field.is_static, field.access)),
+                )
+                .filter(|(member, _, _)| member.token_type != TokenType::PrivateName)
+                .map(|(member, is_static, access)| ((member.lexeme.clone(), is_static), access))
+                .collect();
+            let id = self.declared.len();
+            self.declared.push(ClassMembers {
+                name: name.lexeme.clone(),
+                superclass: parent_class,
+                access,
+            });
+
+            self.declare(name);
+            self.define(name);
+            match self.class_bindings.last_mut() {
+                Some(classes) => classes.insert(name.lexeme.clone(), id),
+                None => self.global_classes.insert(name.lexeme.clone(), id),
+            };
+
+            if let Some(superclass) = superclass {
+                self.current_class = ClassType::Subclass;
+                self.resolve_expression(superclass);
+
+                self.begin_scope();
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.insert(String::from("super"), true);
+                }
+            }
+
+            // Private names are visible to the whole class body, so they are
+            // collected before any method is resolved.
+            let mut private_names: HashMap<String, MethodKind> = HashMap::new();
+            let members = methods
+                .iter()
+                .filter(|method| method.computed.is_none())
+                .map(|method| (&method.name, method.kind))
+                .chain(fields.iter().map(|field| (&field.name, MethodKind::Method)))
+                .filter(|(member, _)| member.token_type == TokenType::PrivateName);
+            for (member, kind) in members {
+                match private_names.get(&member.lexeme) {
+                    None => {
+                        private_names.insert(member.lexeme.clone(), kind);
+                    }
+                    // A getter and a setter may share one private name. The
+                    // completed pair is recorded as a plain member.
+                    Some(previous)
+                        if *previous != MethodKind::Method
+                            && kind != MethodKind::Method
+                            && *previous != kind =>
+                    {
+                        private_names.insert(member.lexeme.clone(), MethodKind::Method);
+                    }
+                    Some(_) => self.error(
+                        member,
+                        &format!("Identifier '{}' has already been declared.", member.lexeme),
+                    ),
+                }
+            }
+            self.classes.push(ClassScope {
+                id,
+                private_names: private_names.into_keys().collect(),
+                is_static: false,
+            });
+
+            for method in methods {
+                if let Some(computed) = &method.computed {
+                    self.resolve_expression(computed);
+                }
+            }
+
+            self.begin_scope();
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.insert(String::from("this"), true);
+            }
+
+            // Field initializers run when an instance is created, outside of
+            // any generator or async function around the class.
+            let enclosing_generator = std::mem::replace(&mut self.in_generator, false);
+            let enclosing_async = std::mem::replace(&mut self.in_async, false);
+            for field in fields {
+                if let Some(initializer) = &field.initializer {
+                    self.set_static(field.is_static);
+                    self.resolve_expression(initializer);
+                }
+            }
+            self.in_generator = enclosing_generator;
+            self.in_async = enclosing_async;
+
+            let mut has_constructor = false;
+            for method in methods {
+                let mut kind = FunctionType::Method;
+                if method.name.lexeme == "constructor" && !method.is_static {
+                    if method.kind != MethodKind::Method {
+                        self.error(&method.name, "Class constructor may not be an accessor.");
+                    } else if method.is_generator {
+                        self.error(&method.name, "Class constructor may not be a generator.");
+                    } else if method.is_async {
+                        self.error(&method.name, "Class constructor may not be async.");
+                    } else if has_constructor {
+                        self.error(&method.name, "A class may only have one constructor.");
+                    }
+                    has_constructor = true;
+                    kind = FunctionType::Initializer;
+                }
+                self.set_static(method.is_static);
+                self.resolve_function(
+                    &method.params,
+                    &method.body,
+                    kind,
+                    method.is_generator,
+                    method.is_async,
+                );
+            }
+
+            self.end_scope();
+            self.classes.pop();
+            if superclass.is_some() {
+                self.end_scope();
+            }
+            self.current_class = enclosing_class;
+        }
+    }
+
+    fn visit_continue_statement(&mut self, statement: &Statement) {
+        if let Statement::Continue { keyword, label } = statement {
+            if self.loop_depth == 0 {
+                self.error(keyword, "Can't use 'continue' outside of a loop.");
+                return;
+            }
+            if let Some(label) = label {
+                match self.labels.iter().find(|l| l.name == label.lexeme) {
+                    Some(target) if target.is_loop => {}
+                    Some(_) => self.error(
+                        label,
+                        &format!("Label '{}' does not denote a loop.", label.lexeme),
+                    ),
+                    None => self.error(label, &format!("Undefined label '{}'.", label.lexeme)),
+                }
+            }
+        }
+    }
+
+    fn visit_do_while_statement(&mut self, statement: &Statement) {
+        if let Statement::DoWhile {
+            body,
+            condition,
+            label,
+        } = statement
+        {
+            self.resolve_loop(label, body);
+            self.resolve_expression(condition);
+        }
+    }
+
+    fn visit_enum_statement(&mut self, statement: &Statement) {
+        if let Statement::Enum { name, variants } = statement {
+            self.declare(name);
+            self.define(name);
+
+            let mut names: Vec<(String, Option<usize>)> = Vec::new();
+            for variant in variants {
+                if names.iter().any(|(name, _)| *name == variant.name.lexeme) {
+                    self.error(
+                        &variant.name,
+                        "Already a variant with this name in this enum.",
+                    );
+                }
+                names.push((
+                    variant.name.lexeme.clone(),
+                    variant.fields.as_ref().map(Vec::len),
+                ));
+
+                let mut fields = HashSet::new();
+                for field in variant.fields.iter().flatten() {
+                    if !fields.insert(field.lexeme.as_str()) {
+                        self.error(field, "Already a field with this name in this variant.");
+                    }
+                }
+            }
+            self.enums.insert(name.lexeme.clone(), names);
+        }
+    }
+
+    fn visit_export_statement(&mut self, statement: &Statement) {
+        if let Statement::Export {
+            keyword,
+            declaration,
+            default,
+        } = statement
+        {
+            self.check_top_level(keyword, "Export");
+            for name in declaration.declared_names() {
+                let exported = match default {
+                    true => "default",
+                    false => &name.lexeme,
+                };
+                self.export(name, exported);
+            }
+            self.resolve_statement(declaration);
+        }
+    }
+
+    fn visit_export_default_statement(&mut self, statement: &Statement) {
+        if let Statement::ExportDefault { keyword, value } = statement {
+            self.check_top_level(keyword, "Export");
+            self.export(keyword, "default");
+            self.resolve_expression(value);
+        }
+    }
+
+    fn visit_export_names_statement(&mut self, statement: &Statement) {
+        if let Statement::ExportNames {
+            keyword,
+            specifiers,
+        } = statement
+        {
+            self.check_top_level(keyword, "Export");
+            for specifier in specifiers {
+                self.export(&specifier.alias, &specifier.alias.lexeme);
+            }
+        }
+    }
+
+    fn visit_expression_statement(&mut self, statement: &Statement) {
+        if let Statement::Expression { expression } = statement {
+            self.resolve_expression(expression);
+        }
+    }
+
+    fn visit_for_each_statement(&mut self, statement: &Statement) {
+        if let Statement::ForEach {
+            keyword: _,
+            kind: _,
+            binding,
+            target,
+            iterable,
+            body,
+            label,
+        } = statement
+        {
+            self.resolve_expression(iterable);
+            self.begin_scope();
+            if *binding != Binding::Assign {
+                self.declare_pattern(target);
+            }
+            self.resolve_pattern(target, *binding != Binding::Assign);
+            self.resolve_loop(label, body);
+            self.end_scope();
+        }
+    }
+
+    fn visit_function_statement(&mut self, statement: &Statement) {
+        if let Statement::Function {
+            name,
+            params,
+            return_type: _,
+            body,
+            is_generator,
+            is_async,
+        } = statement
+        {
+            self.declare(name);
+            self.define(name);
+            self.resolve_function(
+                params,
+                body,
+                FunctionType::Function,
+                *is_generator,
+                *is_async,
+            );
+        }
+    }
+
+    fn visit_if_statement(&mut self, statement: &Statement) {
+        if let Statement::If {
+            condition,
+            then_branch,
+            else_branch,
+        } = statement
+        {
+            self.resolve_expression(condition);
+            self.resolve_statement(then_branch);
+            if let Some(else_branch) = else_branch {
+                self.resolve_statement(else_branch);
+            }
+        }
+    }
+
+    fn visit_import_statement(&mut self, statement: &Statement) {
+        if let Statement::Import { keyword, .. } = statement {
+            self.check_top_level(keyword, "Import");
+        }
+    }
+
+    fn visit_interface_statement(&mut self, _statement: &Statement) {}
+
+    fn visit_labeled_statement(&mut self, statement: &Statement) {
+        if let Statement::Labeled { label, body } = statement {
+            self.push_label(label, false);
+            self.resolve_statement(body);
+            self.labels.pop();
+        }
+    }
+
+    fn visit_print_statement(&mut self, statement: &Statement) {
+        if let Statement::Print { expression } = statement {
+            self.resolve_expression(expression);
+        }
+    }
+
+    fn visit_return_statement(&mut self, statement: &Statement) {
+        if let Statement::Return { keyword, value } = statement {
+            if self.current_function == FunctionType::None {
+                self.error(keyword, "Can't return from top-level code.");
+            }
+            if let Some(value) = value {
+                self.resolve_expression(value);
+            }
+        }
+    }
+
+    fn visit_let_statement(&mut self, statement: &Statement) {
+        if let Statement::Let {
+            target,
+            annotation: _,
+            initializer,
+        } = statement
+        {
+            self.declare_pattern(target);
+            if let Some(initializer) = initializer {
+                self.resolve_expression(initializer);
+            }
+            self.resolve_pattern(target, true);
+        }
+    }
+
+    fn visit_const_statement(&mut self, statement: &Statement) {
+        if let Statement::Const {
+            target,
+            annotation: _,
+            initializer,
+        } = statement
+        {
+            self.declare_pattern(target);
+            if let Some(initializer) = initializer {
+                self.resolve_expression(initializer);
+            }
+            self.resolve_pattern(target, true);
+        }
+    }
+
+    fn visit_switch_statement(&mut self, statement: &Statement) {
+        if let Statement::Switch {
+            discriminant,
+            cases,
+        } = statement
+        {
+            self.resolve_expression(discriminant);
+            self.check_exhaustive(cases);
+
+            self.begin_scope();
+            self.switch_depth += 1;
+            for case in cases {
+                if let Some(test) = &case.test {
+                    self.resolve_expression(test);
+                }
+                self.resolve_statements(&case.body);
+            }
+            self.switch_depth -= 1;
+            self.end_scope();
+        }
+    }
+
+    fn visit_throw_statement(&mut self, statement: &Statement) {
+        if let Statement::Throw { keyword: _, value } = statement {
+            self.resolve_expression(value);
+        }
+    }
+
+    fn visit_try_statement(&mut self, statement: &Statement) {
+        if let Statement::Try {
+            try_block,
+            catch_param,
+            catch_block,
+            finally_block,
+        } = statement
+        {
+            self.begin_scope();
+            self.resolve_statements(try_block);
+            self.end_scope();
+
+            if let Some(catch_block) = catch_block {
+                self.begin_scope();
+                if let Some(param) = catch_param {
+                    self.declare(param);
+                    self.define(param);
+                }
+                self.resolve_statements(catch_block);
+                self.end_scope();
+            }
+
+            if let Some(finally_block) = finally_block {
+                self.begin_scope();
+                self.resolve_statements(finally_block);
+                self.end_scope();
+            }
+        }
+    }
+
+    fn visit_type_alias_statement(&mut self, _statement: &Statement) {}
+
+    fn visit_while_statement(&mut self, statement: &Statement) {
+        if let Statement::While {
+            condition,
+            body,
+            increment,
+            label,
+        } = statement
+        {
+            self.resolve_expression(condition);
+            self.resolve_loop(label, body);
+            if let Some(increment) = increment {
+                self.resolve_expression(increment);
+            }
+        }
+    }
+}
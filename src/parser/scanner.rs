@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
+
+use crate::JSPlusPlus;
 
 use super::{
     keyword::KEYWORDS,
@@ -11,6 +13,10 @@ pub(crate) struct Scanner {
     start: usize,
     current: usize,
     line: usize,
+    /// Whether a line break has been passed since the last token.
+    newline: bool,
+    file: Option<Rc<String>>,
+    had_error: bool,
 }
 
 impl Scanner {
@@ -21,9 +27,30 @@ impl Scanner {
             start: 0,
             current: 0,
             line: 1,
+            newline: false,
+            file: None,
+            had_error: false,
         }
     }
 
+    /// Names the module being scanned, which its tokens carry into errors.
+    pub fn set_file(&mut self, file: &str) {
+        self.file = Some(Rc::new(file.to_string()));
+    }
+
+    pub fn had_error(&self) -> bool {
+        self.had_error
+    }
+
+    fn error(&mut self, message: &str) {
+        let location = match &self.file {
+            Some(file) => format!("line {} in {}", self.line, file),
+            None => format!("line {}", self.line),
+        };
+        JSPlusPlus::error(&location, message);
+        self.had_error = true;
+    }
+
     fn is_at_end(&self) -> bool {
         self.current >= self.source.len()
     }
@@ -39,8 +66,10 @@ impl Scanner {
         literal: Option<HashMap<String, String>>,
     ) {
         let text = self.source[self.start..self.current].to_string();
-        self.tokens
-            .push(Token::new(token_type, text, literal, self.line));
+        let mut token = Token::new(token_type, text, literal, self.line);
+        token.newline_before = std::mem::take(&mut self.newline);
+        token.file = self.file.clone();
+        self.tokens.push(token);
     }
 
     fn add_token(&mut self, token_type: TokenType) {
@@ -82,7 +111,7 @@ impl Scanner {
         }
 
         if self.is_at_end() {
-            println!("Unterminated string.");
+            self.error("Unterminated string.");
             return;
         }
 
@@ -95,14 +124,23 @@ impl Scanner {
     }
 
     fn number(&mut self) {
-        while self.peek().is_digit(10) {
+        while self.peek().is_ascii_digit() {
+            self.advance();
+        }
+
+        // An integer ending in `n`, like `123n`, is a BigInt.
+        if self.peek() == 'n' {
+            let value = self.source[self.start..self.current].to_string();
             self.advance();
+            let mut map = HashMap::new();
+            map.insert("value".to_string(), value);
+            return self.add_token_with_literal(TokenType::BigInt, Some(map));
         }
 
-        if self.peek() == '.' && self.peek_next().is_digit(10) {
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             self.advance();
 
-            while self.peek().is_digit(10) {
+            while self.peek().is_ascii_digit() {
                 self.advance();
             }
         }
@@ -113,6 +151,93 @@ impl Scanner {
         self.add_token_with_literal(TokenType::Number, Some(map));
     }
 
+    /// Whether a `/` starts a regex rather than dividing, which it does
+    /// anywhere an operand cannot have just ended, and after a `print` on
+    /// the same line that starts a print statement rather than being read.
+    fn regex_allowed(&self) -> bool {
+        if self.is_print_word() && !self.newline {
+            return true;
+        }
+        !matches!(
+            self.tokens.last().map(|token| token.token_type),
+            Some(
+                TokenType::Identifier
+                    | TokenType::PrivateName
+                    | TokenType::String
+                    | TokenType::Number
+                    | TokenType::BigInt
+                    | TokenType::RightParen
+                    | TokenType::RightBracket
+                    | TokenType::True
+                    | TokenType::False
+                    | TokenType::Nil
+                    | TokenType::This
+                    | TokenType::Super
+            )
+        )
+    }
+
+    /// Whether the last token is a `print` that starts a statement: the
+    /// first token, one on a new line, or one after `;`, a brace, or the
+    /// `)` or `else` before a statement body. Anywhere else, such as in
+    /// `console.print` or `let half = print / 2`, it is just a name.
+    fn is_print_word(&self) -> bool {
+        let mut tokens = self.tokens.iter().rev();
+        let Some(print) = tokens.next() else {
+            return false;
+        };
+        if print.token_type != TokenType::Identifier || print.lexeme != "print" {
+            return false;
+        }
+        match tokens.next() {
+            None => true,
+            Some(previous) => {
+                print.newline_before
+                    || matches!(
+                        previous.token_type,
+                        TokenType::Semicolon
+                            | TokenType::LeftBrace
+                            | TokenType::RightBrace
+                            | TokenType::RightParen
+                            | TokenType::Else
+                    )
+            }
+        }
+    }
+
+    fn regex(&mut self) {
+        // A `/` inside a character class such as `[/]` does not end it.
+        let mut in_class = false;
+        loop {
+            if self.is_at_end() || self.peek() == '\n' {
+                self.error("Unterminated regular expression.");
+                return;
+            }
+            match self.advance() {
+                '\\' if self.peek() != '\n' && !self.is_at_end() => {
+                    self.advance();
+                }
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '/' if !in_class => break,
+                _ => {}
+            }
+        }
+        let pattern = self.source[self.start + 1..self.current - 1].to_string();
+
+        let flags = self.current;
+        while self.peek().is_alphanumeric() {
+            self.advance();
+        }
+        let mut map = HashMap::new();
+        map.insert("value".to_string(), pattern);
+        map.insert(
+            "flags".to_string(),
+            self.source[flags..self.current].to_string(),
+        );
+        self.add_token_with_literal(TokenType::Regex, Some(map));
+    }
+
     fn get_identifier_type(&self) -> TokenType {
         let text = self.source[self.start..self.current].to_string();
 
@@ -124,13 +249,22 @@ impl Scanner {
     }
 
     fn identifier(&mut self) {
-        while self.peek().is_alphanumeric() {
+        while self.peek().is_alphanumeric() || self.peek() == '_' {
             self.advance();
         }
 
         self.add_token(self.get_identifier_type());
     }
 
+    /// Scans a `#name` that refers to a private class member.
+    fn private_name(&mut self) {
+        while self.peek().is_alphanumeric() || self.peek() == '_' {
+            self.advance();
+        }
+
+        self.add_token(TokenType::PrivateName);
+    }
+
     fn scan_token(&mut self) {
         let c = self.advance();
 
@@ -139,23 +273,62 @@ impl Scanner {
             ')' => self.add_token(TokenType::RightParen),
             '{' => self.add_token(TokenType::LeftBrace),
             '}' => self.add_token(TokenType::RightBrace),
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
+            '.' => {
+                if self.peek() == '.' && self.peek_next() == '.' {
+                    self.advance();
+                    self.advance();
+                    self.add_token(TokenType::Ellipsis);
+                } else {
+                    self.add_token(TokenType::Dot);
+                }
+            }
             '-' => self.add_token(TokenType::Minus),
             '+' => self.add_token(TokenType::Plus),
             ';' => self.add_token(TokenType::Semicolon),
-            '*' => self.add_token(TokenType::Star),
+            '*' => {
+                if self.match_char('*') {
+                    self.add_token(TokenType::StarStar);
+                } else {
+                    self.add_token(TokenType::Star);
+                }
+            }
+            '%' => self.add_token(TokenType::Percent),
+            ':' => self.add_token(TokenType::Colon),
+            '|' => self.add_token(TokenType::Pipe),
+            '?' => {
+                if self.match_char('.') {
+                    self.add_token(TokenType::QuestionDot);
+                } else {
+                    self.add_token(TokenType::Question);
+                }
+            }
+            '#' if self.peek().is_alphabetic() || self.peek() == '_' => self.private_name(),
+            '#' if self.match_char('{') => self.add_token(TokenType::HashBrace),
+            '#' if self.match_char('[') => self.add_token(TokenType::HashBracket),
 
             '!' => {
                 if self.match_char('=') {
-                    self.add_token(TokenType::BangEqual);
+                    if self.match_char('=') {
+                        self.add_token(TokenType::BangEqualEqual);
+                    } else {
+                        self.add_token(TokenType::BangEqual);
+                    }
                 } else {
                     self.add_token(TokenType::Bang);
                 }
             }
             '=' => {
                 if self.match_char('=') {
-                    self.add_token(TokenType::EqualEqual);
+                    if self.match_char('=') {
+                        self.add_token(TokenType::EqualEqualEqual);
+                    } else {
+                        self.add_token(TokenType::EqualEqual);
+                    }
+                } else if self.match_char('>') {
+                    self.add_token(TokenType::Arrow);
                 } else {
                     self.add_token(TokenType::Equal);
                 }
@@ -181,30 +354,29 @@ impl Scanner {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                } else if self.regex_allowed() {
+                    self.regex();
                 } else {
                     self.add_token(TokenType::Slash);
                 }
             }
 
             ' ' | '\r' | '\t' => {}
-            '\n' => self.line += 1,
+            '\n' => {
+                self.line += 1;
+                self.newline = true;
+            }
 
             '"' => self.string(),
 
-            'o' => {
-                if self.match_char('r') {
-                    self.add_token(TokenType::Or);
-                }
-            }
-
             _ => {
-                if c.is_digit(10) {
+                if c.is_ascii_digit() {
                     return self.number();
-                } else if c.is_alphanumeric() {
+                } else if c.is_alphanumeric() || c == '_' {
                     return self.identifier();
                 }
 
-                println!("Unexpected character: {}", c);
+                self.error(&format!("Unexpected character: {}", c));
             }
         }
     }
@@ -370,4 +542,125 @@ mod tests {
 
         assert_eq!(tokens.len(), expected_tokens.len());
     }
+
+    #[test]
+    fn test_scanner_private_name() {
+        let expected_tokens = [
+            TokenType::This,
+            TokenType::Dot,
+            TokenType::PrivateName,
+            TokenType::Semicolon,
+            TokenType::EOF,
+        ];
+
+        let source = String::from("this.#count;");
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        for (i, token) in tokens.iter().enumerate() {
+            assert_eq!(token.token_type, expected_tokens[i]);
+        }
+
+        assert_eq!(tokens[2].lexeme, "#count");
+        assert_eq!(tokens.len(), expected_tokens.len());
+    }
+
+    #[test]
+    fn test_scanner_regex() {
+        let expected_tokens = [
+            TokenType::Identifier,
+            TokenType::Equal,
+            TokenType::Identifier,
+            TokenType::Slash,
+            TokenType::Number,
+            TokenType::Slash,
+            TokenType::Regex,
+            TokenType::Dot,
+            TokenType::Identifier,
+            TokenType::Semicolon,
+            TokenType::EOF,
+        ];
+
+        let source = String::from("x = a / 2 / /[/]+/g.source;");
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        for (i, token) in tokens.iter().enumerate() {
+            assert_eq!(token.token_type, expected_tokens[i]);
+        }
+
+        assert_eq!(tokens[6].lexeme, "/[/]+/g");
+        assert_eq!(tokens.len(), expected_tokens.len());
+    }
+
+    #[test]
+    fn test_scanner_regex_after_print() {
+        let expected_tokens = [
+            TokenType::Identifier,
+            TokenType::Regex,
+            TokenType::Dot,
+            TokenType::Identifier,
+            TokenType::LeftParen,
+            TokenType::Identifier,
+            TokenType::RightParen,
+            TokenType::Semicolon,
+            TokenType::Identifier,
+            TokenType::Dot,
+            TokenType::Identifier,
+            TokenType::Slash,
+            TokenType::Number,
+            TokenType::Semicolon,
+            TokenType::EOF,
+        ];
+
+        let source = String::from("print /a+/.test(s);\nx.print / 2;");
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        for (i, token) in tokens.iter().enumerate() {
+            assert_eq!(token.token_type, expected_tokens[i]);
+        }
+
+        assert_eq!(tokens[1].lexeme, "/a+/");
+        assert_eq!(tokens.len(), expected_tokens.len());
+    }
+
+    #[test]
+    fn test_scanner_print_as_name() {
+        let expected_tokens = [
+            TokenType::Let,
+            TokenType::Identifier,
+            TokenType::Equal,
+            TokenType::Identifier,
+            TokenType::Slash,
+            TokenType::Number,
+            TokenType::Semicolon,
+            TokenType::If,
+            TokenType::LeftParen,
+            TokenType::Identifier,
+            TokenType::RightParen,
+            TokenType::Identifier,
+            TokenType::Regex,
+            TokenType::Semicolon,
+            TokenType::EOF,
+        ];
+
+        let source = String::from("let half = print / 2; if (ok) print /a/;");
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens();
+
+        for (i, token) in tokens.iter().enumerate() {
+            assert_eq!(token.token_type, expected_tokens[i]);
+        }
+
+        assert_eq!(tokens.len(), expected_tokens.len());
+        assert!(!scanner.had_error());
+    }
+
+    #[test]
+    fn test_scanner_unterminated_regex() {
+        let mut scanner = Scanner::new(String::from("print /a+;\nprint 1;"));
+        scanner.scan_tokens();
+        assert!(scanner.had_error());
+    }
 }
@@ -1,24 +1,193 @@
+use super::annotation::{TypeAnnotation, TypeMember};
 use super::expression::Expression;
+use super::pattern::Pattern;
 use super::token::Token;
 
+#[derive(Clone, Debug)]
+pub struct Parameter {
+    pub target: Pattern,
+    pub annotation: Option<TypeAnnotation>,
+    pub default: Option<Expression>,
+    /// Set for a trailing `...name` that collects the remaining arguments.
+    pub rest: bool,
+}
+
+/// A `public`, `private` or `protected` modifier. The resolver checks the
+/// accesses whose object's class it knows, and the rest are checked when a
+/// member is read or written, against the class of the object it is on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Access {
+    Public,
+    Private,
+    Protected,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MethodKind {
+    Method,
+    Getter,
+    Setter,
+}
+
+#[derive(Clone, Debug)]
+pub struct Method {
+    /// For a computed name such as `[Symbol.iterator]` this is the opening
+    /// bracket.
+    pub name: Token,
+    pub computed: Option<Expression>,
+    pub kind: MethodKind,
+    /// Set for members declared `static`, which belong to the class itself.
+    pub is_static: bool,
+    pub access: Access,
+    /// Set for `*name() {}`, which returns a generator when called.
+    pub is_generator: bool,
+    /// Set for `async name() {}`, which returns a promise when called.
+    pub is_async: bool,
+    pub params: Vec<Parameter>,
+    pub return_type: Option<TypeAnnotation>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub name: Token,
+    pub annotation: Option<TypeAnnotation>,
+    pub initializer: Option<Expression>,
+    pub is_static: bool,
+    pub access: Access,
+}
+
+/// How the names in a pattern are bound: declared by `let` or `const`, or
+/// assigned to existing variables and properties.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Binding {
+    Let,
+    Const,
+    Assign,
+}
+
+/// Whether a `for` loop walks the keys of an object or the values of an
+/// iterable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ForKind {
+    In,
+    Of,
+}
+
+/// `name as alias` in an import or export list. Without `as` both tokens
+/// are the same.
+#[derive(Clone, Debug)]
+pub struct ModuleSpecifier {
+    pub name: Token,
+    pub alias: Token,
+}
+
+/// One variant of an `enum`, such as `Red` or `Circle(r)`.
+#[derive(Clone, Debug)]
+pub struct Variant {
+    pub name: Token,
+    /// The names of the values a variant like `Circle(r)` carries, or `None`
+    /// for a variant like `Red` that carries nothing.
+    pub fields: Option<Vec<Token>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SwitchCase {
+    /// `None` for the `default` clause.
+    pub test: Option<Expression>,
+    pub body: Vec<Statement>,
+}
+
 #[derive(Clone, Debug)]
 pub enum Statement {
     Block {
         statements: Vec<Statement>,
     },
+    Break {
+        keyword: Token,
+        label: Option<Token>,
+    },
+    Class {
+        name: Token,
+        superclass: Option<Expression>,
+        methods: Vec<Method>,
+        fields: Vec<Field>,
+    },
+    Continue {
+        keyword: Token,
+        label: Option<Token>,
+    },
+    DoWhile {
+        body: Box<Statement>,
+        condition: Expression,
+        label: Option<Token>,
+    },
+    /// `enum Shape { Circle(r), Rect(w, h) }`.
+    Enum {
+        name: Token,
+        variants: Vec<Variant>,
+    },
+    /// `export let x = 1;`, `export function f() {}` and the like, or
+    /// `export default` followed by a named function or class.
+    Export {
+        keyword: Token,
+        declaration: Box<Statement>,
+        default: bool,
+    },
+    ExportDefault {
+        keyword: Token,
+        value: Expression,
+    },
+    ExportNames {
+        keyword: Token,
+        specifiers: Vec<ModuleSpecifier>,
+    },
     Expression {
         expression: Expression,
     },
+    /// `for (const x of iterable)` and `for (const key in object)`.
+    ForEach {
+        keyword: Token,
+        kind: ForKind,
+        binding: Binding,
+        target: Pattern,
+        iterable: Expression,
+        body: Box<Statement>,
+        label: Option<Token>,
+    },
     Function {
         name: Token,
-        params: Vec<Token>,
+        params: Vec<Parameter>,
+        return_type: Option<TypeAnnotation>,
         body: Vec<Statement>,
+        /// Set for `function*`.
+        is_generator: bool,
+        is_async: bool,
     },
     If {
         condition: Expression,
         then_branch: Box<Statement>,
         else_branch: Option<Box<Statement>>,
     },
+    Import {
+        keyword: Token,
+        /// The string token naming the module, and its value.
+        source: Token,
+        path: String,
+        default: Option<Token>,
+        namespace: Option<Token>,
+        specifiers: Vec<ModuleSpecifier>,
+    },
+    /// `interface Name extends Other { member: T; }`.
+    Interface {
+        name: Token,
+        extends: Vec<Token>,
+        members: Vec<TypeMember>,
+    },
+    Labeled {
+        label: Token,
+        body: Box<Statement>,
+    },
     Print {
         expression: Expression,
     },
@@ -27,62 +196,193 @@ pub enum Statement {
         value: Option<Expression>,
     },
     Let {
-        name: Token,
+        target: Pattern,
+        annotation: Option<TypeAnnotation>,
         initializer: Option<Expression>,
     },
+    Switch {
+        discriminant: Expression,
+        cases: Vec<SwitchCase>,
+    },
     Const {
-        name: Token,
+        target: Pattern,
+        annotation: Option<TypeAnnotation>,
         initializer: Option<Expression>,
     },
+    Throw {
+        keyword: Token,
+        value: Expression,
+    },
+    Try {
+        try_block: Vec<Statement>,
+        catch_param: Option<Token>,
+        catch_block: Option<Vec<Statement>>,
+        finally_block: Option<Vec<Statement>>,
+    },
+    /// `type Name = T;`.
+    TypeAlias {
+        name: Token,
+        value: TypeAnnotation,
+    },
     While {
         condition: Expression,
         body: Box<Statement>,
+        increment: Option<Expression>,
+        label: Option<Token>,
     },
 }
 
 pub trait Visitor<T> {
     fn visit_block_statement(&mut self, statement: &Statement) -> T;
+    fn visit_break_statement(&mut self, statement: &Statement) -> T;
+    fn visit_class_statement(&mut self, statement: &Statement) -> T;
+    fn visit_continue_statement(&mut self, statement: &Statement) -> T;
+    fn visit_do_while_statement(&mut self, statement: &Statement) -> T;
+    fn visit_enum_statement(&mut self, statement: &Statement) -> T;
+    fn visit_export_statement(&mut self, statement: &Statement) -> T;
+    fn visit_export_default_statement(&mut self, statement: &Statement) -> T;
+    fn visit_export_names_statement(&mut self, statement: &Statement) -> T;
     fn visit_expression_statement(&mut self, statement: &Statement) -> T;
+    fn visit_for_each_statement(&mut self, statement: &Statement) -> T;
     fn visit_function_statement(&mut self, statement: &Statement) -> T;
     fn visit_if_statement(&mut self, statement: &Statement) -> T;
+    fn visit_import_statement(&mut self, statement: &Statement) -> T;
+    fn visit_interface_statement(&mut self, statement: &Statement) -> T;
+    fn visit_labeled_statement(&mut self, statement: &Statement) -> T;
     fn visit_print_statement(&mut self, statement: &Statement) -> T;
     fn visit_return_statement(&mut self, statement: &Statement) -> T;
     fn visit_let_statement(&mut self, statement: &Statement) -> T;
     fn visit_const_statement(&mut self, statement: &Statement) -> T;
+    fn visit_switch_statement(&mut self, statement: &Statement) -> T;
+    fn visit_throw_statement(&mut self, statement: &Statement) -> T;
+    fn visit_try_statement(&mut self, statement: &Statement) -> T;
+    fn visit_type_alias_statement(&mut self, statement: &Statement) -> T;
     fn visit_while_statement(&mut self, statement: &Statement) -> T;
 }
 
 impl Statement {
+    /// The names bound by a declaration that can be exported.
+    pub fn declared_names(&self) -> Vec<&Token> {
+        match self {
+            Statement::Class { name, .. }
+            | Statement::Enum { name, .. }
+            | Statement::Function { name, .. } => vec![name],
+            Statement::Const { target, .. } | Statement::Let { target, .. } => target.names(),
+            _ => Vec::new(),
+        }
+    }
+
     pub fn accept<T>(&self, visitor: &mut impl Visitor<T>) -> T {
         match self {
             Statement::Block { statements: _ } => visitor.visit_block_statement(self),
+            Statement::Break {
+                keyword: _,
+                label: _,
+            } => visitor.visit_break_statement(self),
+            Statement::Class {
+                name: _,
+                superclass: _,
+                methods: _,
+                fields: _,
+            } => visitor.visit_class_statement(self),
+            Statement::Continue {
+                keyword: _,
+                label: _,
+            } => visitor.visit_continue_statement(self),
+            Statement::DoWhile {
+                body: _,
+                condition: _,
+                label: _,
+            } => visitor.visit_do_while_statement(self),
+            Statement::Enum {
+                name: _,
+                variants: _,
+            } => visitor.visit_enum_statement(self),
+            Statement::Export {
+                keyword: _,
+                declaration: _,
+                default: _,
+            } => visitor.visit_export_statement(self),
+            Statement::ExportDefault {
+                keyword: _,
+                value: _,
+            } => visitor.visit_export_default_statement(self),
+            Statement::ExportNames {
+                keyword: _,
+                specifiers: _,
+            } => visitor.visit_export_names_statement(self),
             Statement::Expression { expression: _ } => visitor.visit_expression_statement(self),
+            Statement::ForEach {
+                keyword: _,
+                kind: _,
+                binding: _,
+                target: _,
+                iterable: _,
+                body: _,
+                label: _,
+            } => visitor.visit_for_each_statement(self),
             Statement::Function {
                 name: _,
                 params: _,
+                return_type: _,
                 body: _,
+                is_generator: _,
+                is_async: _,
             } => visitor.visit_function_statement(self),
             Statement::If {
                 condition: _,
                 then_branch: _,
                 else_branch: _,
             } => visitor.visit_if_statement(self),
+            Statement::Import {
+                keyword: _,
+                source: _,
+                path: _,
+                default: _,
+                namespace: _,
+                specifiers: _,
+            } => visitor.visit_import_statement(self),
+            Statement::Interface {
+                name: _,
+                extends: _,
+                members: _,
+            } => visitor.visit_interface_statement(self),
+            Statement::Labeled { label: _, body: _ } => visitor.visit_labeled_statement(self),
             Statement::Print { expression: _ } => visitor.visit_print_statement(self),
             Statement::Return {
                 keyword: _,
                 value: _,
             } => visitor.visit_return_statement(self),
             Statement::Let {
-                name: _,
+                target: _,
+                annotation: _,
                 initializer: _,
             } => visitor.visit_let_statement(self),
             Statement::Const {
-                name: _,
+                target: _,
+                annotation: _,
                 initializer: _,
             } => visitor.visit_const_statement(self),
+            Statement::Switch {
+                discriminant: _,
+                cases: _,
+            } => visitor.visit_switch_statement(self),
+            Statement::Throw {
+                keyword: _,
+                value: _,
+            } => visitor.visit_throw_statement(self),
+            Statement::Try {
+                try_block: _,
+                catch_param: _,
+                catch_block: _,
+                finally_block: _,
+            } => visitor.visit_try_statement(self),
+            Statement::TypeAlias { name: _, value: _ } => visitor.visit_type_alias_statement(self),
             Statement::While {
                 condition: _,
                 body: _,
+                increment: _,
+                label: _,
             } => visitor.visit_while_statement(self),
         }
     }
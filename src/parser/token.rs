@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, rc::Rc};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum TokenType {
@@ -7,45 +7,83 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
+    Ellipsis,
     Minus,
     Plus,
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Colon,
+    Pipe,
 
     // One or two character tokens.
     Bang,
     BangEqual,
     Equal,
     EqualEqual,
+    Arrow,
     Greater,
     GreaterEqual,
     Less,
     LessEqual,
+    Question,
+    QuestionDot,
+    HashBrace,
+    HashBracket,
+    StarStar,
+
+    // Three character tokens.
+    BangEqualEqual,
+    EqualEqualEqual,
 
     // Literals.
     Identifier,
+    PrivateName,
     String,
     Number,
+    BigInt,
+    Regex,
 
     // Keywords.
     And,
+    Async,
+    Await,
+    Break,
+    Case,
+    Catch,
+    Class,
     Const,
+    Continue,
+    Default,
+    Do,
     Else,
+    Export,
+    Extends,
     False,
+    Finally,
     Function,
     For,
     If,
     Import,
     Let,
+    New,
     Nil,
     Or,
-    Print,
     Return,
+    Static,
+    Super,
+    Switch,
+    This,
+    Throw,
     True,
+    Try,
     While,
+    Yield,
 
     EOF,
 }
@@ -56,6 +94,12 @@ pub struct Token {
     pub lexeme: String,
     pub literal: Option<HashMap<String, String>>,
     pub line: usize,
+    /// Whether a line break comes between this token and the one before
+    /// it, which is what automatic semicolon insertion goes by.
+    pub newline_before: bool,
+    /// The name of the module the token was read from, or `None` in the
+    /// main script.
+    pub file: Option<Rc<String>>,
 }
 
 impl Token {
@@ -70,11 +114,28 @@ impl Token {
             lexeme,
             literal,
             line,
+            newline_before: false,
+            file: None,
         }
     }
 
-    pub fn to_string(&self) -> String {
-        format!("{:?} {} {:?}", self.token_type, self.lexeme, self.literal)
+    /// Where the token is, as errors report it: `line 3`, or
+    /// `line 3 in math.jspp` in a module.
+    pub fn location(&self) -> String {
+        match &self.file {
+            Some(file) => format!("line {} in {}", self.line, file),
+            None => format!("line {}", self.line),
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {} {:?}",
+            self.token_type, self.lexeme, self.literal
+        )
     }
 }
 
@@ -0,0 +1,1550 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use crate::JSPlusPlus;
+
+use super::{
+    annotation::{TypeAnnotation, TypeMember},
+    expression::{Expression, Literal, PropertyKey, Visitor as ExpressionVisitor},
+    pattern::{MatchPattern, Pattern},
+    statement::{Binding, ForKind, Parameter, Statement, Visitor as StatementVisitor},
+    token::{Token, TokenType},
+};
+
+/// What the checker knows about a value. `Any` is what it falls back on when
+/// it cannot tell, and goes with every other type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Any,
+    Nil,
+    Bool,
+    Number,
+    BigInt,
+    String,
+    Array(Box<Type>),
+    Function {
+        params: Vec<Type>,
+        /// How many leading parameters have no default and must be passed.
+        required: usize,
+        /// The type of each extra argument a rest parameter collects.
+        rest: Option<Box<Type>>,
+        result: Box<Type>,
+    },
+    /// The members of an object literal or interface, each with whether it
+    /// may be missing.
+    Object(BTreeMap<String, (Type, bool)>),
+    Union(Vec<Type>),
+}
+
+impl Type {
+    /// The type of a value that has one of `types`.
+    fn union(types: Vec<Type>) -> Type {
+        let mut members: Vec<Type> = Vec::new();
+        for member in types {
+            let flattened = match member {
+                Type::Any => return Type::Any,
+                Type::Union(types) => types,
+                member => vec![member],
+            };
+            for member in flattened {
+                if !members.contains(&member) {
+                    members.push(member);
+                }
+            }
+        }
+        match members.len() {
+            0 => Type::Any,
+            1 => members.remove(0),
+            _ => Type::Union(members),
+        }
+    }
+
+    /// What is left of the type once `?.` has ruled out nil.
+    fn without_nil(self) -> Type {
+        match self {
+            Type::Union(types) => {
+                Type::union(types.into_iter().filter(|t| *t != Type::Nil).collect())
+            }
+            other => other,
+        }
+    }
+
+    fn is_assignable_to(&self, target: &Type) -> bool {
+        match (self, target) {
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::Union(types), _) => types.iter().all(|t| t.is_assignable_to(target)),
+            (_, Type::Union(types)) => types.iter().any(|t| self.is_assignable_to(t)),
+            (Type::Array(element), Type::Array(target)) => element.is_assignable_to(target),
+            // A function may ignore arguments it is given, but must not need
+            // more than it will get.
+            (
+                Type::Function {
+                    params,
+                    required,
+                    rest: _,
+                    result,
+                },
+                Type::Function {
+                    params: target_params,
+                    required: _,
+                    rest: target_rest,
+                    result: target_result,
+                },
+            ) => {
+                (*required <= target_params.len() || target_rest.is_some())
+                    && target_params
+                        .iter()
+                        .zip(params)
+                        .all(|(target, param)| target.is_assignable_to(param))
+                    && result.is_assignable_to(target_result)
+            }
+            (Type::Object(members), Type::Object(targets)) => {
+                targets
+                    .iter()
+                    .all(|(name, (target, optional))| match members.get(name) {
+                        Some((member, _)) => member.is_assignable_to(target),
+                        None => *optional,
+                    })
+            }
+            _ => self == target,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Any => write!(f, "any"),
+            Type::Nil => write!(f, "nil"),
+            Type::Bool => write!(f, "bool"),
+            Type::Number => write!(f, "number"),
+            Type::BigInt => write!(f, "bigint"),
+            Type::String => write!(f, "string"),
+            Type::Array(element) => match element.as_ref() {
+                Type::Union(_) | Type::Function { .. } => write!(f, "({})[]", element),
+                _ => write!(f, "{}[]", element),
+            },
+            Type::Function {
+                params,
+                required: _,
+                rest,
+                result,
+            } => {
+                let mut params: Vec<String> = params.iter().map(|t| t.to_string()).collect();
+                if let Some(rest) = rest {
+                    params.push(format!("...{}[]", rest));
+                }
+                write!(f, "({}) => {}", params.join(", "), result)
+            }
+            Type::Object(members) => {
+                if members.is_empty() {
+                    return write!(f, "{{}}");
+                }
+                let members: Vec<String> = members
+                    .iter()
+                    .map(|(name, (member, optional))| {
+                        let optional = if *optional { "?" } else { "" };
+                        format!("{}{}: {}", name, optional, member)
+                    })
+                    .collect();
+                write!(f, "{{ {} }}", members.join("; "))
+            }
+            Type::Union(types) => {
+                let types: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+                write!(f, "{}", types.join(" | "))
+            }
+        }
+    }
+}
+
+/// A name declared by `type`, `interface`, `class` or `enum`. Aliases and
+/// interfaces are resolved the first time they are used.
+enum TypeDeclaration {
+    Alias(TypeAnnotation),
+    Interface {
+        extends: Vec<Token>,
+        members: Vec<TypeMember>,
+    },
+    /// Classes and enums may be named as types, but their values are not
+    /// checked.
+    Class,
+    /// Being resolved, so that a name used inside its own declaration is
+    /// taken to be `any` instead of recursing forever.
+    Resolving,
+    Resolved(Type),
+}
+
+#[derive(Default)]
+struct Scope {
+    values: HashMap<String, Type>,
+    types: HashMap<String, TypeDeclaration>,
+    /// The signatures of the functions declared in the scope, worked out
+    /// ahead of time so that they can be called before their declaration.
+    signatures: HashMap<String, Type>,
+}
+
+/// Checks annotated programs before they run. Variables without an
+/// annotation take the type of their initializer, and whatever cannot be
+/// worked out is `any`.
+pub struct TypeChecker {
+    scopes: Vec<Scope>,
+    /// The declared return type of the innermost function, if it has one.
+    return_type: Option<Type>,
+    had_error: bool,
+}
+
+impl TypeChecker {
+    pub fn new() -> TypeChecker {
+        TypeChecker {
+            scopes: vec![Scope::default()],
+            return_type: None,
+            had_error: false,
+        }
+    }
+
+    pub fn had_error(&self) -> bool {
+        self.had_error
+    }
+
+    pub fn check(&mut self, statements: &[Statement]) {
+        self.hoist(statements);
+        for statement in statements {
+            statement.accept(self);
+        }
+    }
+
+    fn check_block(&mut self, statements: &[Statement]) {
+        self.scopes.push(Scope::default());
+        self.check(statements);
+        self.scopes.pop();
+    }
+
+    fn evaluate(&mut self, expr: &Expression) -> Type {
+        expr.accept(self)
+    }
+
+    fn error(&mut self, token: &Token, message: &str) {
+        self.had_error = true;
+        JSPlusPlus::error_token(token, message);
+    }
+
+    fn expect_assignable(&mut self, token: &Token, value: &Type, target: &Type) {
+        if !value.is_assignable_to(target) {
+            self.error(
+                token,
+                &format!("Type '{}' is not assignable to type '{}'.", value, target),
+            );
+        }
+    }
+
+    /// Declares the types, classes and functions of a block up front, since
+    /// they can be used before the statement that declares them.
+    fn hoist(&mut self, statements: &[Statement]) {
+        let declarations = statements.iter().map(|statement| match statement {
+            Statement::Export { declaration, .. } => declaration.as_ref(),
+            statement => statement,
+        });
+
+        for statement in declarations.clone() {
+            match statement {
+                Statement::TypeAlias { name, value } => {
+                    self.declare_type(name, TypeDeclaration::Alias(value.clone()))
+                }
+                Statement::Interface {
+                    name,
+                    extends,
+                    members,
+                } => self.declare_type(
+                    name,
+                    TypeDeclaration::Interface {
+                        extends: extends.clone(),
+                        members: members.clone(),
+                    },
+                ),
+                Statement::Class { name, .. } | Statement::Enum { name, .. } => {
+                    self.declare_type(name, TypeDeclaration::Class);
+                    self.declare(name, Type::Any);
+                }
+                _ => {}
+            }
+        }
+
+        // Signatures may use any of the types declared above.
+        for statement in declarations {
+            if let Statement::Function {
+                name,
+                params,
+                return_type,
+                is_generator,
+                is_async,
+                ..
+            } = statement
+            {
+                let signature = self.signature(params, return_type);
+                self.declare(name, callable(&signature, *is_generator || *is_async));
+                self.scope()
+                    .signatures
+                    .insert(name.lexeme.clone(), signature);
+            }
+        }
+    }
+
+    /// Checks the enums named by the variant patterns in `pattern`.
+    fn check_match_pattern(&mut self, pattern: &MatchPattern) {
+        match pattern {
+            MatchPattern::Array { elements, rest, .. } => {
+                for element in elements.iter().chain(rest.as_deref()) {
+                    self.check_match_pattern(element);
+                }
+            }
+            MatchPattern::Object { properties, .. } => {
+                for property in properties {
+                    self.check_match_pattern(&property.pattern);
+                }
+            }
+            MatchPattern::Variant {
+                enumeration,
+                fields,
+                ..
+            } => {
+                self.evaluate(enumeration);
+                for field in fields.iter().flatten() {
+                    self.check_match_pattern(field);
+                }
+            }
+            MatchPattern::Or { patterns } => {
+                for pattern in patterns {
+                    self.check_match_pattern(pattern);
+                }
+            }
+            MatchPattern::Wildcard { .. }
+            | MatchPattern::Literal { .. }
+            | MatchPattern::Binding { .. } => {}
+        }
+    }
+
+    fn scope(&mut self) -> &mut Scope {
+        self.scopes
+            .last_mut()
+            .expect("the global scope is never popped")
+    }
+
+    fn declare(&mut self, name: &Token, value: Type) {
+        self.scope().values.insert(name.lexeme.clone(), value);
+    }
+
+    fn declare_type(&mut self, name: &Token, declaration: TypeDeclaration) {
+        if self.scope().types.contains_key(&name.lexeme) {
+            self.error(name, &format!("Duplicate identifier '{}'.", name.lexeme));
+            return;
+        }
+        self.scope().types.insert(name.lexeme.clone(), declaration);
+    }
+
+    fn lookup(&self, name: &Token) -> Option<Type> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.values.get(&name.lexeme).cloned())
+    }
+
+    /// Declares the names a pattern binds, taking what it can from `value`.
+    fn declare_pattern(&mut self, pattern: &Pattern, value: Type) {
+        match pattern {
+            Pattern::Identifier { name } => self.declare(name, value),
+            Pattern::Member { expression } => {
+                self.evaluate(expression);
+            }
+            Pattern::Array {
+                bracket: _,
+                elements,
+                rest,
+            } => {
+                let element = match &value {
+                    Type::Array(element) => element.as_ref().clone(),
+                    _ => Type::Any,
+                };
+                for element_pattern in elements.iter().flatten() {
+                    let mut element = element.clone();
+                    if let Some(default) = &element_pattern.default {
+                        let default = self.evaluate(default);
+                        element = Type::union(vec![element.without_nil(), default]);
+                    }
+                    self.declare_pattern(&element_pattern.target, element);
+                }
+                if let Some(rest) = rest {
+                    self.declare_pattern(rest, Type::Array(Box::new(element)));
+                }
+            }
+            Pattern::Object {
+                brace: _,
+                properties,
+                rest,
+            } => {
+                for property in properties {
+                    let mut member = match (&value, &property.key) {
+                        (Type::Object(members), PropertyKey::Named { name, token: _ }) => {
+                            match members.get(name) {
+                                Some((member, false)) => member.clone(),
+                                Some((member, true)) => {
+                                    Type::union(vec![member.clone(), Type::Nil])
+                                }
+                                None => Type::Any,
+                            }
+                        }
+                        (_, PropertyKey::Computed(key)) => {
+                            self.evaluate(key);
+                            Type::Any
+                        }
+                        _ => Type::Any,
+                    };
+                    if let Some(default) = &property.default {
+                        let default = self.evaluate(default);
+                        member = Type::union(vec![member.without_nil(), default]);
+                    }
+                    self.declare_pattern(&property.target, member);
+                }
+                if let Some(rest) = rest {
+                    self.declare_pattern(rest, Type::Any);
+                }
+            }
+        }
+    }
+
+    /// Checks a `let` or `const` declaration. Without an annotation the
+    /// variable has the type of its initializer, unless that is nil.
+    fn check_declaration(
+        &mut self,
+        target: &Pattern,
+        annotation: &Option<TypeAnnotation>,
+        initializer: &Option<Expression>,
+    ) {
+        let value = initializer
+            .as_ref()
+            .map(|initializer| self.evaluate(initializer));
+        let declared = match (annotation, value) {
+            (Some(annotation), value) => {
+                let declared = self.resolve(annotation);
+                if let Some(value) = value {
+                    self.expect_assignable(pattern_token(target), &value, &declared);
+                }
+                declared
+            }
+            (None, None | Some(Type::Nil)) => Type::Any,
+            (None, Some(value)) => value,
+        };
+        self.declare_pattern(target, declared);
+    }
+
+    /// Works out the type of a function from its annotations. Parameters
+    /// and results without one are `any`.
+    fn signature(&mut self, params: &[Parameter], return_type: &Option<TypeAnnotation>) -> Type {
+        let mut types = Vec::new();
+        let mut rest = None;
+        for param in params {
+            let param_type = match &param.annotation {
+                Some(annotation) => self.resolve(annotation),
+                None if param.rest => Type::Array(Box::new(Type::Any)),
+                None => Type::Any,
+            };
+            if param.rest {
+                rest = Some(Box::new(match param_type {
+                    Type::Array(element) => *element,
+                    _ => Type::Any,
+                }));
+            } else {
+                types.push(param_type);
+            }
+        }
+        let required = params
+            .iter()
+            .take_while(|param| param.default.is_none() && !param.rest)
+            .count();
+        let result = match return_type {
+            Some(annotation) => self.resolve(annotation),
+            None => Type::Any,
+        };
+        Type::Function {
+            params: types,
+            required,
+            rest,
+            result: Box::new(result),
+        }
+    }
+
+    /// Checks a function body against its signature.
+    fn check_function(&mut self, signature: &Type, params: &[Parameter], body: &[Statement]) {
+        let Type::Function {
+            params: types,
+            required: _,
+            rest,
+            result,
+        } = signature
+        else {
+            panic!("Expected function signature");
+        };
+
+        self.scopes.push(Scope::default());
+        for (index, param) in params.iter().enumerate() {
+            let param_type = match (param.rest, rest) {
+                (true, Some(rest)) => Type::Array(rest.clone()),
+                _ => types.get(index).cloned().unwrap_or(Type::Any),
+            };
+            if let Some(default) = &param.default {
+                let default = self.evaluate(default);
+                self.expect_assignable(pattern_token(&param.target), &default, &param_type);
+            }
+            self.declare_pattern(&param.target, param_type);
+        }
+
+        let return_type = match result.as_ref() {
+            Type::Any => None,
+            result => Some(result.clone()),
+        };
+        let enclosing = std::mem::replace(&mut self.return_type, return_type);
+        self.check(body);
+        self.return_type = enclosing;
+        self.scopes.pop();
+    }
+
+    fn resolve(&mut self, annotation: &TypeAnnotation) -> Type {
+        match annotation {
+            TypeAnnotation::Named { name } => match name.lexeme.as_str() {
+                "any" => Type::Any,
+                "nil" | "void" => Type::Nil,
+                "bool" => Type::Bool,
+                "number" => Type::Number,
+                "bigint" => Type::BigInt,
+                "string" => Type::String,
+                _ => self.lookup_type(name),
+            },
+            TypeAnnotation::Array { element } => Type::Array(Box::new(self.resolve(element))),
+            TypeAnnotation::Union { types } => {
+                let types = types.iter().map(|t| self.resolve(t)).collect();
+                Type::union(types)
+            }
+            TypeAnnotation::Function {
+                paren: _,
+                params,
+                result,
+            } => Type::Function {
+                params: params.iter().map(|param| self.resolve(param)).collect(),
+                required: params.len(),
+                rest: None,
+                result: Box::new(self.resolve(result)),
+            },
+            TypeAnnotation::Object { brace: _, members } => self.object_type(members),
+        }
+    }
+
+    fn object_type(&mut self, members: &[TypeMember]) -> Type {
+        let mut types = BTreeMap::new();
+        for member in members {
+            let member_type = self.resolve(&member.annotation);
+            types.insert(member.name.lexeme.clone(), (member_type, member.optional));
+        }
+        Type::Object(types)
+    }
+
+    fn lookup_type(&mut self, name: &Token) -> Type {
+        let Some(index) = self
+            .scopes
+            .iter()
+            .rposition(|scope| scope.types.contains_key(&name.lexeme))
+        else {
+            self.error(name, &format!("Cannot find name '{}'.", name.lexeme));
+            return Type::Any;
+        };
+
+        let declaration = self.scopes[index]
+            .types
+            .insert(name.lexeme.clone(), TypeDeclaration::Resolving)
+            .expect("the scope was found to declare the type");
+        let (resolved, declaration) = match declaration {
+            TypeDeclaration::Alias(annotation) => {
+                let resolved = self.resolve(&annotation);
+                (resolved.clone(), TypeDeclaration::Resolved(resolved))
+            }
+            TypeDeclaration::Interface { extends, members } => {
+                let resolved = self.interface_type(&extends, &members);
+                (resolved.clone(), TypeDeclaration::Resolved(resolved))
+            }
+            TypeDeclaration::Resolved(resolved) => {
+                (resolved.clone(), TypeDeclaration::Resolved(resolved))
+            }
+            TypeDeclaration::Class => (Type::Any, TypeDeclaration::Class),
+            TypeDeclaration::Resolving => (Type::Any, TypeDeclaration::Resolving),
+        };
+        self.scopes[index]
+            .types
+            .insert(name.lexeme.clone(), declaration);
+        resolved
+    }
+
+    fn interface_type(&mut self, extends: &[Token], members: &[TypeMember]) -> Type {
+        let mut types = BTreeMap::new();
+        for extended in extends {
+            match self.lookup_type(extended) {
+                Type::Object(inherited) => types.extend(inherited),
+                Type::Any => {}
+                _ => self.error(extended, "An interface can only extend an object type."),
+            }
+        }
+        let Type::Object(own) = self.object_type(members) else {
+            panic!("Expected object type");
+        };
+        types.extend(own);
+        Type::Object(types)
+    }
+
+    /// The type of reading `name` from a value of type `object`.
+    fn member_type(&mut self, object: &Type, name: &Token) -> Type {
+        if name.token_type == TokenType::PrivateName {
+            return Type::Any;
+        }
+        match object {
+            Type::Object(members) => match members.get(&name.lexeme) {
+                Some((member, false)) => member.clone(),
+                Some((member, true)) => Type::union(vec![member.clone(), Type::Nil]),
+                None => {
+                    self.error(
+                        name,
+                        &format!(
+                            "Property '{}' does not exist on type '{}'.",
+                            name.lexeme, object
+                        ),
+                    );
+                    Type::Any
+                }
+            },
+            Type::Array(_) if name.lexeme == "length" => Type::Number,
+            Type::Nil => {
+                self.error(name, "Object is possibly 'nil'.");
+                Type::Any
+            }
+            _ => Type::Any,
+        }
+    }
+
+    fn check_arguments(&mut self, paren: &Token, callee: &Type, arguments: &[Type]) {
+        let Type::Function {
+            params,
+            required,
+            rest,
+            result: _,
+        } = callee
+        else {
+            return;
+        };
+
+        if arguments.len() < *required || (rest.is_none() && arguments.len() > params.len()) {
+            let expected = match rest {
+                Some(_) => format!("at least {}", required),
+                None if *required == params.len() => required.to_string(),
+                None => format!("{}-{}", required, params.len()),
+            };
+            self.error(
+                paren,
+                &format!(
+                    "Expected {} arguments, but got {}.",
+                    expected,
+                    arguments.len()
+                ),
+            );
+            return;
+        }
+
+        for (index, argument) in arguments.iter().enumerate() {
+            let param = match (params.get(index), rest) {
+                (Some(param), _) => param,
+                (None, Some(rest)) => rest.as_ref(),
+                (None, None) => break,
+            };
+            if !argument.is_assignable_to(param) {
+                self.error(
+                    paren,
+                    &format!(
+                        "Argument of type '{}' is not assignable to parameter of type '{}'.",
+                        argument, param
+                    ),
+                );
+            }
+        }
+    }
+
+    fn expect_operands(&mut self, operator: &Token, left: &Type, right: &Type, operand: &Type) {
+        if !left.is_assignable_to(operand) || !right.is_assignable_to(operand) {
+            self.error(
+                operator,
+                &format!(
+                    "Operator '{}' cannot be applied to types '{}' and '{}'.",
+                    operator.lexeme, left, right
+                ),
+            );
+        }
+    }
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The type of a function value. Generators and async functions return an
+/// object that wraps their result, so calling them produces `any`.
+fn callable(signature: &Type, wraps_result: bool) -> Type {
+    match signature {
+        Type::Function {
+            params,
+            required,
+            rest,
+            result: _,
+        } if wraps_result => Type::Function {
+            params: params.clone(),
+            required: *required,
+            rest: rest.clone(),
+            result: Box::new(Type::Any),
+        },
+        signature => signature.clone(),
+    }
+}
+
+/// The token to report a declaration's errors at.
+fn pattern_token(pattern: &Pattern) -> &Token {
+    match pattern {
+        Pattern::Identifier { name } => name,
+        Pattern::Array { bracket, .. } => bracket,
+        Pattern::Object { brace, .. } => brace,
+        Pattern::Member { .. } => panic!("Expected declaration pattern"),
+    }
+}
+
+impl ExpressionVisitor<Type> for TypeChecker {
+    fn visit_array_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Array {
+            bracket: _,
+            elements,
+        } = expr
+        {
+            if elements.is_empty() {
+                return Type::Array(Box::new(Type::Any));
+            }
+            let mut types = Vec::new();
+            for element in elements {
+                types.push(match element {
+                    Some(Expression::Spread {
+                        ellipsis: _,
+                        expression,
+                    }) => match self.evaluate(expression) {
+                        Type::Array(element) => *element,
+                        _ => Type::Any,
+                    },
+                    Some(element) => self.evaluate(element),
+                    None => Type::Nil,
+                });
+            }
+            return Type::Array(Box::new(Type::union(types)));
+        }
+        Type::Any
+    }
+
+    fn visit_assign_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Assign { name, value } = expr {
+            let value = self.evaluate(value);
+            if let Some(declared) = self.lookup(name) {
+                self.expect_assignable(name, &value, &declared);
+            }
+            return value;
+        }
+        Type::Any
+    }
+
+    fn visit_assign_pattern_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::AssignPattern {
+            target: _,
+            equals: _,
+            value,
+        } = expr
+        {
+            return self.evaluate(value);
+        }
+        Type::Any
+    }
+
+    fn visit_await_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Await { keyword: _, value } = expr {
+            self.evaluate(value);
+        }
+        Type::Any
+    }
+
+    fn visit_binary_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Binary {
+            left,
+            operator,
+            right,
+        } = expr
+        {
+            let left = self.evaluate(left);
+            let right = self.evaluate(right);
+            // Once either side is a BigInt, both must be.
+            let numeric = match left == Type::BigInt || right == Type::BigInt {
+                true => Type::BigInt,
+                false => Type::Number,
+            };
+            return match operator.token_type {
+                TokenType::Minus
+                | TokenType::Slash
+                | TokenType::Star
+                | TokenType::Percent
+                | TokenType::StarStar => {
+                    self.expect_operands(operator, &left, &right, &numeric);
+                    numeric
+                }
+                TokenType::Plus => match (&left, &right) {
+                    (Type::String, _) | (_, Type::String) => Type::String,
+                    (Type::Number, Type::Number) => Type::Number,
+                    (Type::BigInt, _) | (_, Type::BigInt) => {
+                        self.expect_operands(operator, &left, &right, &Type::BigInt);
+                        Type::BigInt
+                    }
+                    _ => {
+                        let operand = Type::Union(vec![Type::Number, Type::String]);
+                        self.expect_operands(operator, &left, &right, &operand);
+                        Type::Any
+                    }
+                },
+                TokenType::Greater
+                | TokenType::GreaterEqual
+                | TokenType::Less
+                | TokenType::LessEqual => {
+                    let strings = left.is_assignable_to(&Type::String)
+                        && right.is_assignable_to(&Type::String);
+                    if !strings {
+                        self.expect_operands(operator, &left, &right, &numeric);
+                    }
+                    Type::Bool
+                }
+                TokenType::EqualEqual
+                | TokenType::BangEqual
+                | TokenType::EqualEqualEqual
+                | TokenType::BangEqualEqual => Type::Bool,
+                _ => Type::Any,
+            };
+        }
+        Type::Any
+    }
+
+    fn visit_call_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Call {
+            callee,
+            paren,
+            arguments,
+            optional,
+        } = expr
+        {
+            let mut callee = self.evaluate(callee);
+            if *optional {
+                callee = callee.without_nil();
+            }
+            let types: Vec<Type> = arguments
+                .iter()
+                .map(|argument| self.evaluate(argument))
+                .collect();
+
+            return match callee {
+                Type::Function { ref result, .. } => {
+                    // Spread arguments could be any number of values.
+                    let spread = arguments
+                        .iter()
+                        .any(|argument| matches!(argument, Expression::Spread { .. }));
+                    if !spread {
+                        self.check_arguments(paren, &callee, &types);
+                    }
+                    result.as_ref().clone()
+                }
+                Type::Any | Type::Union(_) => Type::Any,
+                callee => {
+                    self.error(
+                        paren,
+                        &format!(
+                            "This expression is not callable. Type '{}' has no call signatures.",
+                            callee
+                        ),
+                    );
+                    Type::Any
+                }
+            };
+        }
+        Type::Any
+    }
+
+    fn visit_function_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Function {
+            name,
+            params,
+            return_type,
+            body,
+            arrow: _,
+            is_generator,
+            is_async,
+        } = expr
+        {
+            let signature = self.signature(params, return_type);
+            let function = callable(&signature, *is_generator || *is_async);
+            self.scopes.push(Scope::default());
+            if let Some(name) = name {
+                self.declare(name, function.clone());
+            }
+            self.check_function(&signature, params, body);
+            self.scopes.pop();
+            return function;
+        }
+        Type::Any
+    }
+
+    fn visit_get_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Get {
+            object,
+            name,
+            optional,
+        } = expr
+        {
+            let mut object = self.evaluate(object);
+            if *optional {
+                object = object.without_nil();
+            }
+            return self.member_type(&object, name);
+        }
+        Type::Any
+    }
+
+    fn visit_grouping_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Grouping { expression } = expr {
+            return self.evaluate(expression);
+        }
+        Type::Any
+    }
+
+    fn visit_index_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Index {
+            object,
+            bracket,
+            index,
+            optional,
+        } = expr
+        {
+            let mut object = self.evaluate(object);
+            if *optional {
+                object = object.without_nil();
+            }
+            self.evaluate(index);
+            return match object {
+                Type::Array(element) => *element,
+                Type::Nil => {
+                    self.error(bracket, "Object is possibly 'nil'.");
+                    Type::Any
+                }
+                _ => Type::Any,
+            };
+        }
+        Type::Any
+    }
+
+    fn visit_literal_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Literal { value } = expr {
+            return match value {
+                None | Some(Literal::Nil) => Type::Nil,
+                Some(Literal::Bool(_)) => Type::Bool,
+                Some(Literal::Number(_)) => Type::Number,
+                Some(Literal::BigInt(_)) => Type::BigInt,
+                Some(Literal::String(_)) => Type::String,
+            };
+        }
+        Type::Any
+    }
+
+    fn visit_logical_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Logical {
+            left,
+            operator: _,
+            right,
+        } = expr
+        {
+            let left = self.evaluate(left);
+            let right = self.evaluate(right);
+            return Type::union(vec![left, right]);
+        }
+        Type::Any
+    }
+
+    fn visit_match_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Match {
+            keyword: _,
+            subject,
+            arms,
+        } = expr
+        {
+            self.evaluate(subject);
+            let mut types = Vec::new();
+            for arm in arms {
+                self.scopes.push(Scope::default());
+                self.check_match_pattern(&arm.pattern);
+                for name in arm.pattern.names() {
+                    self.declare(name, Type::Any);
+                }
+                if let Some(guard) = &arm.guard {
+                    self.evaluate(guard);
+                }
+                types.push(self.evaluate(&arm.body));
+                self.scopes.pop();
+            }
+            return Type::union(types);
+        }
+        Type::Any
+    }
+
+    fn visit_new_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::New {
+            keyword: _,
+            callee,
+            arguments,
+        } = expr
+        {
+            self.evaluate(callee);
+            for argument in arguments {
+                self.evaluate(argument);
+            }
+        }
+        Type::Any
+    }
+
+    fn visit_object_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Object {
+            brace: _,
+            properties,
+        } = expr
+        {
+            // With a computed key the shape of the object is not known.
+            let mut members = Some(BTreeMap::new());
+            for property in properties {
+                let key = match &property.key {
+                    PropertyKey::Named { name, token: _ } => Some(name.clone()),
+                    PropertyKey::Computed(key) => {
+                        self.evaluate(key);
+                        None
+                    }
+                };
+                let value = self.evaluate(&property.value);
+                match (key, members.as_mut()) {
+                    (Some(key), Some(members)) => {
+                        members.insert(key, (value, false));
+                    }
+                    _ => members = None,
+                }
+            }
+            return members.map_or(Type::Any, Type::Object);
+        }
+        Type::Any
+    }
+
+    fn visit_optional_chain_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::OptionalChain { expression } = expr {
+            let value = self.evaluate(expression);
+            return Type::union(vec![value, Type::Nil]);
+        }
+        Type::Any
+    }
+
+    fn visit_record_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Record { hash: _, object } = expr {
+            return self.evaluate(object);
+        }
+        Type::Any
+    }
+
+    fn visit_regex_expression(&mut self, _expr: &Expression) -> Type {
+        Type::Any
+    }
+
+    fn visit_set_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Set {
+            object,
+            name,
+            value,
+        } = expr
+        {
+            let object = self.evaluate(object);
+            let value = self.evaluate(value);
+            if let Type::Object(_) | Type::Nil = object {
+                let member = self.member_type(&object, name);
+                self.expect_assignable(name, &value, &member);
+            }
+            return value;
+        }
+        Type::Any
+    }
+
+    fn visit_set_index_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::SetIndex {
+            object,
+            bracket,
+            index,
+            value,
+        } = expr
+        {
+            let object = self.evaluate(object);
+            self.evaluate(index);
+            let value = self.evaluate(value);
+            if let Type::Array(element) = object {
+                self.expect_assignable(bracket, &value, &element);
+            }
+            return value;
+        }
+        Type::Any
+    }
+
+    fn visit_spread_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Spread {
+            ellipsis: _,
+            expression,
+        } = expr
+        {
+            self.evaluate(expression);
+        }
+        Type::Any
+    }
+
+    fn visit_super_expression(&mut self, _expr: &Expression) -> Type {
+        Type::Any
+    }
+
+    fn visit_this_expression(&mut self, _expr: &Expression) -> Type {
+        Type::Any
+    }
+
+    fn visit_tuple_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Tuple { hash: _, array } = expr {
+            return self.evaluate(array);
+        }
+        Type::Any
+    }
+
+    fn visit_unary_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Unary { operator, right } = expr {
+            let right = self.evaluate(right);
+            if operator.token_type == TokenType::Bang {
+                return Type::Bool;
+            }
+            if right == Type::BigInt {
+                return Type::BigInt;
+            }
+            if !right.is_assignable_to(&Type::Number) {
+                self.error(
+                    operator,
+                    &format!(
+                        "Operator '{}' cannot be applied to type '{}'.",
+                        operator.lexeme, right
+                    ),
+                );
+            }
+            return Type::Number;
+        }
+        Type::Any
+    }
+
+    fn visit_variable_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Variable { name } = expr {
+            return self.lookup(name).unwrap_or(Type::Any);
+        }
+        Type::Any
+    }
+
+    fn visit_yield_expression(&mut self, expr: &Expression) -> Type {
+        if let Expression::Yield {
+            keyword: _,
+            value: Some(value),
+            delegate: _,
+        } = expr
+        {
+            self.evaluate(value);
+        }
+        Type::Any
+    }
+}
+
+impl StatementVisitor<()> for TypeChecker {
+    fn visit_block_statement(&mut self, statement: &Statement) {
+        if let Statement::Block { statements } = statement {
+            self.check_block(statements);
+        }
+    }
+
+    fn visit_break_statement(&mut self, _statement: &Statement) {}
+
+    fn visit_class_statement(&mut self, statement: &Statement) {
+        if let Statement::Class {
+            name,
+            superclass,
+            methods,
+            fields,
+        } = statement
+        {
+            if let Some(superclass) = superclass {
+                self.evaluate(superclass);
+            }
+            if self.lookup(name).is_none() {
+                self.declare(name, Type::Any);
+            }
+
+            for field in fields {
+                let value = field
+                    .initializer
+                    .as_ref()
+                    .map(|initializer| self.evaluate(initializer));
+                if let (Some(annotation), Some(value)) = (&field.annotation, value) {
+                    let declared = self.resolve(annotation);
+                    self.expect_assignable(&field.name, &value, &declared);
+                }
+            }
+            for method in methods {
+                if let Some(computed) = &method.computed {
+                    self.evaluate(computed);
+                }
+                let signature = self.signature(&method.params, &method.return_type);
+                self.check_function(&signature, &method.params, &method.body);
+            }
+        }
+    }
+
+    fn visit_continue_statement(&mut self, _statement: &Statement) {}
+
+    fn visit_do_while_statement(&mut self, statement: &Statement) {
+        if let Statement::DoWhile {
+            body,
+            condition,
+            label: _,
+        } = statement
+        {
+            body.accept(self);
+            self.evaluate(condition);
+        }
+    }
+
+    fn visit_enum_statement(&mut self, statement: &Statement) {
+        if let Statement::Enum { name, variants: _ } = statement {
+            if self.lookup(name).is_none() {
+                self.declare(name, Type::Any);
+            }
+        }
+    }
+
+    fn visit_export_statement(&mut self, statement: &Statement) {
+        if let Statement::Export {
+            keyword: _,
+            declaration,
+            default: _,
+        } = statement
+        {
+            declaration.accept(self);
+        }
+    }
+
+    fn visit_export_default_statement(&mut self, statement: &Statement) {
+        if let Statement::ExportDefault { keyword: _, value } = statement {
+            self.evaluate(value);
+        }
+    }
+
+    fn visit_export_names_statement(&mut self, _statement: &Statement) {}
+
+    fn visit_expression_statement(&mut self, statement: &Statement) {
+        if let Statement::Expression { expression } = statement {
+            self.evaluate(expression);
+        }
+    }
+
+    fn visit_for_each_statement(&mut self, statement: &Statement) {
+        if let Statement::ForEach {
+            keyword: _,
+            kind,
+            binding,
+            target,
+            iterable,
+            body,
+            label: _,
+        } = statement
+        {
+            let iterable = self.evaluate(iterable);
+            let element = match (kind, iterable) {
+                (ForKind::In, _) => Type::String,
+                (ForKind::Of, Type::Array(element)) => *element,
+                (ForKind::Of, _) => Type::Any,
+            };
+
+            self.scopes.push(Scope::default());
+            match binding {
+                Binding::Assign => {
+                    if let Pattern::Identifier { name } = target {
+                        if let Some(declared) = self.lookup(name) {
+                            self.expect_assignable(name, &element, &declared);
+                        }
+                    }
+                }
+                _ => self.declare_pattern(target, element),
+            }
+            body.accept(self);
+            self.scopes.pop();
+        }
+    }
+
+    fn visit_function_statement(&mut self, statement: &Statement) {
+        if let Statement::Function {
+            name,
+            params,
+            return_type,
+            body,
+            is_generator,
+            is_async,
+        } = statement
+        {
+            let signature = match self.scope().signatures.remove(&name.lexeme) {
+                Some(signature) => signature,
+                None => {
+                    let signature = self.signature(params, return_type);
+                    self.declare(name, callable(&signature, *is_generator || *is_async));
+                    signature
+                }
+            };
+            self.check_function(&signature, params, body);
+        }
+    }
+
+    fn visit_if_statement(&mut self, statement: &Statement) {
+        if let Statement::If {
+            condition,
+            then_branch,
+            else_branch,
+        } = statement
+        {
+            self.evaluate(condition);
+            then_branch.accept(self);
+            if let Some(else_branch) = else_branch {
+                else_branch.accept(self);
+            }
+        }
+    }
+
+    fn visit_import_statement(&mut self, statement: &Statement) {
+        if let Statement::Import {
+            keyword: _,
+            source: _,
+            path: _,
+            default,
+            namespace,
+            specifiers,
+        } = statement
+        {
+            // Modules are checked on their own, so imports could be anything.
+            let names = specifiers.iter().map(|specifier| &specifier.alias);
+            for name in default.iter().chain(namespace).chain(names) {
+                self.declare(name, Type::Any);
+            }
+        }
+    }
+
+    fn visit_interface_statement(&mut self, statement: &Statement) {
+        if let Statement::Interface {
+            name,
+            extends,
+            members,
+        } = statement
+        {
+            if !self.scope().types.contains_key(&name.lexeme) {
+                let declaration = TypeDeclaration::Interface {
+                    extends: extends.clone(),
+                    members: members.clone(),
+                };
+                self.declare_type(name, declaration);
+            }
+            // Resolving the interface reports any names in it that do not
+            // exist, even if it is never used.
+            self.lookup_type(name);
+        }
+    }
+
+    fn visit_labeled_statement(&mut self, statement: &Statement) {
+        if let Statement::Labeled { label: _, body } = statement {
+            body.accept(self);
+        }
+    }
+
+    fn visit_print_statement(&mut self, statement: &Statement) {
+        if let Statement::Print { expression } = statement {
+            self.evaluate(expression);
+        }
+    }
+
+    fn visit_return_statement(&mut self, statement: &Statement) {
+        if let Statement::Return { keyword, value } = statement {
+            let value = match value {
+                Some(value) => self.evaluate(value),
+                None => Type::Nil,
+            };
+            if let Some(return_type) = self.return_type.clone() {
+                self.expect_assignable(keyword, &value, &return_type);
+            }
+        }
+    }
+
+    fn visit_let_statement(&mut self, statement: &Statement) {
+        if let Statement::Let {
+            target,
+            annotation,
+            initializer,
+        } = statement
+        {
+            self.check_declaration(target, annotation, initializer);
+        }
+    }
+
+    fn visit_const_statement(&mut self, statement: &Statement) {
+        if let Statement::Const {
+            target,
+            annotation,
+            initializer,
+        } = statement
+        {
+            self.check_declaration(target, annotation, initializer);
+        }
+    }
+
+    fn visit_switch_statement(&mut self, statement: &Statement) {
+        if let Statement::Switch {
+            discriminant,
+            cases,
+        } = statement
+        {
+            self.evaluate(discriminant);
+            self.scopes.push(Scope::default());
+            for case in cases {
+                self.hoist(&case.body);
+            }
+            for case in cases {
+                if let Some(test) = &case.test {
+                    self.evaluate(test);
+                }
+                for statement in &case.body {
+                    statement.accept(self);
+                }
+            }
+            self.scopes.pop();
+        }
+    }
+
+    fn visit_throw_statement(&mut self, statement: &Statement) {
+        if let Statement::Throw { keyword: _, value } = statement {
+            self.evaluate(value);
+        }
+    }
+
+    fn visit_try_statement(&mut self, statement: &Statement) {
+        if let Statement::Try {
+            try_block,
+            catch_param,
+            catch_block,
+            finally_block,
+        } = statement
+        {
+            self.check_block(try_block);
+            if let Some(catch_block) = catch_block {
+                self.scopes.push(Scope::default());
+                if let Some(param) = catch_param {
+                    self.declare(param, Type::Any);
+                }
+                self.check_block(catch_block);
+                self.scopes.pop();
+            }
+            if let Some(finally_block) = finally_block {
+                self.check_block(finally_block);
+            }
+        }
+    }
+
+    fn visit_type_alias_statement(&mut self, statement: &Statement) {
+        if let Statement::TypeAlias { name, value } = statement {
+            if !self.scope().types.contains_key(&name.lexeme) {
+                self.declare_type(name, TypeDeclaration::Alias(value.clone()));
+            }
+            self.lookup_type(name);
+        }
+    }
+
+    fn visit_while_statement(&mut self, statement: &Statement) {
+        if let Statement::While {
+            condition,
+            body,
+            increment,
+            label: _,
+        } = statement
+        {
+            self.evaluate(condition);
+            body.accept(self);
+            if let Some(increment) = increment {
+                self.evaluate(increment);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{scanner::Scanner, typeck::TypeChecker, Parser};
+
+    fn has_type_errors(source: &str) -> bool {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().clone();
+        let mut parser = Parser::new(tokens);
+        let statements = parser.parse();
+        assert!(!parser.had_error());
+
+        let mut checker = TypeChecker::new();
+        checker.check(&statements);
+        checker.had_error()
+    }
+
+    #[test]
+    fn test_type_checker() {
+        assert!(!has_type_errors(
+            "
+            type Id = number | string;
+            interface Named { name: string; }
+            interface Person extends Named { age: number; email?: string; }
+            function greet(person: Person): string { return person.name + person.age; }
+            let id: Id = 1;
+            id = \"one\";
+            let total = sum(1, 2, 3);
+            function sum(...values: number[]): number { return values.length; }
+            const twice = (f: (n: number) => number, n: number): number => f(f(n));
+            print greet({ name: \"Ada\", age: 36 }) + twice((n) => n * 2, total);
+            "
+        ));
+
+        for source in [
+            "let a: number = \"x\";",
+            "let b = 1; b = \"y\";",
+            "function f(x: number): string { return x; }",
+            "function f(x: number) {} f(\"a\");",
+            "function f(x: number) {} f(1, 2);",
+            "interface P { name: string; } let p: P = { nam: \"x\" };",
+            "let p: { name: string } = { name: \"x\" }; print p.age;",
+            "let u: Unknown = 1;",
+            "let xs: number[] = [1, \"a\"];",
+        ] {
+            assert!(has_type_errors(source), "{}", source);
+        }
+    }
+}